@@ -0,0 +1,2 @@
+-- We need to do something, or the migration fails
+select 1;
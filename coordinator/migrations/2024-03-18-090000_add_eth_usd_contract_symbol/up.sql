@@ -0,0 +1 @@
+ALTER TYPE "ContractSymbol_Type" ADD VALUE IF NOT EXISTS 'EthUsd';
@@ -0,0 +1,3 @@
+ALTER TABLE users
+    DROP COLUMN IF EXISTS dead_mans_switch_timeout_secs,
+    DROP COLUMN IF EXISTS last_heartbeat;
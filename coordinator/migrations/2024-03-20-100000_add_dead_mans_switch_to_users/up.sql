@@ -0,0 +1,3 @@
+ALTER TABLE users
+    ADD COLUMN dead_mans_switch_timeout_secs INTEGER,
+    ADD COLUMN last_heartbeat TIMESTAMP WITH TIME ZONE;
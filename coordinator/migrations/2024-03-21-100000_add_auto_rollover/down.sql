@@ -0,0 +1,4 @@
+ALTER TABLE orders
+    DROP COLUMN IF EXISTS auto_rollover;
+ALTER TABLE positions
+    DROP COLUMN IF EXISTS auto_rollover;
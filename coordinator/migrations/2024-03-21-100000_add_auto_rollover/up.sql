@@ -0,0 +1,4 @@
+ALTER TABLE orders
+    ADD COLUMN auto_rollover BOOLEAN NOT NULL DEFAULT true;
+ALTER TABLE positions
+    ADD COLUMN auto_rollover BOOLEAN NOT NULL DEFAULT true;
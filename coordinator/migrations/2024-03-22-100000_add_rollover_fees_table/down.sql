@@ -0,0 +1 @@
+DROP TABLE "rollover_fees";
@@ -0,0 +1,9 @@
+CREATE TABLE "rollover_fees" (
+    id SERIAL PRIMARY KEY NOT NULL,
+    protocol_id UUID UNIQUE NOT NULL REFERENCES dlc_protocols(protocol_id),
+    position_id INTEGER NOT NULL REFERENCES positions(id),
+    trader_pubkey TEXT NOT NULL,
+    fee_sats BIGINT NOT NULL,
+    is_confirmed BOOLEAN NOT NULL DEFAULT false,
+    timestamp TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1 @@
+ALTER TYPE "Protocol_Type_Type" ADD VALUE IF NOT EXISTS 'partial-settle';
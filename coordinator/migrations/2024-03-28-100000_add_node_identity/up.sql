@@ -0,0 +1,7 @@
+-- The node id derived from the seed, to detect starting with the wrong seed. There is only ever
+-- a single row.
+CREATE TABLE "node_identity" (
+    id INTEGER PRIMARY KEY NOT NULL DEFAULT 1 CHECK (id = 1),
+    node_id TEXT NOT NULL,
+    timestamp TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
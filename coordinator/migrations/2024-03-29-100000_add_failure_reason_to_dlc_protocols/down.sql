@@ -0,0 +1 @@
+ALTER TABLE "dlc_protocols" DROP COLUMN "failure_reason";
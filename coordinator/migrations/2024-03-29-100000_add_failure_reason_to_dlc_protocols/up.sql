@@ -0,0 +1,2 @@
+-- Why a dlc protocol failed, to ease investigating incidents.
+ALTER TABLE "dlc_protocols" ADD COLUMN "failure_reason" TEXT;
@@ -1,6 +1,9 @@
 use crate::collaborative_revert;
 use crate::db;
+use crate::dlc_protocol::DlcProtocolState;
+use crate::dlc_protocol::ProtocolKind;
 use crate::parse_dlc_channel_id;
+use crate::position::timeline::ProtocolRecord;
 use crate::routes::empty_string_as_none;
 use crate::routes::AppState;
 use crate::AppError;
@@ -9,16 +12,22 @@ use axum::extract::Path;
 use axum::extract::Query;
 use axum::extract::State;
 use axum::Json;
-use bitcoin::secp256k1::PublicKey;
 use bitcoin::Amount;
 use bitcoin::OutPoint;
 use bitcoin::Transaction;
 use bitcoin::TxOut;
 use commons::CollaborativeRevertCoordinatorRequest;
 use dlc_manager::channel::Channel;
+use dlc_manager::ContractId;
 use dlc_manager::Storage;
+use dlc_messages::oracle_msgs::OracleAttestation;
+use hex::FromHex;
 use lightning::chain::chaininterface::ConfirmationTarget;
+use ln_dlc_node::node::dlc_channel::ForceCloseDlcChannelError;
+use ln_dlc_node::node::ClaimableBalanceDetails;
 use ln_dlc_node::node::NodeInfo;
+use ln_dlc_node::node::NodeStatus;
+use ln_dlc_node::node::PeerDetails;
 use rust_decimal::prelude::FromPrimitive;
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
@@ -30,6 +39,7 @@ use std::sync::Arc;
 use time::OffsetDateTime;
 use tokio::task::spawn_blocking;
 use tracing::instrument;
+use uuid::Uuid;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Balance {
@@ -85,6 +95,12 @@ pub async fn get_balance(State(state): State<Arc<AppState>>) -> Result<Json<Bala
     .map_err(|e| AppError::InternalServerError(format!("Failed to get balance: {e:#}")))?
 }
 
+pub async fn get_claimable_balances(
+    State(state): State<Arc<AppState>>,
+) -> Json<Vec<ClaimableBalanceDetails>> {
+    Json(state.node.inner.claimable_balances())
+}
+
 pub async fn get_utxos(
     State(state): State<Arc<AppState>>,
 ) -> Result<Json<Vec<(OutPoint, TxOut)>>, AppError> {
@@ -360,11 +376,16 @@ pub async fn list_on_chain_transactions(
     )
 }
 
-pub async fn list_peers(State(state): State<Arc<AppState>>) -> Json<Vec<PublicKey>> {
+pub async fn list_peers(State(state): State<Arc<AppState>>) -> Json<Vec<PeerDetails>> {
     let peers = state.node.inner.list_peers();
     Json(peers)
 }
 
+pub async fn get_node_status(State(state): State<Arc<AppState>>) -> Json<NodeStatus> {
+    let status = state.node.inner.status().await;
+    Json(status)
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CloseChannelParams {
     #[serde(default, deserialize_with = "empty_string_as_none")]
@@ -380,14 +401,36 @@ pub async fn close_channel(
     let channel_id = parse_dlc_channel_id(&channel_id_string)
         .map_err(|_| AppError::BadRequest("Provided channel ID was invalid".to_string()))?;
 
-    tracing::info!(channel_id = %channel_id_string, "Attempting to close channel");
-
-    state
-        .node
-        .inner
-        .close_dlc_channel(channel_id, params.force.unwrap_or_default())
-        .await
-        .map_err(|e| AppError::InternalServerError(format!("{e:#}")))?;
+    let is_force_close = params.force.unwrap_or_default();
+
+    tracing::info!(
+        channel_id = %channel_id_string,
+        is_force_close,
+        "Attempting to close channel"
+    );
+
+    if is_force_close {
+        let state = state.clone();
+        spawn_blocking(move || state.node.force_close_dlc_channel(channel_id))
+            .await
+            .expect("task to complete")
+            .map_err(|e| match e {
+                ForceCloseDlcChannelError::UnknownChannel
+                | ForceCloseDlcChannelError::WrongState(_) => {
+                    AppError::BadRequest(format!("{e:#}"))
+                }
+                ForceCloseDlcChannelError::Other(e) => {
+                    AppError::InternalServerError(format!("{e:#}"))
+                }
+            })?;
+    } else {
+        state
+            .node
+            .inner
+            .close_dlc_channel(channel_id, false)
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("{e:#}")))?;
+    }
 
     Ok(())
 }
@@ -435,6 +478,71 @@ pub async fn delete_dlc_channel(
     Ok(())
 }
 
+/// A DLC protocol run on a channel or contract, without its parameters.
+#[derive(Serialize, Debug)]
+pub struct DlcProtocolDetails {
+    pub protocol_id: Uuid,
+    pub previous_protocol_id: Option<Uuid>,
+    pub contract_id: String,
+    pub kind: ProtocolKind,
+    pub state: DlcProtocolState,
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+}
+
+impl From<ProtocolRecord> for DlcProtocolDetails {
+    fn from(value: ProtocolRecord) -> Self {
+        Self {
+            protocol_id: value.id.to_uuid(),
+            previous_protocol_id: value.previous_id.map(|id| id.to_uuid()),
+            contract_id: hex::encode(value.contract_id),
+            kind: value.kind,
+            state: value.state,
+            timestamp: value.timestamp,
+        }
+    }
+}
+
+/// All DLC protocols run on the given DLC channel, oldest first.
+#[instrument(skip_all, err(Debug))]
+pub async fn get_dlc_channel_protocols(
+    Path(channel_id_string): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<DlcProtocolDetails>>, AppError> {
+    let channel_id = parse_dlc_channel_id(&channel_id_string)
+        .map_err(|_| AppError::BadRequest("Provided channel ID was invalid".to_string()))?;
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|e| AppError::InternalServerError(format!("Could not get connection: {e:#}")))?;
+
+    let protocols = db::dlc_protocols::get_by_channel_id(&mut conn, &channel_id)
+        .map_err(|e| AppError::InternalServerError(format!("Could not load protocols: {e:#}")))?;
+
+    Ok(Json(protocols.into_iter().map(Into::into).collect()))
+}
+
+/// All DLC protocols run on the given contract, oldest first.
+#[instrument(skip_all, err(Debug))]
+pub async fn get_contract_protocols(
+    Path(contract_id_string): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<DlcProtocolDetails>>, AppError> {
+    let contract_id = ContractId::from_hex(&contract_id_string)
+        .map_err(|_| AppError::BadRequest("Provided contract ID was invalid".to_string()))?;
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|e| AppError::InternalServerError(format!("Could not get connection: {e:#}")))?;
+
+    let protocols = db::dlc_protocols::get_by_contract_id(&mut conn, &contract_id)
+        .map_err(|e| AppError::InternalServerError(format!("Could not load protocols: {e:#}")))?;
+
+    Ok(Json(protocols.into_iter().map(Into::into).collect()))
+}
+
 /// This function attempts to roll back a DLC channel to the last stable state!
 /// The action is irreversible, only use if you know what you are doing!
 #[instrument(skip_all, err(Debug))]
@@ -479,6 +587,48 @@ pub async fn roll_back_dlc_channel(
     Ok(())
 }
 
+#[derive(Debug, Deserialize)]
+pub struct AttestationOverride {
+    event_id: String,
+    attestation: OracleAttestation,
+}
+
+/// Settle the contracts on an event with an attestation supplied by us, e.g. signed with a backup
+/// oracle key, if the oracle is permanently unavailable.
+pub async fn override_oracle_attestation(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<Confirmation>,
+    Json(AttestationOverride {
+        event_id,
+        attestation,
+    }): Json<AttestationOverride>,
+) -> Result<(), AppError> {
+    if !params.i_know_what_i_am_doing.unwrap_or_default() {
+        let error_message =
+            "Looks like you don't know what you are doing! Go and ask your supervisor for help!";
+        tracing::warn!(error_message);
+        return Err(AppError::BadRequest(error_message.to_string()));
+    }
+
+    tracing::warn!(
+        event_id,
+        oracle_pk = %attestation.oracle_public_key,
+        outcomes = ?attestation.outcomes,
+        "Attempting to override oracle attestation"
+    );
+
+    state
+        .node
+        .inner
+        .override_oracle_attestation(&event_id, attestation)
+        .map_err(|e| {
+            tracing::warn!(event_id, "Rejected oracle attestation override: {e:#}");
+            AppError::BadRequest(format!("Failed to override oracle attestation: {e:#}"))
+        })?;
+
+    Ok(())
+}
+
 #[instrument(skip_all, err(Debug))]
 pub async fn sign_message(
     Path(msg): Path<String>,
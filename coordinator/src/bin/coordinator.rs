@@ -10,14 +10,22 @@ use coordinator::message::spawn_delivering_messages_to_authenticated_users;
 use coordinator::message::NewUserMessage;
 use coordinator::metrics;
 use coordinator::metrics::init_meter;
+use coordinator::metrics::DbPoolEventHandler;
+use coordinator::node::dead_mans_switch;
 use coordinator::node::expired_positions;
+use coordinator::node::identity::check_node_id;
+use coordinator::node::index_price;
+use coordinator::node::payout_reconciliation;
+use coordinator::node::reconcile;
 use coordinator::node::rollover;
 use coordinator::node::storage::NodeStorage;
+use coordinator::node::stuck_protocols;
 use coordinator::node::unrealized_pnl;
 use coordinator::node::Node;
 use coordinator::notifications::NotificationService;
 use coordinator::orderbook::async_match;
 use coordinator::orderbook::collaborative_revert;
+use coordinator::orderbook::throttle::OrderThrottle;
 use coordinator::orderbook::trading;
 use coordinator::routes::router;
 use coordinator::run_migration;
@@ -25,6 +33,7 @@ use coordinator::scheduler::NotificationScheduler;
 use coordinator::settings::Settings;
 use coordinator::storage::CoordinatorTenTenOneStorage;
 use coordinator::trade::websocket::InternalPositionUpdateMessage;
+use coordinator::webhook;
 use diesel::r2d2;
 use diesel::r2d2::ConnectionManager;
 use diesel::PgConnection;
@@ -40,6 +49,8 @@ use std::net::SocketAddr;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::signal::unix::signal;
+use tokio::signal::unix::SignalKind;
 use tokio::sync::broadcast;
 use tokio::task::spawn_blocking;
 use tracing::metadata::LevelFilter;
@@ -47,7 +58,13 @@ use tracing::metadata::LevelFilter;
 const PROCESS_PROMETHEUS_METRICS: Duration = Duration::from_secs(10);
 const PROCESS_INCOMING_DLC_MESSAGES_INTERVAL: Duration = Duration::from_millis(200);
 const EXPIRED_POSITION_SYNC_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const DEAD_MANS_SWITCH_CHECK_INTERVAL: Duration = Duration::from_secs(60);
 const UNREALIZED_PNL_SYNC_INTERVAL: Duration = Duration::from_secs(10 * 60);
+const STUCK_DLC_PROTOCOLS_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const EXPIRED_ORDERS_PRUNE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const ORDER_THROTTLE_PRUNE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const INDEX_PRICE_SYNC_INTERVAL: Duration = Duration::from_secs(30);
+const PAYOUT_RECONCILIATION_INTERVAL: Duration = Duration::from_secs(60 * 60);
 
 const NODE_ALIAS: &str = "10101.finance";
 
@@ -102,6 +119,12 @@ async fn main() -> Result<()> {
     // set up database connection pool
     let manager = ConnectionManager::<PgConnection>::new(opts.database.clone());
     let pool = r2d2::Pool::builder()
+        .max_size(opts.database_pool_size)
+        .connection_timeout(Duration::from_secs(
+            opts.database_connection_timeout_seconds,
+        ))
+        .idle_timeout(opts.database_idle_timeout_seconds.map(Duration::from_secs))
+        .event_handler(Box::new(DbPoolEventHandler))
         .build(manager)
         .expect("Failed to create pool.");
 
@@ -141,6 +164,8 @@ async fn main() -> Result<()> {
         node_event_handler.clone(),
     )?);
 
+    check_node_id(&mut conn, node.info.pubkey, opts.accept_node_id_change)?;
+
     let dlc_handler = DlcHandler::new(pool.clone(), node.clone());
     let _handle = dlc_handler::spawn_handling_outbound_dlc_messages(
         dlc_handler,
@@ -155,12 +180,19 @@ async fn main() -> Result<()> {
 
     let node = Node::new(
         node,
-        running,
         pool.clone(),
         settings.to_node_settings(),
         tx_position_feed.clone(),
     );
 
+    let _handle = webhook::spawn_position_webhook(node.clone(), tx_position_feed.clone());
+
+    if let Err(e) =
+        reconcile::reconcile_positions_with_dlc_channels(&node, settings.reconciliation_policy)
+    {
+        tracing::error!("Failed to reconcile positions with DLC channels: {e:#}");
+    }
+
     // TODO: Pass the tokio metrics into Prometheus
     if let Some(interval) = opts.tokio_metrics_interval_seconds {
         let handle = tokio::runtime::Handle::current();
@@ -176,9 +208,6 @@ async fn main() -> Result<()> {
 
     tokio::spawn({
         let node = node.clone();
-
-        // TODO: Do we still want to be able to update this at runtime?
-        let interval = settings.ln_dlc.on_chain_sync_interval;
         async move {
             loop {
                 if let Err(e) = node.inner.sync_on_chain_wallet().await {
@@ -196,7 +225,9 @@ async fn main() -> Result<()> {
                 .await
                 .expect("task to complete");
 
-                tokio::time::sleep(interval).await;
+                node.inner
+                    .sleep_for_interval(|settings| settings.on_chain_sync_interval)
+                    .await;
             }
         }
     });
@@ -227,6 +258,18 @@ async fn main() -> Result<()> {
         }
     });
 
+    tokio::spawn({
+        let node = node.clone();
+        async move {
+            loop {
+                if let Err(e) = index_price::sync(node.clone()).await {
+                    tracing::error!("Failed to sync index price: {e:#}");
+                }
+                tokio::time::sleep(INDEX_PRICE_SYNC_INTERVAL).await;
+            }
+        }
+    });
+
     tokio::spawn({
         let node = node.clone();
         async move {
@@ -241,10 +284,49 @@ async fn main() -> Result<()> {
         }
     });
 
+    tokio::spawn({
+        let node = node.clone();
+        async move {
+            loop {
+                tokio::time::sleep(STUCK_DLC_PROTOCOLS_CHECK_INTERVAL).await;
+                if let Err(e) = stuck_protocols::check(node.clone()).await {
+                    tracing::error!("Failed to check for stuck DLC protocols: {e:#}");
+                }
+            }
+        }
+    });
+
+    tokio::spawn({
+        let node = node.clone();
+        async move {
+            loop {
+                tokio::time::sleep(PAYOUT_RECONCILIATION_INTERVAL).await;
+                if let Err(e) = payout_reconciliation::check(node.clone()).await {
+                    tracing::error!("Failed to reconcile payouts with computed PnL: {e:#}");
+                }
+            }
+        }
+    });
+
     let (tx_user_feed, _rx) = broadcast::channel::<NewUserMessage>(100);
 
     let (tx_price_feed, _rx) = broadcast::channel(100);
 
+    tokio::spawn({
+        let node = node.clone();
+        let tx_price_feed = tx_price_feed.clone();
+        async move {
+            loop {
+                tokio::time::sleep(EXPIRED_ORDERS_PRUNE_INTERVAL).await;
+                if let Err(e) =
+                    trading::prune_expired_orders(node.clone(), tx_price_feed.clone()).await
+                {
+                    tracing::error!("Failed to prune expired orders: {e:#}");
+                }
+            }
+        }
+    });
+
     let notification_service = NotificationService::new(opts.fcm_api_key.clone());
 
     let (_handle, auth_users_notifier) = spawn_delivering_messages_to_authenticated_users(
@@ -295,8 +377,32 @@ async fn main() -> Result<()> {
         }
     });
 
+    tokio::spawn({
+        let node = node.clone();
+        let tx_price_feed = tx_price_feed.clone();
+        async move {
+            loop {
+                tokio::time::sleep(DEAD_MANS_SWITCH_CHECK_INTERVAL).await;
+                if let Err(e) = dead_mans_switch::halt(node.clone(), tx_price_feed.clone()).await {
+                    tracing::error!("Failed to check dead man's switches! Error: {e:#}");
+                }
+            }
+        }
+    });
+
     let user_backup = SledBackup::new(data_dir.to_string_lossy().to_string());
 
+    let order_throttle = Arc::new(OrderThrottle::default());
+    tokio::spawn({
+        let order_throttle = order_throttle.clone();
+        async move {
+            loop {
+                tokio::time::sleep(ORDER_THROTTLE_PRUNE_INTERVAL).await;
+                order_throttle.prune();
+            }
+        }
+    });
+
     let app = router(
         node.clone(),
         pool.clone(),
@@ -311,6 +417,7 @@ async fn main() -> Result<()> {
         auth_users_notifier.clone(),
         notification_service.get_sender(),
         user_backup,
+        order_throttle,
     );
 
     let sender = notification_service.get_sender();
@@ -347,6 +454,7 @@ async fn main() -> Result<()> {
 
     match axum::Server::bind(&http_address)
         .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(shutdown_signal())
         .await
     {
         Ok(_) => {
@@ -357,5 +465,17 @@ async fn main() -> Result<()> {
         }
     }
 
+    running.shutdown().await?;
+
     Ok(())
 }
+
+/// Resolves once the process receives SIGTERM or SIGINT.
+async fn shutdown_signal() {
+    let mut sigterm = signal(SignalKind::terminate()).expect("to be able to listen for SIGTERM");
+
+    tokio::select! {
+        _ = sigterm.recv() => tracing::info!("Received SIGTERM"),
+        _ = tokio::signal::ctrl_c() => tracing::info!("Received SIGINT"),
+    }
+}
@@ -42,6 +42,18 @@ pub struct Opts {
     )]
     pub database: String,
 
+    /// The maximum number of connections in the database connection pool.
+    #[clap(long, default_value = "10")]
+    pub database_pool_size: u32,
+
+    /// How long to wait for a connection from the database connection pool before failing.
+    #[clap(long, default_value = "30")]
+    pub database_connection_timeout_seconds: u64,
+
+    /// If specified, idle connections are closed after the given number of seconds.
+    #[clap(long)]
+    pub database_idle_timeout_seconds: Option<u64>,
+
     /// The address to connect to the Electrs API.
     #[clap(long, default_value = "http://localhost:3000", aliases = ["esplora"])]
     pub electrs: String,
@@ -78,6 +90,11 @@ pub struct Opts {
         default_value = "16f88cf7d21e6c0f46bcbc983a4e3b19726c6c98858cc31c83551a88fde171c0"
     )]
     pub oracle_pubkey: String,
+
+    /// Start even if the node id derived from the seed differs from the one the coordinator was
+    /// last started with. Only set this flag if the seed was changed on purpose.
+    #[clap(long)]
+    pub accept_node_id_change: bool,
 }
 
 #[derive(Debug, Clone, Copy, clap::ValueEnum)]
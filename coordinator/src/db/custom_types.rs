@@ -28,6 +28,7 @@ impl ToSql<ContractSymbolType, Pg> for ContractSymbol {
     fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
         match *self {
             ContractSymbol::BtcUsd => out.write_all(b"BtcUsd")?,
+            ContractSymbol::EthUsd => out.write_all(b"EthUsd")?,
         }
         Ok(IsNull::No)
     }
@@ -37,6 +38,7 @@ impl FromSql<ContractSymbolType, Pg> for ContractSymbol {
     fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
         match bytes.as_bytes() {
             b"BtcUsd" => Ok(ContractSymbol::BtcUsd),
+            b"EthUsd" => Ok(ContractSymbol::EthUsd),
             _ => Err("Unrecognized enum variant".into()),
         }
     }
@@ -211,6 +213,7 @@ impl ToSql<ProtocolTypeType, Pg> for DlcProtocolType {
         match *self {
             DlcProtocolType::Open => out.write_all(b"open")?,
             DlcProtocolType::Settle => out.write_all(b"settle")?,
+            DlcProtocolType::PartialSettle => out.write_all(b"partial-settle")?,
             DlcProtocolType::Renew => out.write_all(b"renew")?,
             DlcProtocolType::Rollover => out.write_all(b"rollover")?,
             DlcProtocolType::Close => out.write_all(b"close")?,
@@ -225,6 +228,7 @@ impl FromSql<ProtocolTypeType, Pg> for DlcProtocolType {
         match bytes.as_bytes() {
             b"open" => Ok(DlcProtocolType::Open),
             b"settle" => Ok(DlcProtocolType::Settle),
+            b"partial-settle" => Ok(DlcProtocolType::PartialSettle),
             b"renew" => Ok(DlcProtocolType::Renew),
             b"rollover" => Ok(DlcProtocolType::Rollover),
             b"close" => Ok(DlcProtocolType::Close),
@@ -1,6 +1,8 @@
 use crate::db;
 use crate::dlc_protocol;
 use crate::dlc_protocol::ProtocolId;
+use crate::dlc_protocol::ProtocolKind;
+use crate::position::timeline::ProtocolRecord;
 use crate::schema::dlc_protocols;
 use crate::schema::sql_types::ProtocolStateType;
 use crate::schema::sql_types::ProtocolTypeType;
@@ -45,6 +47,7 @@ pub(crate) enum DlcProtocolType {
     Open,
     Renew,
     Settle,
+    PartialSettle,
     Close,
     ForceClose,
     Rollover,
@@ -72,6 +75,7 @@ pub(crate) struct DlcProtocol {
     pub trader_pubkey: String,
     pub timestamp: OffsetDateTime,
     pub protocol_type: DlcProtocolType,
+    pub failure_reason: Option<String>,
 }
 
 pub(crate) fn get_dlc_protocol(
@@ -95,6 +99,10 @@ pub(crate) fn get_dlc_protocol(
             let trade_params = db::trade_params::get(conn, protocol_id)?;
             dlc_protocol::DlcProtocolType::Settle { trade_params }
         }
+        DlcProtocolType::PartialSettle => {
+            let trade_params = db::trade_params::get(conn, protocol_id)?;
+            dlc_protocol::DlcProtocolType::PartialSettle { trade_params }
+        }
         DlcProtocolType::Close => dlc_protocol::DlcProtocolType::Close {
             trader: PublicKey::from_str(&dlc_protocol.trader_pubkey).expect("valid public key"),
         },
@@ -114,25 +122,137 @@ pub(crate) fn get_dlc_protocol(
         trader: PublicKey::from_str(&dlc_protocol.trader_pubkey).expect("valid public key"),
         protocol_state: dlc_protocol.protocol_state.into(),
         protocol_type,
+        failure_reason: dlc_protocol.failure_reason,
     };
 
     Ok(protocol)
 }
 
+/// Load all dlc protocols run with the given trader, oldest first.
+///
+/// Unlike [`get_dlc_protocol`], this does not load the protocol parameters, which are deleted once
+/// a protocol has finished.
+pub(crate) fn get_dlc_protocols_by_trader(
+    conn: &mut PgConnection,
+    trader: &PublicKey,
+) -> QueryResult<Vec<ProtocolRecord>> {
+    let dlc_protocols: Vec<DlcProtocol> = dlc_protocols::table
+        .filter(dlc_protocols::trader_pubkey.eq(trader.to_string()))
+        .order_by(dlc_protocols::timestamp.asc())
+        .then_order_by(dlc_protocols::id.asc())
+        .load(conn)?;
+
+    let dlc_protocols = dlc_protocols
+        .into_iter()
+        .map(ProtocolRecord::from)
+        .collect();
+
+    Ok(dlc_protocols)
+}
+
+/// Load all dlc protocols run on the given dlc channel, oldest first, without their parameters.
+pub(crate) fn get_by_channel_id(
+    conn: &mut PgConnection,
+    channel_id: &DlcChannelId,
+) -> QueryResult<Vec<ProtocolRecord>> {
+    let dlc_protocols: Vec<DlcProtocol> = dlc_protocols::table
+        .filter(dlc_protocols::channel_id.eq(hex::encode(channel_id)))
+        .order_by(dlc_protocols::timestamp.asc())
+        .then_order_by(dlc_protocols::id.asc())
+        .load(conn)?;
+
+    Ok(dlc_protocols
+        .into_iter()
+        .map(ProtocolRecord::from)
+        .collect())
+}
+
+/// Load all dlc protocols run on the given contract, oldest first, without their parameters.
+pub(crate) fn get_by_contract_id(
+    conn: &mut PgConnection,
+    contract_id: &ContractId,
+) -> QueryResult<Vec<ProtocolRecord>> {
+    let dlc_protocols: Vec<DlcProtocol> = dlc_protocols::table
+        .filter(dlc_protocols::contract_id.eq(hex::encode(contract_id)))
+        .order_by(dlc_protocols::timestamp.asc())
+        .then_order_by(dlc_protocols::id.asc())
+        .load(conn)?;
+
+    Ok(dlc_protocols
+        .into_iter()
+        .map(ProtocolRecord::from)
+        .collect())
+}
+
+/// Load all pending dlc protocols, oldest first, without their parameters.
+pub(crate) fn get_pending_dlc_protocols(
+    conn: &mut PgConnection,
+) -> QueryResult<Vec<ProtocolRecord>> {
+    let dlc_protocols: Vec<DlcProtocol> = dlc_protocols::table
+        .filter(dlc_protocols::protocol_state.eq(DlcProtocolState::Pending))
+        .order_by(dlc_protocols::timestamp.asc())
+        .load(conn)?;
+
+    Ok(dlc_protocols
+        .into_iter()
+        .map(ProtocolRecord::from)
+        .collect())
+}
+
+/// Load the dlc protocol with the given id, without its parameters.
+pub(crate) fn get_protocol_record(
+    conn: &mut PgConnection,
+    protocol_id: ProtocolId,
+) -> QueryResult<ProtocolRecord> {
+    let dlc_protocol: DlcProtocol = dlc_protocols::table
+        .filter(dlc_protocols::protocol_id.eq(protocol_id.to_uuid()))
+        .first(conn)?;
+
+    Ok(dlc_protocol.into())
+}
+
+pub(crate) fn exists(conn: &mut PgConnection, protocol_id: ProtocolId) -> QueryResult<bool> {
+    diesel::select(diesel::dsl::exists(
+        dlc_protocols::table.filter(dlc_protocols::protocol_id.eq(protocol_id.to_uuid())),
+    ))
+    .get_result(conn)
+}
+
+/// Lock the dlc protocol row until the end of the current transaction and return its state.
+///
+/// Concurrent transactions trying to lock the same protocol will block until the lock is
+/// released, so they will see the state as it was left by the first transaction.
+pub(crate) fn lock_dlc_protocol_state(
+    conn: &mut PgConnection,
+    protocol_id: ProtocolId,
+) -> QueryResult<dlc_protocol::DlcProtocolState> {
+    let state: DlcProtocolState = dlc_protocols::table
+        .select(dlc_protocols::protocol_state)
+        .filter(dlc_protocols::protocol_id.eq(protocol_id.to_uuid()))
+        .for_update()
+        .first(conn)?;
+
+    Ok(state.into())
+}
+
+/// Set the protocol to failed, unless it is no longer pending.
+///
+/// Returns whether the protocol was pending and has been set to failed.
 pub(crate) fn set_dlc_protocol_state_to_failed(
     conn: &mut PgConnection,
     protocol_id: ProtocolId,
-) -> QueryResult<()> {
+    reason: &str,
+) -> QueryResult<bool> {
     let affected_rows = diesel::update(dlc_protocols::table)
         .filter(dlc_protocols::protocol_id.eq(protocol_id.to_uuid()))
-        .set((dlc_protocols::protocol_state.eq(DlcProtocolState::Failed),))
+        .filter(dlc_protocols::protocol_state.eq(DlcProtocolState::Pending))
+        .set((
+            dlc_protocols::protocol_state.eq(DlcProtocolState::Failed),
+            dlc_protocols::failure_reason.eq(reason),
+        ))
         .execute(conn)?;
 
-    if affected_rows == 0 {
-        return Err(diesel::result::Error::NotFound);
-    }
-
-    Ok(())
+    Ok(affected_rows > 0)
 }
 
 pub(crate) fn set_dlc_protocol_state_to_success(
@@ -212,9 +332,37 @@ impl From<dlc_protocol::DlcProtocolType> for DlcProtocolType {
             dlc_protocol::DlcProtocolType::Open { .. } => DlcProtocolType::Open,
             dlc_protocol::DlcProtocolType::Renew { .. } => DlcProtocolType::Renew,
             dlc_protocol::DlcProtocolType::Settle { .. } => DlcProtocolType::Settle,
+            dlc_protocol::DlcProtocolType::PartialSettle { .. } => DlcProtocolType::PartialSettle,
             dlc_protocol::DlcProtocolType::Close { .. } => DlcProtocolType::Close,
             dlc_protocol::DlcProtocolType::ForceClose { .. } => DlcProtocolType::ForceClose,
             dlc_protocol::DlcProtocolType::Rollover { .. } => DlcProtocolType::Rollover,
         }
     }
 }
+
+impl From<DlcProtocol> for ProtocolRecord {
+    fn from(value: DlcProtocol) -> Self {
+        ProtocolRecord {
+            id: value.protocol_id.into(),
+            previous_id: value.previous_protocol_id.map(ProtocolId::from),
+            contract_id: ContractId::from_hex(&value.contract_id).expect("valid contract id"),
+            state: value.protocol_state.into(),
+            kind: value.protocol_type.into(),
+            timestamp: value.timestamp,
+        }
+    }
+}
+
+impl From<DlcProtocolType> for ProtocolKind {
+    fn from(value: DlcProtocolType) -> Self {
+        match value {
+            DlcProtocolType::Open => ProtocolKind::Open,
+            DlcProtocolType::Renew => ProtocolKind::Renew,
+            DlcProtocolType::Settle => ProtocolKind::Settle,
+            DlcProtocolType::PartialSettle => ProtocolKind::PartialSettle,
+            DlcProtocolType::Close => ProtocolKind::Close,
+            DlcProtocolType::ForceClose => ProtocolKind::ForceClose,
+            DlcProtocolType::Rollover => ProtocolKind::Rollover,
+        }
+    }
+}
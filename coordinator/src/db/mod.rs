@@ -7,9 +7,12 @@ pub mod dlc_protocols;
 pub mod last_outbound_dlc_message;
 pub mod liquidity;
 pub mod liquidity_options;
+pub mod node_identity;
 pub mod polls;
 pub mod positions;
 pub mod positions_helper;
+pub mod retry;
+pub mod rollover_fees;
 pub mod spendable_outputs;
 pub mod trade_params;
 pub mod trades;
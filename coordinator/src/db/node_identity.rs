@@ -0,0 +1,48 @@
+use crate::schema::node_identity;
+use anyhow::ensure;
+use anyhow::Result;
+use bitcoin::secp256k1::PublicKey;
+use diesel::ExpressionMethods;
+use diesel::OptionalExtension;
+use diesel::PgConnection;
+use diesel::QueryDsl;
+use diesel::RunQueryDsl;
+use std::str::FromStr;
+use time::OffsetDateTime;
+
+/// The node id the coordinator was last started with, if any.
+pub fn get(conn: &mut PgConnection) -> Result<Option<PublicKey>> {
+    let node_id: Option<String> = node_identity::table
+        .select(node_identity::node_id)
+        .first(conn)
+        .optional()?;
+
+    let node_id = node_id
+        .map(|node_id| PublicKey::from_str(&node_id))
+        .transpose()?;
+
+    Ok(node_id)
+}
+
+/// Persist the node id the coordinator is started with, replacing the previous one.
+pub fn set(conn: &mut PgConnection, node_id: PublicKey) -> Result<()> {
+    let timestamp = OffsetDateTime::now_utc();
+
+    let affected_rows = diesel::insert_into(node_identity::table)
+        .values((
+            node_identity::id.eq(1),
+            node_identity::node_id.eq(node_id.to_string()),
+            node_identity::timestamp.eq(timestamp),
+        ))
+        .on_conflict(node_identity::id)
+        .do_update()
+        .set((
+            node_identity::node_id.eq(node_id.to_string()),
+            node_identity::timestamp.eq(timestamp),
+        ))
+        .execute(conn)?;
+
+    ensure!(affected_rows > 0, "Could not persist node id");
+
+    Ok(())
+}
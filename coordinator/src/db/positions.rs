@@ -38,6 +38,7 @@ pub struct Position {
     pub coordinator_leverage: f32,
     pub trader_margin: i64,
     pub stable: bool,
+    pub auto_rollover: bool,
 }
 
 impl Position {
@@ -65,6 +66,18 @@ impl Position {
         Ok(x.map(crate::position::models::Position::from))
     }
 
+    pub fn get(
+        conn: &mut PgConnection,
+        id: i32,
+    ) -> QueryResult<Option<crate::position::models::Position>> {
+        let position = positions::table
+            .filter(positions::id.eq(id))
+            .first::<Position>(conn)
+            .optional()?;
+
+        Ok(position.map(crate::position::models::Position::from))
+    }
+
     pub fn get_all_open_positions_with_expiry_before(
         conn: &mut PgConnection,
         expiry: OffsetDateTime,
@@ -263,6 +276,89 @@ impl Position {
         Ok(())
     }
 
+    /// Sets the position in state `Resizing` back to a new state, e.g. if the trader rejected the
+    /// proposal to reduce it.
+    pub fn update_resizing_position(
+        conn: &mut PgConnection,
+        trader_pubkey: String,
+        state: crate::position::models::PositionState,
+    ) -> Result<()> {
+        let state = PositionState::from(state);
+        let affected_rows = diesel::update(positions::table)
+            .filter(positions::trader_pubkey.eq(trader_pubkey.clone()))
+            .filter(positions::position_state.eq(PositionState::Resizing))
+            .set((
+                positions::position_state.eq(state),
+                positions::update_timestamp.eq(OffsetDateTime::now_utc()),
+            ))
+            .execute(conn)?;
+
+        if affected_rows == 0 {
+            bail!("Could not update position to {state:?} for {trader_pubkey}")
+        }
+
+        Ok(())
+    }
+
+    /// Sets the position in state `Resizing` back to `Open` after part of it has been closed.
+    ///
+    /// The entry price, leverages and expiry are unchanged by a reduction.
+    pub fn set_reduced_position(
+        conn: &mut PgConnection,
+        id: i32,
+        quantity: f32,
+        coordinator_margin: i64,
+        trader_margin: i64,
+        trader_realized_pnl_sat: i64,
+        temporary_contract_id: ContractId,
+    ) -> QueryResult<crate::position::models::Position> {
+        let position: Position = diesel::update(positions::table)
+            .filter(positions::id.eq(id))
+            .filter(positions::position_state.eq(PositionState::Resizing))
+            .set((
+                positions::position_state.eq(PositionState::Open),
+                positions::quantity.eq(quantity),
+                positions::coordinator_margin.eq(coordinator_margin),
+                positions::trader_margin.eq(trader_margin),
+                positions::trader_realized_pnl_sat.eq(Some(trader_realized_pnl_sat)),
+                positions::temporary_contract_id.eq(hex::encode(temporary_contract_id)),
+                positions::update_timestamp.eq(OffsetDateTime::now_utc()),
+            ))
+            .get_result(conn)?;
+
+        Ok(crate::position::models::Position::from(position))
+    }
+
+    /// Sets the position in state `Resizing` back to `Open` after contracts have been added to it.
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_increased_position(
+        conn: &mut PgConnection,
+        id: i32,
+        quantity: f32,
+        average_entry_price: f32,
+        liquidation_price: f32,
+        coordinator_margin: i64,
+        trader_margin: i64,
+        temporary_contract_id: ContractId,
+    ) -> QueryResult<crate::position::models::Position> {
+        let position: Position = diesel::update(positions::table)
+            .filter(positions::id.eq(id))
+            .filter(positions::position_state.eq(PositionState::Resizing))
+            .set((
+                positions::position_state.eq(PositionState::Open),
+                positions::quantity.eq(quantity),
+                positions::average_entry_price.eq(average_entry_price),
+                positions::trader_liquidation_price.eq(liquidation_price),
+                positions::coordinator_margin.eq(coordinator_margin),
+                positions::trader_margin.eq(trader_margin),
+                positions::temporary_contract_id.eq(hex::encode(temporary_contract_id)),
+                positions::update_timestamp.eq(OffsetDateTime::now_utc()),
+            ))
+            .get_result(conn)?;
+
+        Ok(crate::position::models::Position::from(position))
+    }
+
     pub fn set_position_to_closed_with_pnl(
         conn: &mut PgConnection,
         id: i32,
@@ -316,6 +412,27 @@ impl Position {
             .execute(conn)
     }
 
+    /// Stop rolling over the open position of the trader, so that it is closed once it expires.
+    ///
+    /// Returns the id of the position, or `None` if the trader has no open position which would
+    /// have been rolled over.
+    pub fn disable_auto_rollover(
+        conn: &mut PgConnection,
+        trader_pubkey: PublicKey,
+    ) -> QueryResult<Option<i32>> {
+        diesel::update(positions::table)
+            .filter(positions::trader_pubkey.eq(trader_pubkey.to_string()))
+            .filter(positions::position_state.eq(PositionState::Open))
+            .filter(positions::auto_rollover.eq(true))
+            .set((
+                positions::auto_rollover.eq(false),
+                positions::update_timestamp.eq(OffsetDateTime::now_utc()),
+            ))
+            .returning(positions::id)
+            .get_result(conn)
+            .optional()
+    }
+
     pub fn update_unrealized_pnl(conn: &mut PgConnection, id: i32, pnl: i64) -> Result<()> {
         let affected_rows = diesel::update(positions::table)
             .filter(positions::id.eq(id))
@@ -409,6 +526,7 @@ impl From<Position> for crate::position::models::Position {
             coordinator_leverage: value.coordinator_leverage,
             trader_margin: value.trader_margin,
             stable: value.stable,
+            auto_rollover: value.auto_rollover,
             trader_realized_pnl_sat: value.trader_realized_pnl_sat,
         }
     }
@@ -431,6 +549,7 @@ struct NewPosition {
     pub coordinator_leverage: f32,
     pub trader_margin: i64,
     pub stable: bool,
+    pub auto_rollover: bool,
 }
 
 impl From<crate::position::models::NewPosition> for NewPosition {
@@ -450,6 +569,7 @@ impl From<crate::position::models::NewPosition> for NewPosition {
             coordinator_leverage: value.coordinator_leverage,
             trader_margin: value.trader_margin,
             stable: value.stable,
+            auto_rollover: value.auto_rollover,
         }
     }
 }
@@ -507,6 +627,7 @@ impl From<(PositionState, Option<i64>, Option<f32>)> for crate::position::models
 #[diesel(sql_type = ContractSymbolType)]
 pub enum ContractSymbol {
     BtcUsd,
+    EthUsd,
 }
 
 impl QueryId for ContractSymbolType {
@@ -522,6 +643,7 @@ impl From<ContractSymbol> for trade::ContractSymbol {
     fn from(value: ContractSymbol) -> Self {
         match value {
             ContractSymbol::BtcUsd => trade::ContractSymbol::BtcUsd,
+            ContractSymbol::EthUsd => trade::ContractSymbol::EthUsd,
         }
     }
 }
@@ -530,6 +652,7 @@ impl From<trade::ContractSymbol> for ContractSymbol {
     fn from(value: trade::ContractSymbol) -> Self {
         match value {
             trade::ContractSymbol::BtcUsd => ContractSymbol::BtcUsd,
+            trade::ContractSymbol::EthUsd => ContractSymbol::EthUsd,
         }
     }
 }
@@ -0,0 +1,111 @@
+use anyhow::Result;
+use diesel::r2d2::PoolError;
+use diesel::result::DatabaseErrorKind;
+use std::time::Duration;
+
+/// How often an operation is attempted before a transient error is returned to the caller.
+const MAX_ATTEMPTS: u32 = 3;
+const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+/// Run the given database operation, retrying it with exponential backoff if it fails with a
+/// transient error.
+///
+/// The operation has to be safe to repeat, i.e. it should get its own connection from the pool
+/// and run in a single transaction, so that a failed attempt leaves no partial changes behind.
+/// Any other error is returned immediately.
+pub fn with_retry<T>(mut operation: impl FnMut() -> Result<T>) -> Result<T> {
+    let mut retry_delay = INITIAL_RETRY_DELAY;
+    let mut attempt = 1;
+    loop {
+        match operation() {
+            Err(e) if attempt < MAX_ATTEMPTS && is_transient(&e) => {
+                tracing::warn!(attempt, ?retry_delay, "Retrying database operation: {e:#}");
+
+                std::thread::sleep(retry_delay);
+                retry_delay *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Whether the error may go away by trying again, e.g. a serialization failure caused by a
+/// concurrent transaction or a dropped connection.
+fn is_transient(e: &anyhow::Error) -> bool {
+    if e.downcast_ref::<PoolError>().is_some() {
+        return true;
+    }
+
+    matches!(
+        e.downcast_ref::<diesel::result::Error>(),
+        Some(diesel::result::Error::DatabaseError(
+            DatabaseErrorKind::SerializationFailure
+                | DatabaseErrorKind::ClosedConnection
+                | DatabaseErrorKind::UnableToSendCommand,
+            _,
+        ))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn retries_transient_error_until_success() {
+        let mut attempts = 0;
+
+        let result = with_retry(|| {
+            attempts += 1;
+            if attempts < MAX_ATTEMPTS {
+                Err(database_error(DatabaseErrorKind::SerializationFailure))
+            } else {
+                Ok(attempts)
+            }
+        });
+
+        assert_eq!(result.unwrap(), MAX_ATTEMPTS);
+    }
+
+    #[test]
+    fn gives_up_after_max_attempts() {
+        let mut attempts = 0;
+
+        let result: Result<()> = with_retry(|| {
+            attempts += 1;
+            Err(database_error(DatabaseErrorKind::ClosedConnection))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(attempts, MAX_ATTEMPTS);
+    }
+
+    #[test]
+    fn does_not_retry_logical_errors() {
+        let mut attempts = 0;
+
+        let result: Result<()> = with_retry(|| {
+            attempts += 1;
+            Err(database_error(DatabaseErrorKind::UniqueViolation))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+
+        let mut attempts = 0;
+
+        let result: Result<()> = with_retry(|| {
+            attempts += 1;
+            Err(anyhow!("missing contract id"))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+    }
+
+    fn database_error(kind: DatabaseErrorKind) -> anyhow::Error {
+        diesel::result::Error::DatabaseError(kind, Box::new("error".to_string())).into()
+    }
+}
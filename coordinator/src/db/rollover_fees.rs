@@ -0,0 +1,86 @@
+use crate::dlc_protocol::ProtocolId;
+use crate::schema::rollover_fees;
+use bitcoin::secp256k1::PublicKey;
+use bitcoin::Amount;
+use diesel::prelude::*;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[derive(Queryable, Debug, Clone)]
+#[diesel(table_name = rollover_fees)]
+#[allow(dead_code)] // We have to allow dead code here because diesel needs the fields to be able to derive queryable.
+struct RolloverFee {
+    id: i32,
+    protocol_id: Uuid,
+    position_id: i32,
+    trader_pubkey: String,
+    fee_sats: i64,
+    is_confirmed: bool,
+    timestamp: OffsetDateTime,
+}
+
+/// A fee charged to a trader for rolling over their position.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfirmedRolloverFee {
+    pub protocol_id: ProtocolId,
+    pub position_id: i32,
+    pub fee: Amount,
+    pub timestamp: OffsetDateTime,
+}
+
+/// Record the fee charged by the rollover protocol with the given id.
+///
+/// The fee is only confirmed once the rollover protocol has finished successfully.
+pub(crate) fn insert(
+    conn: &mut PgConnection,
+    protocol_id: ProtocolId,
+    position_id: i32,
+    trader: PublicKey,
+    fee: Amount,
+) -> QueryResult<()> {
+    diesel::insert_into(rollover_fees::table)
+        .values((
+            rollover_fees::protocol_id.eq(protocol_id.to_uuid()),
+            rollover_fees::position_id.eq(position_id),
+            rollover_fees::trader_pubkey.eq(trader.to_string()),
+            rollover_fees::fee_sats.eq(fee.to_sat() as i64),
+        ))
+        .execute(conn)?;
+
+    Ok(())
+}
+
+/// Confirm the fee charged by the rollover protocol with the given id, if any.
+pub(crate) fn confirm(conn: &mut PgConnection, protocol_id: ProtocolId) -> QueryResult<()> {
+    diesel::update(rollover_fees::table)
+        .filter(rollover_fees::protocol_id.eq(protocol_id.to_uuid()))
+        .set(rollover_fees::is_confirmed.eq(true))
+        .execute(conn)?;
+
+    Ok(())
+}
+
+/// Load the confirmed rollover fees of the given trader, oldest first.
+pub fn get_confirmed_by_trader(
+    conn: &mut PgConnection,
+    trader: PublicKey,
+) -> QueryResult<Vec<ConfirmedRolloverFee>> {
+    let fees = rollover_fees::table
+        .filter(rollover_fees::trader_pubkey.eq(trader.to_string()))
+        .filter(rollover_fees::is_confirmed.eq(true))
+        .order_by(rollover_fees::id.asc())
+        .load::<RolloverFee>(conn)?;
+
+    Ok(fees.into_iter().map(ConfirmedRolloverFee::from).collect())
+}
+
+impl From<RolloverFee> for ConfirmedRolloverFee {
+    fn from(value: RolloverFee) -> Self {
+        ConfirmedRolloverFee {
+            protocol_id: value.protocol_id.into(),
+            position_id: value.position_id,
+            fee: Amount::from_sat(value.fee_sats as u64),
+            timestamp: value.timestamp,
+        }
+    }
+}
@@ -9,6 +9,7 @@ use diesel::QueryDsl;
 use diesel::QueryResult;
 use diesel::Queryable;
 use diesel::RunQueryDsl;
+use std::collections::HashMap;
 use std::str::FromStr;
 use uuid::Uuid;
 
@@ -59,6 +60,27 @@ pub(crate) fn get(
     Ok(dlc_protocol::TradeParams::from(trade_params))
 }
 
+/// Load the trade direction of those of the given protocols which have trade parameters.
+pub(crate) fn get_directions(
+    conn: &mut PgConnection,
+    protocol_ids: &[ProtocolId],
+) -> QueryResult<HashMap<Uuid, trade::Direction>> {
+    let protocol_ids = protocol_ids
+        .iter()
+        .map(|protocol_id| protocol_id.to_uuid())
+        .collect::<Vec<_>>();
+
+    let directions: Vec<(Uuid, Direction)> = trade_params::table
+        .filter(trade_params::protocol_id.eq_any(protocol_ids))
+        .select((trade_params::protocol_id, trade_params::direction))
+        .load(conn)?;
+
+    Ok(directions
+        .into_iter()
+        .map(|(protocol_id, direction)| (protocol_id, direction.into()))
+        .collect())
+}
+
 pub(crate) fn delete(conn: &mut PgConnection, protocol_id: ProtocolId) -> QueryResult<usize> {
     diesel::delete(trade_params::table)
         .filter(trade_params::protocol_id.eq(protocol_id.to_uuid()))
@@ -64,6 +64,79 @@ pub fn get_latest_for_position(
     Ok(trade.map(crate::trade::models::Trade::from))
 }
 
+/// Load the most recent trade in the given contract symbol, if any.
+pub fn get_latest_by_contract_symbol(
+    conn: &mut PgConnection,
+    contract_symbol: trade::ContractSymbol,
+) -> QueryResult<Option<crate::trade::models::Trade>> {
+    let trade = trades::table
+        .filter(trades::contract_symbol.eq(ContractSymbol::from(contract_symbol)))
+        .order_by(trades::timestamp.desc())
+        .then_order_by(trades::id.desc())
+        .first::<Trade>(conn)
+        .optional()?;
+
+    Ok(trade.map(crate::trade::models::Trade::from))
+}
+
+/// Load all trades of the given position, oldest first.
+pub fn get_by_position(
+    conn: &mut PgConnection,
+    position_id: i32,
+) -> QueryResult<Vec<crate::trade::models::Trade>> {
+    let trades = trades::table
+        .filter(trades::position_id.eq(position_id))
+        .order_by(trades::timestamp.asc())
+        .then_order_by(trades::id.asc())
+        .load::<Trade>(conn)?;
+
+    Ok(trades
+        .into_iter()
+        .map(crate::trade::models::Trade::from)
+        .collect())
+}
+
+/// Load a page of the trades of the given trader, executed within `[start, end)`, oldest first.
+pub fn get_trades_by_trader(
+    conn: &mut PgConnection,
+    trader_pubkey: PublicKey,
+    start: OffsetDateTime,
+    end: OffsetDateTime,
+    limit: i64,
+    offset: i64,
+) -> QueryResult<Vec<crate::trade::models::Trade>> {
+    let trades = trades::table
+        .filter(trades::trader_pubkey.eq(trader_pubkey.to_string()))
+        .filter(trades::timestamp.ge(start))
+        .filter(trades::timestamp.lt(end))
+        .order_by(trades::timestamp.asc())
+        .then_order_by(trades::id.asc())
+        .limit(limit)
+        .offset(offset)
+        .load::<Trade>(conn)?;
+
+    Ok(trades
+        .into_iter()
+        .map(crate::trade::models::Trade::from)
+        .collect())
+}
+
+/// Load the contract symbol and quantity of every trade executed since `since`.
+pub fn get_quantities_since(
+    conn: &mut PgConnection,
+    since: OffsetDateTime,
+) -> QueryResult<Vec<(trade::ContractSymbol, f32)>> {
+    let quantities = trades::table
+        .filter(trades::timestamp.ge(since))
+        .select((trades::contract_symbol, trades::quantity))
+        .load::<(ContractSymbol, f32)>(conn)?;
+
+    Ok(quantities
+        .into_iter()
+        .map(|(symbol, quantity)| (symbol.into(), quantity))
+        .collect())
+}
+
 impl From<crate::trade::models::NewTrade> for NewTrade {
     fn from(value: crate::trade::models::NewTrade) -> Self {
         NewTrade {
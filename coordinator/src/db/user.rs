@@ -23,6 +23,20 @@ pub struct User {
     // TODO(holzeis): Version is only optional for the first upgrade. Afterwards we should make it
     // mandatory.
     pub version: Option<String>,
+    /// If set, the trader's open positions are closed once we have not heard from them for this
+    /// many seconds.
+    pub dead_mans_switch_timeout_secs: Option<i32>,
+    pub last_heartbeat: Option<OffsetDateTime>,
+}
+
+impl User {
+    /// The last time the trader logged in or sent a heartbeat.
+    pub fn last_seen(&self) -> OffsetDateTime {
+        match self.last_heartbeat {
+            Some(last_heartbeat) => last_heartbeat.max(self.last_login),
+            None => self.last_login,
+        }
+    }
 }
 
 impl From<RegisterParams> for User {
@@ -36,6 +50,8 @@ impl From<RegisterParams> for User {
             fcm_token: "".to_owned(),
             last_login: OffsetDateTime::now_utc(),
             version: value.version,
+            dead_mans_switch_timeout_secs: None,
+            last_heartbeat: None,
         }
     }
 }
@@ -43,6 +59,10 @@ impl From<RegisterParams> for User {
 pub fn all(conn: &mut PgConnection) -> QueryResult<Vec<User>> {
     users::dsl::users.load(conn)
 }
+pub fn count(conn: &mut PgConnection) -> QueryResult<i64> {
+    users::table.count().get_result(conn)
+}
+
 pub fn by_id(conn: &mut PgConnection, id: String) -> QueryResult<Option<User>> {
     let x = users::table
         .filter(users::pubkey.eq(id))
@@ -74,6 +94,8 @@ pub fn upsert_user(
             fcm_token: "".to_owned(),
             last_login: timestamp,
             version: version.clone(),
+            dead_mans_switch_timeout_secs: None,
+            last_heartbeat: None,
         })
         .on_conflict(schema::users::pubkey)
         .do_update()
@@ -128,6 +150,8 @@ pub fn login_user(
             fcm_token: token.clone(),
             version: version.clone(),
             last_login,
+            dead_mans_switch_timeout_secs: None,
+            last_heartbeat: None,
         })
         .on_conflict(schema::users::pubkey)
         .do_update()
@@ -162,3 +186,27 @@ pub fn get_users(conn: &mut PgConnection, trader_ids: Vec<PublicKey>) -> Result<
 
     Ok(users)
 }
+
+pub fn set_dead_mans_switch(
+    conn: &mut PgConnection,
+    trader_id: PublicKey,
+    timeout_secs: Option<i32>,
+) -> QueryResult<usize> {
+    diesel::update(users::table)
+        .filter(users::pubkey.eq(trader_id.to_string()))
+        .set(users::dead_mans_switch_timeout_secs.eq(timeout_secs))
+        .execute(conn)
+}
+
+pub fn record_heartbeat(conn: &mut PgConnection, trader_id: PublicKey) -> QueryResult<usize> {
+    diesel::update(users::table)
+        .filter(users::pubkey.eq(trader_id.to_string()))
+        .set(users::last_heartbeat.eq(OffsetDateTime::now_utc()))
+        .execute(conn)
+}
+
+pub fn get_users_with_dead_mans_switch(conn: &mut PgConnection) -> QueryResult<Vec<User>> {
+    users::table
+        .filter(users::dead_mans_switch_timeout_secs.is_not_null())
+        .load(conn)
+}
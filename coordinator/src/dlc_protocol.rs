@@ -1,14 +1,27 @@
 use crate::db;
+use crate::db::retry::with_retry;
+use crate::metrics;
+use crate::metrics::DlcProtocolSample;
+use crate::metrics::ProtocolOutcome;
+use crate::position::models::Position;
+use crate::position::models::PositionFlip;
 use crate::position::models::PositionState;
 use crate::trade::models::NewTrade;
 use crate::trade::websocket::InternalPositionUpdateMessage;
+use crate::webhook::PositionEvent;
+use crate::webhook::PositionEventTrade;
+use crate::webhook::PositionEventType;
+use anyhow::bail;
+use anyhow::ensure;
 use anyhow::Context;
 use anyhow::Result;
 use bitcoin::secp256k1::PublicKey;
+use commons::order_matching_fee_taker;
 use diesel::r2d2::ConnectionManager;
 use diesel::r2d2::Pool;
 use diesel::result::Error::RollbackTransaction;
 use diesel::Connection;
+use diesel::OptionalExtension;
 use diesel::PgConnection;
 use diesel::QueryResult;
 use dlc_manager::ContractId;
@@ -17,13 +30,14 @@ use ln_dlc_node::node::rust_dlc_manager::DlcChannelId;
 use rust_decimal::prelude::FromPrimitive;
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
+use serde::Serialize;
 use std::fmt::Display;
 use std::fmt::Formatter;
 use std::str::from_utf8;
 use time::OffsetDateTime;
 use tokio::sync::broadcast::Sender;
 use trade::cfd::calculate_margin;
-use trade::cfd::calculate_pnl;
+use trade::cfd::calculate_pnl_with_fees;
 use trade::Direction;
 use uuid::Uuid;
 
@@ -108,6 +122,8 @@ pub struct DlcProtocol {
     pub trader: PublicKey,
     pub protocol_state: DlcProtocolState,
     pub protocol_type: DlcProtocolType,
+    /// Why the protocol failed, if it did.
+    pub failure_reason: Option<String>,
 }
 
 #[derive(Clone, Debug)]
@@ -120,6 +136,17 @@ pub struct TradeParams {
     pub direction: Direction,
 }
 
+impl TradeParams {
+    /// The average execution price as a [`Decimal`], consistent with
+    /// [`commons::TradeParams::average_execution_price`].
+    ///
+    /// We only persist the average execution price as `f32`, so we convert it back here rather
+    /// than at every call site.
+    pub fn average_execution_price(&self) -> Decimal {
+        Decimal::from_f32(self.average_price).expect("to fit into decimal")
+    }
+}
+
 impl From<(ProtocolId, &commons::TradeParams)> for TradeParams {
     fn from((protocol_id, trade_params): (ProtocolId, &commons::TradeParams)) -> Self {
         Self {
@@ -136,6 +163,7 @@ impl From<(ProtocolId, &commons::TradeParams)> for TradeParams {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
 pub enum DlcProtocolState {
     Pending,
     Success,
@@ -147,6 +175,7 @@ pub enum DlcProtocolType {
     Open { trade_params: TradeParams },
     Renew { trade_params: TradeParams },
     Settle { trade_params: TradeParams },
+    PartialSettle { trade_params: TradeParams },
     Close { trader: PublicKey },
     ForceClose { trader: PublicKey },
     Rollover { trader: PublicKey },
@@ -164,11 +193,52 @@ impl DlcProtocolType {
             DlcProtocolType::Settle {
                 trade_params: TradeParams { trader, .. },
             } => trader,
+            DlcProtocolType::PartialSettle {
+                trade_params: TradeParams { trader, .. },
+            } => trader,
             DlcProtocolType::Close { trader } => trader,
             DlcProtocolType::ForceClose { trader } => trader,
             DlcProtocolType::Rollover { trader } => trader,
         }
     }
+
+    pub fn kind(&self) -> ProtocolKind {
+        match self {
+            DlcProtocolType::Open { .. } => ProtocolKind::Open,
+            DlcProtocolType::Renew { .. } => ProtocolKind::Renew,
+            DlcProtocolType::Settle { .. } => ProtocolKind::Settle,
+            DlcProtocolType::PartialSettle { .. } => ProtocolKind::PartialSettle,
+            DlcProtocolType::Close { .. } => ProtocolKind::Close,
+            DlcProtocolType::ForceClose { .. } => ProtocolKind::ForceClose,
+            DlcProtocolType::Rollover { .. } => ProtocolKind::Rollover,
+        }
+    }
+}
+
+/// The kind of a DLC protocol, without its parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum ProtocolKind {
+    Open,
+    Renew,
+    Settle,
+    PartialSettle,
+    Close,
+    ForceClose,
+    Rollover,
+}
+
+impl ProtocolKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProtocolKind::Open => "open",
+            ProtocolKind::Renew => "renew",
+            ProtocolKind::Settle => "settle",
+            ProtocolKind::PartialSettle => "partial_settle",
+            ProtocolKind::Close => "close",
+            ProtocolKind::ForceClose => "force_close",
+            ProtocolKind::Rollover => "rollover",
+        }
+    }
 }
 
 pub struct DlcProtocolExecutor {
@@ -183,7 +253,9 @@ impl DlcProtocolExecutor {
     /// Starts a dlc protocol, by creating a new dlc protocol and temporarily stores
     /// the trade params.
     ///
-    /// Returns a uniquely generated protocol id as [`dlc_manager::ReferenceId`]
+    /// Starting a protocol is idempotent: if a protocol with the same id is still pending, nothing
+    /// is changed. Starting a protocol which has already finished or failed, or restarting it with
+    /// a different type or channel, is an error.
     pub fn start_dlc_protocol(
         &self,
         protocol_id: ProtocolId,
@@ -192,36 +264,150 @@ impl DlcProtocolExecutor {
         channel_id: &DlcChannelId,
         protocol_type: DlcProtocolType,
     ) -> Result<()> {
-        let mut conn = self.pool.get()?;
-        conn.transaction(|conn| {
-            db::dlc_protocols::create(
-                conn,
-                protocol_id,
-                previous_protocol_id,
-                contract_id,
-                channel_id,
-                protocol_type.clone(),
-                protocol_type.get_trader_pubkey(),
-            )?;
+        let existing_protocol = with_retry(|| {
+            let mut conn = self.pool.get()?;
+            let existing_protocol = conn.transaction(|conn| {
+                if let Some(protocol) =
+                    db::dlc_protocols::get_dlc_protocol(conn, protocol_id).optional()?
+                {
+                    return QueryResult::Ok(Some(protocol));
+                }
 
-            match protocol_type {
-                DlcProtocolType::Open { trade_params }
-                | DlcProtocolType::Renew { trade_params }
-                | DlcProtocolType::Settle { trade_params } => {
-                    db::trade_params::insert(conn, protocol_id, &trade_params)?;
+                db::dlc_protocols::create(
+                    conn,
+                    protocol_id,
+                    previous_protocol_id,
+                    contract_id,
+                    channel_id,
+                    protocol_type.clone(),
+                    protocol_type.get_trader_pubkey(),
+                )?;
+
+                match &protocol_type {
+                    DlcProtocolType::Open { trade_params }
+                    | DlcProtocolType::Renew { trade_params }
+                    | DlcProtocolType::Settle { trade_params }
+                    | DlcProtocolType::PartialSettle { trade_params } => {
+                        db::trade_params::insert(conn, protocol_id, trade_params)?;
+                    }
+                    _ => {}
                 }
-                _ => {}
+
+                Ok(None)
+            })?;
+
+            Ok(existing_protocol)
+        })?;
+
+        let existing_protocol = match existing_protocol {
+            Some(existing_protocol) => existing_protocol,
+            None => return Ok(()),
+        };
+
+        let existing_kind = existing_protocol.protocol_type.kind();
+        ensure!(
+            existing_kind == protocol_type.kind(),
+            "Cannot restart DLC protocol {protocol_id} of type {} as {}",
+            existing_kind.as_str(),
+            protocol_type.kind().as_str()
+        );
+        ensure!(
+            existing_protocol.channel_id == *channel_id,
+            "Cannot restart DLC protocol {protocol_id} of channel {} on channel {}",
+            hex::encode(existing_protocol.channel_id),
+            hex::encode(channel_id)
+        );
+
+        match existing_protocol.protocol_state {
+            DlcProtocolState::Pending => {
+                tracing::debug!(%protocol_id, "DLC protocol has already been started");
+                Ok(())
             }
+            state => {
+                bail!("Cannot start DLC protocol {protocol_id} which has already ended: {state:?}")
+            }
+        }
+    }
 
-            diesel::result::QueryResult::Ok(())
+    /// Whether the given reference id refers to a DLC protocol we know about.
+    ///
+    /// A reference id which cannot be converted into a [`ProtocolId`] is treated as unknown.
+    pub fn is_known_protocol(&self, reference_id: ReferenceId) -> Result<bool> {
+        let protocol_id = match ProtocolId::try_from(reference_id) {
+            Ok(protocol_id) => protocol_id,
+            Err(e) => {
+                tracing::debug!("Reference id is not a valid protocol id: {e:#}");
+                return Ok(false);
+            }
+        };
+
+        let mut conn = self.pool.get()?;
+        let exists = db::dlc_protocols::exists(&mut conn, protocol_id)?;
+
+        Ok(exists)
+    }
+
+    /// Marks the protocol as failed, recording the `reason` for later investigation.
+    ///
+    /// A protocol which has already finished or failed is left untouched.
+    pub fn fail_dlc_protocol(&self, protocol_id: ProtocolId, reason: &str) -> Result<()> {
+        let failed_protocol = with_retry(|| {
+            let mut conn = self.pool.get()?;
+            let failed_protocol = conn.transaction(|conn| {
+                let protocol = db::dlc_protocols::get_protocol_record(conn, protocol_id)?;
+                let failed =
+                    db::dlc_protocols::set_dlc_protocol_state_to_failed(conn, protocol_id, reason)?;
+
+                QueryResult::Ok(failed.then_some(protocol))
+            })?;
+
+            Ok(failed_protocol)
         })?;
 
+        match failed_protocol {
+            Some(protocol) => metrics::record_dlc_protocol(&DlcProtocolSample::new(
+                protocol.kind,
+                ProtocolOutcome::Failed,
+                protocol.timestamp,
+                OffsetDateTime::now_utc(),
+            )),
+            None => {
+                tracing::debug!(%protocol_id, "Not failing DLC protocol which is no longer pending")
+            }
+        }
+
         Ok(())
     }
 
-    pub fn fail_dlc_protocol(&self, protocol_id: ProtocolId) -> Result<()> {
-        let mut conn = self.pool.get()?;
-        db::dlc_protocols::set_dlc_protocol_state_to_failed(&mut conn, protocol_id)?;
+    /// Finishes a force-close protocol. A force-close is unilateral, so there is nothing to wait
+    /// for once our transactions have been broadcast.
+    pub fn finish_force_close_dlc_protocol(
+        &self,
+        protocol_id: ProtocolId,
+        contract_id: &ContractId,
+        channel_id: &DlcChannelId,
+    ) -> Result<()> {
+        let protocol = with_retry(|| {
+            let mut conn = self.pool.get()?;
+            let protocol = db::dlc_protocols::get_protocol_record(&mut conn, protocol_id)?;
+            db::dlc_protocols::set_dlc_protocol_state_to_success(
+                &mut conn,
+                protocol_id,
+                contract_id,
+                channel_id,
+            )?;
+
+            Ok(protocol)
+        })?;
+
+        if protocol.state == DlcProtocolState::Pending {
+            metrics::record_dlc_protocol(&DlcProtocolSample::new(
+                protocol.kind,
+                ProtocolOutcome::Success,
+                protocol.timestamp,
+                OffsetDateTime::now_utc(),
+            ));
+        }
 
         Ok(())
     }
@@ -235,66 +421,220 @@ impl DlcProtocolExecutor {
         channel_id: &DlcChannelId,
         tx_position_feed: Sender<InternalPositionUpdateMessage>,
     ) -> Result<()> {
-        let mut conn = self.pool.get()?;
-        let dlc_protocol = db::dlc_protocols::get_dlc_protocol(&mut conn, protocol_id)?;
-        conn.transaction(|conn| {
-            match &dlc_protocol.protocol_type {
-                DlcProtocolType::Open { trade_params }
-                | DlcProtocolType::Renew { trade_params } => {
-                    let contract_id = contract_id
-                        .context("missing contract id")
-                        .map_err(|_| RollbackTransaction)?;
-                    self.finish_open_trade_dlc_protocol(
-                        conn,
-                        trade_params,
-                        protocol_id,
-                        &contract_id,
-                        channel_id,
-                    )
+        let dlc_protocol = with_retry(|| {
+            let mut conn = self.pool.get()?;
+            let dlc_protocol = conn.transaction(|conn| {
+                // Lock the protocol so that concurrent attempts to finish the same protocol are
+                // serialized and only the first one applies its effects.
+                let state = db::dlc_protocols::lock_dlc_protocol_state(conn, protocol_id)?;
+                if state != DlcProtocolState::Pending {
+                    return QueryResult::Ok(None);
                 }
-                DlcProtocolType::Settle { trade_params } => {
-                    let settled_contract = &dlc_protocol.contract_id;
-
-                    self.finish_close_trade_dlc_protocol(
-                        conn,
-                        trade_params,
-                        protocol_id,
-                        // If the contract got settled, we do not get a new contract id, hence we
-                        // copy the contract id of the settled contract.
-                        settled_contract,
-                        channel_id,
-                    )
-                }
-                DlcProtocolType::Rollover { .. } => {
-                    let contract_id = contract_id
-                        .context("missing contract id")
-                        .map_err(|_| RollbackTransaction)?;
-                    self.finish_rollover_dlc_protocol(
-                        conn,
-                        trader_id,
-                        protocol_id,
-                        &contract_id,
-                        channel_id,
-                    )
-                }
-                DlcProtocolType::Close { .. } | DlcProtocolType::ForceClose { .. } => {
-                    debug_assert!(false, "Finishing unexpected dlc protocol types");
-                    Ok(())
-                }
-            }
+
+                let dlc_protocol = db::dlc_protocols::get_dlc_protocol(conn, protocol_id)?;
+                let trader = *dlc_protocol.protocol_type.get_trader_pubkey();
+                let position_events = match &dlc_protocol.protocol_type {
+                    DlcProtocolType::Open { trade_params } => {
+                        let contract_id = contract_id
+                            .context("missing contract id")
+                            .map_err(|_| RollbackTransaction)?;
+                        self.finish_open_trade_dlc_protocol(
+                            conn,
+                            trade_params,
+                            protocol_id,
+                            &contract_id,
+                            channel_id,
+                        )?;
+
+                        vec![PositionEvent::new(
+                            PositionEventType::Opened,
+                            trader,
+                            protocol_id,
+                            Some(trade_params.into()),
+                        )]
+                    }
+                    DlcProtocolType::Renew { trade_params } => {
+                        let contract_id = contract_id
+                            .context("missing contract id")
+                            .map_err(|_| RollbackTransaction)?;
+
+                        // A renew either opens a new position in a settled channel or resizes
+                        // the position, by adding to it or by flipping it.
+                        match db::positions::Position::get_position_by_trader(
+                            conn,
+                            trade_params.trader,
+                            vec![PositionState::Resizing],
+                        )? {
+                            Some(position)
+                                if trade_params.direction != position.trader_direction =>
+                            {
+                                let flip = self.finish_flip_position_dlc_protocol(
+                                    conn,
+                                    &position,
+                                    trade_params,
+                                    protocol_id,
+                                    &contract_id,
+                                    channel_id,
+                                )?;
+
+                                // A flip settles the position and opens a new one in the opposite
+                                // direction.
+                                vec![
+                                    PositionEvent::new(
+                                        PositionEventType::Settled,
+                                        trader,
+                                        protocol_id,
+                                        Some(PositionEventTrade {
+                                            quantity: position.quantity,
+                                            leverage: position.trader_leverage,
+                                            average_price: trade_params.average_price,
+                                            direction: trade_params.direction,
+                                        }),
+                                    ),
+                                    PositionEvent::new(
+                                        PositionEventType::Opened,
+                                        trader,
+                                        protocol_id,
+                                        Some(PositionEventTrade {
+                                            quantity: flip.quantity,
+                                            leverage: flip.trader_leverage,
+                                            average_price: trade_params.average_price,
+                                            direction: flip.trader_direction,
+                                        }),
+                                    ),
+                                ]
+                            }
+                            Some(position) => {
+                                self.finish_increase_position_dlc_protocol(
+                                    conn,
+                                    &position,
+                                    trade_params,
+                                    protocol_id,
+                                    &contract_id,
+                                    channel_id,
+                                )?;
+
+                                vec![PositionEvent::new(
+                                    PositionEventType::Increased,
+                                    trader,
+                                    protocol_id,
+                                    Some(trade_params.into()),
+                                )]
+                            }
+                            None => {
+                                self.finish_open_trade_dlc_protocol(
+                                    conn,
+                                    trade_params,
+                                    protocol_id,
+                                    &contract_id,
+                                    channel_id,
+                                )?;
+
+                                vec![PositionEvent::new(
+                                    PositionEventType::Opened,
+                                    trader,
+                                    protocol_id,
+                                    Some(trade_params.into()),
+                                )]
+                            }
+                        }
+                    }
+                    DlcProtocolType::Settle { trade_params } => {
+                        let settled_contract = &dlc_protocol.contract_id;
+
+                        self.finish_close_trade_dlc_protocol(
+                            conn,
+                            trade_params,
+                            protocol_id,
+                            // If the contract got settled, we do not get a new contract id, hence
+                            // we copy the contract id of the settled contract.
+                            settled_contract,
+                            channel_id,
+                        )?;
+
+                        vec![PositionEvent::new(
+                            PositionEventType::Settled,
+                            trader,
+                            protocol_id,
+                            Some(trade_params.into()),
+                        )]
+                    }
+                    DlcProtocolType::PartialSettle { trade_params } => {
+                        let contract_id = contract_id
+                            .context("missing contract id")
+                            .map_err(|_| RollbackTransaction)?;
+                        self.finish_partial_settle_dlc_protocol(
+                            conn,
+                            trade_params,
+                            protocol_id,
+                            &contract_id,
+                            channel_id,
+                        )?;
+
+                        vec![PositionEvent::new(
+                            PositionEventType::Reduced,
+                            trader,
+                            protocol_id,
+                            Some(trade_params.into()),
+                        )]
+                    }
+                    DlcProtocolType::Rollover { .. } => {
+                        let contract_id = contract_id
+                            .context("missing contract id")
+                            .map_err(|_| RollbackTransaction)?;
+                        self.finish_rollover_dlc_protocol(
+                            conn,
+                            trader_id,
+                            protocol_id,
+                            &contract_id,
+                            channel_id,
+                        )?;
+
+                        vec![PositionEvent::new(
+                            PositionEventType::RolledOver,
+                            trader,
+                            protocol_id,
+                            None,
+                        )]
+                    }
+                    DlcProtocolType::Close { .. } | DlcProtocolType::ForceClose { .. } => {
+                        debug_assert!(false, "Finishing unexpected dlc protocol types");
+                        vec![]
+                    }
+                };
+
+                Ok(Some((dlc_protocol, position_events)))
+            })?;
+
+            Ok(dlc_protocol)
         })?;
 
+        let (dlc_protocol, position_events) = match dlc_protocol {
+            Some(finished) => finished,
+            None => {
+                tracing::warn!(%protocol_id, "DLC protocol has already been finished or failed");
+                return Ok(());
+            }
+        };
+
+        metrics::record_dlc_protocol(&DlcProtocolSample::new(
+            dlc_protocol.protocol_type.kind(),
+            ProtocolOutcome::Success,
+            dlc_protocol.timestamp,
+            OffsetDateTime::now_utc(),
+        ));
+
         match &dlc_protocol.protocol_type {
             DlcProtocolType::Open { trade_params }
             | DlcProtocolType::Renew { trade_params }
-            | DlcProtocolType::Settle { trade_params } => {
+            | DlcProtocolType::Settle { trade_params }
+            | DlcProtocolType::PartialSettle { trade_params } => {
                 if let Err(e) = {
                     tx_position_feed.send(InternalPositionUpdateMessage::NewTrade {
-                        quantity: if trade_params.direction == Direction::Short {
-                            trade_params.quantity
-                        } else {
-                            // We want to reflect the quantity as seen by the coordinator
-                            trade_params.quantity * -1.0
+                        // We want to reflect the quantity as seen by the coordinator
+                        quantity: match trade_params.direction.opposite() {
+                            Direction::Long => trade_params.quantity,
+                            Direction::Short => -trade_params.quantity,
                         },
                         average_entry_price: trade_params.average_price,
                     })
@@ -303,7 +643,15 @@ impl DlcProtocolExecutor {
                 }
             }
             _ => {
-                // a trade only happens in Open, Renew and Settle
+                // a trade only happens in Open, Renew, Settle and PartialSettle
+            }
+        }
+
+        for event in position_events {
+            if let Err(e) =
+                tx_position_feed.send(InternalPositionUpdateMessage::PositionEvent(event))
+            {
+                tracing::error!("Could not notify channel about position event {e:#}");
             }
         }
 
@@ -361,13 +709,26 @@ impl DlcProtocolExecutor {
                 Direction::Short => (position.coordinator_margin, position.trader_margin),
             };
 
-            match calculate_pnl(
-                Decimal::from_f32(position.average_entry_price).expect("to fit into decimal"),
-                Decimal::from_f32(trade_params.average_price).expect("to fit into decimal"),
+            let opening_price =
+                Decimal::from_f32(position.average_entry_price).expect("to fit into decimal");
+
+            // The trader pays the order-matching fee for both the order which opened the position
+            // and the one which closes it.
+            let opening_fee = order_matching_fee_taker(position.quantity, opening_price);
+            let closing_fee = order_matching_fee_taker(
+                trade_params.quantity,
+                trade_params.average_execution_price(),
+            );
+
+            match calculate_pnl_with_fees(
+                opening_price,
+                trade_params.average_execution_price(),
                 trade_params.quantity,
                 trade_params.direction,
                 initial_margin_long as u64,
                 initial_margin_short as u64,
+                opening_fee.to_sat(),
+                closing_fee.to_sat(),
             ) {
                 Ok(pnl) => pnl,
                 Err(e) => {
@@ -377,10 +738,13 @@ impl DlcProtocolExecutor {
             }
         };
 
+        // Add the PnL which was already realized by reducing the position.
+        let pnl = pnl + position.trader_realized_pnl_sat.unwrap_or_default();
+
         db::positions::Position::set_position_to_closed_with_pnl(conn, position.id, pnl)?;
 
         let coordinator_margin = calculate_margin(
-            Decimal::try_from(trade_params.average_price).expect("to fit into decimal"),
+            trade_params.average_execution_price(),
             trade_params.quantity,
             crate::trade::coordinator_leverage_for_trade(&trade_params.trader)
                 .map_err(|_| RollbackTransaction)?,
@@ -408,6 +772,291 @@ impl DlcProtocolExecutor {
         Ok(())
     }
 
+    /// Completes the partial settle dlc protocol as successful and updates the 10101 meta data
+    /// accordingly in a single database transaction.
+    /// - Set dlc protocol to success
+    /// - Reduces the `[PositionState::Resizing`] position by the traded quantity, adds the PnL
+    ///   realized on the reduced contracts and sets it back to `[PositionState::Open`]
+    /// - Creates and inserts the new trade
+    fn finish_partial_settle_dlc_protocol(
+        &self,
+        conn: &mut PgConnection,
+        trade_params: &TradeParams,
+        protocol_id: ProtocolId,
+        contract_id: &ContractId,
+        channel_id: &DlcChannelId,
+    ) -> QueryResult<()> {
+        db::dlc_protocols::set_dlc_protocol_state_to_success(
+            conn,
+            protocol_id,
+            contract_id,
+            channel_id,
+        )?;
+
+        let position = match db::positions::Position::get_position_by_trader(
+            conn,
+            trade_params.trader,
+            vec![PositionState::Resizing],
+        )? {
+            Some(position) => position,
+            None => {
+                tracing::error!("No position in state Resizing found.");
+                return Err(RollbackTransaction);
+            }
+        };
+
+        let reduction = match position.calculate_reduction(
+            trade_params.quantity,
+            trade_params.average_execution_price(),
+        ) {
+            Ok(reduction) => reduction,
+            Err(e) => {
+                tracing::error!("Failed to calculate position reduction. Error: {e:#}");
+                return Err(RollbackTransaction);
+            }
+        };
+
+        tracing::debug!(
+            ?position,
+            ?reduction,
+            trader_id = %trade_params.trader,
+            "Finalize reducing position",
+        );
+
+        db::positions::Position::set_reduced_position(
+            conn,
+            position.id,
+            reduction.remaining_quantity,
+            reduction.remaining_coordinator_margin as i64,
+            reduction.remaining_trader_margin as i64,
+            position.trader_realized_pnl_sat.unwrap_or_default()
+                + reduction.trader_realized_pnl_sat,
+            *contract_id,
+        )?;
+
+        let coordinator_margin = calculate_margin(
+            trade_params.average_execution_price(),
+            trade_params.quantity,
+            position.coordinator_leverage,
+        );
+
+        let new_trade = NewTrade {
+            position_id: position.id,
+            contract_symbol: position.contract_symbol,
+            trader_pubkey: trade_params.trader,
+            quantity: trade_params.quantity,
+            trader_leverage: trade_params.leverage,
+            coordinator_margin: coordinator_margin as i64,
+            trader_direction: trade_params.direction,
+            average_price: trade_params.average_price,
+            dlc_expiry_timestamp: None,
+        };
+
+        db::trades::insert(conn, new_trade)?;
+
+        db::trade_params::delete(conn, protocol_id)?;
+
+        Ok(())
+    }
+
+    /// Completes the renew dlc protocol which added to a position as successful and updates the
+    /// 10101 meta data accordingly in a single database transaction.
+    /// - Set dlc protocol to success
+    /// - Updates the quantity, average entry price and margins of the `[PositionState::Resizing`]
+    ///   position and sets it back to `[PositionState::Open`]
+    /// - Creates and inserts the new trade
+    fn finish_increase_position_dlc_protocol(
+        &self,
+        conn: &mut PgConnection,
+        position: &Position,
+        trade_params: &TradeParams,
+        protocol_id: ProtocolId,
+        contract_id: &ContractId,
+        channel_id: &DlcChannelId,
+    ) -> QueryResult<()> {
+        db::dlc_protocols::set_dlc_protocol_state_to_success(
+            conn,
+            protocol_id,
+            contract_id,
+            channel_id,
+        )?;
+
+        let increase = match position.calculate_increase(
+            trade_params.quantity,
+            trade_params.average_execution_price(),
+        ) {
+            Ok(increase) => increase,
+            Err(e) => {
+                tracing::error!("Failed to calculate position increase. Error: {e:#}");
+                return Err(RollbackTransaction);
+            }
+        };
+
+        tracing::debug!(
+            ?position,
+            ?increase,
+            trader_id = %trade_params.trader,
+            "Finalize increasing position",
+        );
+
+        db::positions::Position::set_increased_position(
+            conn,
+            position.id,
+            increase.quantity,
+            increase
+                .average_entry_price
+                .to_f32()
+                .expect("to fit into f32"),
+            increase
+                .trader_liquidation_price
+                .to_f32()
+                .expect("to fit into f32"),
+            increase.coordinator_margin as i64,
+            increase.trader_margin as i64,
+            *contract_id,
+        )?;
+
+        let coordinator_margin = calculate_margin(
+            trade_params.average_execution_price(),
+            trade_params.quantity,
+            position.coordinator_leverage,
+        );
+
+        let new_trade = NewTrade {
+            position_id: position.id,
+            contract_symbol: position.contract_symbol,
+            trader_pubkey: trade_params.trader,
+            quantity: trade_params.quantity,
+            trader_leverage: trade_params.leverage,
+            coordinator_margin: coordinator_margin as i64,
+            trader_direction: trade_params.direction,
+            average_price: trade_params.average_price,
+            dlc_expiry_timestamp: None,
+        };
+
+        db::trades::insert(conn, new_trade)?;
+
+        db::trade_params::delete(conn, protocol_id)?;
+
+        Ok(())
+    }
+
+    /// Completes the renew dlc protocol which flipped a position as successful and updates the
+    /// 10101 meta data accordingly in a single database transaction.
+    /// - Set dlc protocol to success
+    /// - Closes the `[PositionState::Resizing`] position with the PnL realized on it
+    /// - Inserts the `[PositionState::Open`] position in the opposite direction
+    /// - Creates and inserts a trade for the closed and for the opened contracts
+    ///
+    /// Returns the flip, describing the opened position.
+    fn finish_flip_position_dlc_protocol(
+        &self,
+        conn: &mut PgConnection,
+        position: &Position,
+        trade_params: &TradeParams,
+        protocol_id: ProtocolId,
+        contract_id: &ContractId,
+        channel_id: &DlcChannelId,
+    ) -> QueryResult<PositionFlip> {
+        db::dlc_protocols::set_dlc_protocol_state_to_success(
+            conn,
+            protocol_id,
+            contract_id,
+            channel_id,
+        )?;
+
+        let price = trade_params.average_execution_price();
+        let flip =
+            match position.calculate_flip(trade_params.quantity, price, trade_params.leverage) {
+                Ok(flip) => flip,
+                Err(e) => {
+                    tracing::error!("Failed to calculate position flip. Error: {e:#}");
+                    return Err(RollbackTransaction);
+                }
+            };
+
+        tracing::debug!(
+            ?position,
+            ?flip,
+            trader_id = %trade_params.trader,
+            "Finalize flipping position",
+        );
+
+        db::positions::Position::set_position_to_closed_with_pnl(
+            conn,
+            position.id,
+            position.trader_realized_pnl_sat.unwrap_or_default() + flip.trader_realized_pnl_sat,
+        )?;
+
+        let closing_trade = NewTrade {
+            position_id: position.id,
+            contract_symbol: position.contract_symbol,
+            trader_pubkey: trade_params.trader,
+            quantity: position.quantity,
+            trader_leverage: position.trader_leverage,
+            coordinator_margin: calculate_margin(
+                price,
+                position.quantity,
+                position.coordinator_leverage,
+            ) as i64,
+            trader_direction: trade_params.direction,
+            average_price: trade_params.average_price,
+            dlc_expiry_timestamp: None,
+        };
+
+        db::trades::insert(conn, closing_trade)?;
+
+        let new_position = crate::position::models::NewPosition {
+            contract_symbol: position.contract_symbol,
+            trader_leverage: flip.trader_leverage,
+            quantity: flip.quantity,
+            trader_direction: flip.trader_direction,
+            trader: trade_params.trader,
+            average_entry_price: trade_params.average_price,
+            trader_liquidation_price: flip
+                .trader_liquidation_price
+                .to_f32()
+                .expect("to fit into f32"),
+            coordinator_margin: flip.coordinator_margin as i64,
+            expiry_timestamp: position.expiry_timestamp,
+            temporary_contract_id: *contract_id,
+            coordinator_leverage: position.coordinator_leverage,
+            trader_margin: flip.trader_margin as i64,
+            stable: position.stable,
+            auto_rollover: position.auto_rollover,
+        };
+
+        if let Err(e) = db::positions::Position::insert(conn, new_position) {
+            tracing::error!("Failed to insert flipped position. Error: {e:#}");
+            return Err(RollbackTransaction);
+        }
+
+        // The new position is inserted as proposed, but its contract is already established.
+        let new_position = db::positions::Position::update_proposed_position(
+            conn,
+            trade_params.trader.to_string(),
+            PositionState::Open,
+        )?;
+
+        let opening_trade = NewTrade {
+            position_id: new_position.id,
+            contract_symbol: new_position.contract_symbol,
+            trader_pubkey: trade_params.trader,
+            quantity: flip.quantity,
+            trader_leverage: flip.trader_leverage,
+            coordinator_margin: flip.coordinator_margin as i64,
+            trader_direction: flip.trader_direction,
+            average_price: trade_params.average_price,
+            dlc_expiry_timestamp: None,
+        };
+
+        db::trades::insert(conn, opening_trade)?;
+
+        db::trade_params::delete(conn, protocol_id)?;
+
+        Ok(flip)
+    }
+
     /// Completes the open trade dlc protocol as successful and updates the 10101 meta data
     /// accordingly in a single database transaction.
     /// - Set dlc protocol to success
@@ -438,7 +1087,7 @@ impl DlcProtocolExecutor {
         )?;
 
         let coordinator_margin = calculate_margin(
-            Decimal::try_from(trade_params.average_price).expect("to fit into decimal"),
+            trade_params.average_execution_price(),
             trade_params.quantity,
             crate::trade::coordinator_leverage_for_trade(&trade_params.trader)
                 .map_err(|_| RollbackTransaction)?,
@@ -468,6 +1117,9 @@ impl DlcProtocolExecutor {
 
     /// Completes the rollover dlc protocol as successful and updates the 10101 meta data
     /// accordingly in a single database transaction.
+    /// - Set dlc protocol to success
+    /// - Sets the `[PositionState::Rollover`] position state to `[PositionState::Open`]
+    /// - Confirms the rollover fee charged by the protocol, if any
     fn finish_rollover_dlc_protocol(
         &self,
         conn: &mut PgConnection,
@@ -485,6 +1137,9 @@ impl DlcProtocolExecutor {
         )?;
 
         db::positions::Position::set_position_to_open(conn, trader.to_string(), *contract_id)?;
+
+        db::rollover_fees::confirm(conn, protocol_id)?;
+
         Ok(())
     }
 }
@@ -492,7 +1147,18 @@ impl DlcProtocolExecutor {
 #[cfg(test)]
 mod test {
     use crate::dlc_protocol::ProtocolId;
+    use crate::dlc_protocol::TradeParams;
+    use bitcoin::secp256k1::PublicKey;
+    use bitcoin::secp256k1::XOnlyPublicKey;
+    use commons::FilledWith;
+    use commons::Match;
     use dlc_manager::ReferenceId;
+    use rust_decimal_macros::dec;
+    use std::str::FromStr;
+    use time::OffsetDateTime;
+    use trade::ContractSymbol;
+    use trade::Direction;
+    use uuid::Uuid;
 
     #[test]
     fn test_protocol_id_roundtrip() {
@@ -504,4 +1170,87 @@ mod test {
 
         assert_eq!(protocol_id_0, protocol_id_1)
     }
+
+    #[test]
+    fn reference_id_with_non_hex_bytes_is_rejected() {
+        let mut reference_id = ReferenceId::from(ProtocolId::new());
+        reference_id[7] = b'g';
+
+        assert!(ProtocolId::try_from(reference_id).is_err());
+    }
+
+    #[test]
+    fn reference_id_with_non_utf8_bytes_is_rejected() {
+        let mut reference_id = ReferenceId::from(ProtocolId::new());
+        reference_id[0] = 0xff;
+
+        assert!(ProtocolId::try_from(reference_id).is_err());
+    }
+
+    #[test]
+    fn reference_id_with_truncated_hex_is_rejected() {
+        // Only 30 hex digits, padded with spaces, which would decode to too few bytes for a UUID.
+        let mut reference_id = ReferenceId::from(ProtocolId::new());
+        reference_id[30] = b' ';
+        reference_id[31] = b' ';
+
+        assert!(ProtocolId::try_from(reference_id).is_err());
+    }
+
+    #[test]
+    fn all_zero_reference_id_is_rejected() {
+        assert!(ProtocolId::try_from([0; 32]).is_err());
+    }
+
+    #[test]
+    fn all_zero_hex_reference_id_is_the_nil_protocol_id() {
+        let protocol_id = ProtocolId::try_from([b'0'; 32]).unwrap();
+
+        assert_eq!(protocol_id.to_uuid(), Uuid::nil());
+    }
+
+    #[test]
+    fn test_average_execution_price_conversion() {
+        let trader = PublicKey::from_str(
+            "02bd998ebd176715fe92b7467cf6b1df8023950a4dd911db4c94dfc89cc9f5a655",
+        )
+        .unwrap();
+        let maker = PublicKey::from_str(
+            "0218845781f631c48f1c9709e23092067d06837f30aa0cd0544ac887fe91ddd166",
+        )
+        .unwrap();
+
+        for execution_price in [dec!(36789.12), dec!(50000.5), dec!(12345.67)] {
+            let trade_params = commons::TradeParams {
+                pubkey: trader,
+                contract_symbol: ContractSymbol::BtcUsd,
+                leverage: 2.0,
+                quantity: 100.0,
+                direction: Direction::Short,
+                filled_with: FilledWith {
+                    order_id: Uuid::new_v4(),
+                    expiry_timestamp: OffsetDateTime::now_utc(),
+                    oracle_pk: XOnlyPublicKey::from_str(
+                        "16f88cf7d21e6c0f46bcbc983a4e3b19726c6c98858cc31c83551a88fde171c0",
+                    )
+                    .unwrap(),
+                    matches: vec![Match {
+                        id: Uuid::new_v4(),
+                        order_id: Uuid::new_v4(),
+                        quantity: dec!(100),
+                        pubkey: maker,
+                        execution_price,
+                    }],
+                },
+            };
+
+            let protocol_trade_params = TradeParams::from((ProtocolId::new(), &trade_params));
+
+            assert_eq!(protocol_trade_params.direction, trade_params.direction);
+            assert_eq!(
+                protocol_trade_params.average_execution_price(),
+                trade_params.average_execution_price()
+            );
+        }
+    }
 }
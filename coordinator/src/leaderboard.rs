@@ -268,6 +268,7 @@ pub mod tests {
             closing_price: None,
             trader_margin: 0,
             stable: false,
+            auto_rollover: true,
             trader_realized_pnl_sat: Some(pnl),
         }
     }
@@ -1,5 +1,6 @@
 use anyhow::anyhow;
 use anyhow::Result;
+use axum::http::header::RETRY_AFTER;
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use axum::response::Response;
@@ -15,6 +16,7 @@ use rust_decimal::prelude::FromPrimitive;
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use serde_json::json;
+use std::time::Duration;
 
 mod collaborative_revert;
 mod payout_curve;
@@ -40,8 +42,10 @@ pub mod routing_fee;
 pub mod scheduler;
 pub mod schema;
 pub mod settings;
+pub mod stats;
 pub mod storage;
 pub mod trade;
+pub mod webhook;
 
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
 
@@ -57,6 +61,10 @@ pub enum AppError {
     BadRequest(String),
     ServiceUnavailable(String),
     Unauthorized,
+    /// The request conflicts with the current state of the resource.
+    Conflict(String),
+    /// The client has to wait for the given duration before trying again.
+    TooManyRequests(Duration),
 }
 
 impl IntoResponse for AppError {
@@ -66,6 +74,20 @@ impl IntoResponse for AppError {
             AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
             AppError::ServiceUnavailable(msg) => (StatusCode::SERVICE_UNAVAILABLE, msg),
             AppError::Unauthorized => (StatusCode::UNAUTHORIZED, "".to_string()),
+            AppError::Conflict(msg) => (StatusCode::CONFLICT, msg),
+            AppError::TooManyRequests(retry_after) => {
+                let retry_after = retry_after.as_secs_f64().ceil() as u64;
+                let body = Json(json!({
+                    "error": format!("Too many requests, retry after {retry_after}s"),
+                }));
+
+                return (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    [(RETRY_AFTER, retry_after.to_string())],
+                    body,
+                )
+                    .into_response();
+            }
         };
 
         let body = Json(json!({
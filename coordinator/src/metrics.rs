@@ -1,10 +1,17 @@
 use crate::db;
+use crate::dlc_protocol::ProtocolKind;
 use crate::node::storage::NodeStorage;
 use crate::node::Node;
+use crate::position::timeline::ProtocolRecord;
 use crate::storage::CoordinatorTenTenOneStorage;
+use diesel::r2d2::event::CheckoutEvent;
+use diesel::r2d2::event::TimeoutEvent;
+use diesel::r2d2::HandleEvent;
 use lazy_static::lazy_static;
 use lightning::ln::channelmanager::ChannelDetails;
 use opentelemetry::global;
+use opentelemetry::metrics::Counter;
+use opentelemetry::metrics::Histogram;
 use opentelemetry::metrics::Meter;
 use opentelemetry::metrics::ObservableGauge;
 use opentelemetry::sdk::export::metrics::aggregation;
@@ -16,6 +23,7 @@ use opentelemetry::KeyValue;
 use opentelemetry_prometheus::PrometheusExporter;
 use std::sync::Arc;
 use std::time::Duration;
+use time::OffsetDateTime;
 use trade::ContractSymbol;
 use trade::Direction;
 
@@ -67,6 +75,155 @@ lazy_static! {
         .i64_observable_gauge("position_margin_sats")
         .with_description("Current open position margin in sats")
         .init();
+
+    // database connection pool metrics
+    pub static ref DB_POOL_CONNECTIONS: ObservableGauge<u64> = METER
+        .u64_observable_gauge("db_pool_connections")
+        .with_description("Number of connections in the database connection pool")
+        .init();
+    pub static ref DB_POOL_CHECKOUT_WAIT_SECONDS: Histogram<f64> = METER
+        .f64_histogram("db_pool_checkout_wait_seconds")
+        .with_description("Time spent waiting for a connection from the database connection pool")
+        .init();
+    pub static ref DB_POOL_CHECKOUT_TIMEOUTS: Counter<u64> = METER
+        .u64_counter("db_pool_checkout_timeouts_total")
+        .with_description("Number of timed out attempts to get a database connection")
+        .init();
+
+    // dlc protocol metrics
+    pub static ref DLC_PROTOCOL_DURATION_SECONDS: Histogram<f64> = METER
+        .f64_histogram("dlc_protocol_duration_seconds")
+        .with_description("Time from starting a DLC protocol until it finished or failed")
+        .init();
+    pub static ref DLC_PROTOCOL_OUTCOMES: Counter<u64> = METER
+        .u64_counter("dlc_protocol_outcomes_total")
+        .with_description("Number of DLC protocols which finished or failed")
+        .init();
+
+    pub static ref STUCK_DLC_PROTOCOLS: ObservableGauge<u64> = METER
+        .u64_observable_gauge("dlc_protocols_stuck")
+        .with_description("Number of DLC protocols pending for longer than their timeout")
+        .init();
+
+    pub static ref PAYOUT_MISMATCHES: ObservableGauge<u64> = METER
+        .u64_observable_gauge("dlc_payout_mismatches")
+        .with_description("Number of oracle-settled contracts with a payout diverging from our PnL")
+        .init();
+
+    // dlc message metrics
+    pub static ref UNKNOWN_PROTOCOL_DLC_MESSAGES: Counter<u64> = METER
+        .u64_counter("dlc_messages_unknown_protocol_total")
+        .with_description("Number of dropped DLC messages referring to an unknown DLC protocol")
+        .init();
+}
+
+/// Records how long it takes to check out connections from the database connection pool.
+#[derive(Debug)]
+pub struct DbPoolEventHandler;
+
+impl HandleEvent for DbPoolEventHandler {
+    fn handle_checkout(&self, event: CheckoutEvent) {
+        let cx = opentelemetry::Context::current();
+        DB_POOL_CHECKOUT_WAIT_SECONDS.record(&cx, event.duration().as_secs_f64(), &[]);
+    }
+
+    fn handle_timeout(&self, event: TimeoutEvent) {
+        tracing::warn!(
+            timeout = ?event.timeout(),
+            "Timed out waiting for a database connection"
+        );
+
+        let cx = opentelemetry::Context::current();
+        DB_POOL_CHECKOUT_TIMEOUTS.add(&cx, 1, &[]);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProtocolOutcome {
+    Success,
+    Failed,
+}
+
+impl ProtocolOutcome {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ProtocolOutcome::Success => "success",
+            ProtocolOutcome::Failed => "failed",
+        }
+    }
+}
+
+/// A DLC protocol which has either finished or failed.
+#[derive(Debug, PartialEq)]
+pub struct DlcProtocolSample {
+    pub kind: ProtocolKind,
+    pub outcome: ProtocolOutcome,
+    pub duration: Duration,
+}
+
+impl DlcProtocolSample {
+    pub fn new(
+        kind: ProtocolKind,
+        outcome: ProtocolOutcome,
+        started_at: OffsetDateTime,
+        ended_at: OffsetDateTime,
+    ) -> Self {
+        // The protocol timestamp is set by the database, so a clock skew could make the duration
+        // negative.
+        let duration = Duration::try_from(ended_at - started_at).unwrap_or(Duration::ZERO);
+
+        Self {
+            kind,
+            outcome,
+            duration,
+        }
+    }
+
+    fn labels(&self) -> [KeyValue; 2] {
+        [
+            KeyValue::new("protocol_type", self.kind.as_str()),
+            KeyValue::new("outcome", self.outcome.as_str()),
+        ]
+    }
+}
+
+/// Records the duration and the outcome of a DLC protocol.
+pub fn record_dlc_protocol(sample: &DlcProtocolSample) {
+    let cx = opentelemetry::Context::current();
+    let labels = sample.labels();
+
+    DLC_PROTOCOL_DURATION_SECONDS.record(&cx, sample.duration.as_secs_f64(), &labels);
+    DLC_PROTOCOL_OUTCOMES.add(&cx, 1, &labels);
+}
+
+/// Records the number of stuck DLC protocols per protocol type.
+pub fn record_stuck_dlc_protocols(stuck: &[&ProtocolRecord]) {
+    let cx = opentelemetry::Context::current();
+
+    for kind in [
+        ProtocolKind::Open,
+        ProtocolKind::Renew,
+        ProtocolKind::Settle,
+        ProtocolKind::PartialSettle,
+        ProtocolKind::Close,
+        ProtocolKind::Rollover,
+    ] {
+        let count = stuck
+            .iter()
+            .filter(|protocol| protocol.kind == kind)
+            .count();
+        STUCK_DLC_PROTOCOLS.observe(
+            &cx,
+            count as u64,
+            &[KeyValue::new("protocol_type", kind.as_str())],
+        );
+    }
+}
+
+/// Records the number of oracle-settled contracts whose payout diverges from the computed PnL.
+pub fn record_payout_mismatches(count: usize) {
+    let cx = opentelemetry::Context::current();
+    PAYOUT_MISMATCHES.observe(&cx, count as u64, &[]);
 }
 
 pub fn init_meter() -> PrometheusExporter {
@@ -82,6 +239,7 @@ pub fn init_meter() -> PrometheusExporter {
 
 pub fn collect(node: Node) {
     let cx = opentelemetry::Context::current();
+    db_pool_metrics(&cx, &node);
     position_metrics(&cx, &node);
 
     let inner_node = node.inner;
@@ -91,6 +249,23 @@ pub fn collect(node: Node) {
     node_metrics(&cx, inner_node);
 }
 
+fn db_pool_metrics(cx: &Context, node: &Node) {
+    let state = node.pool.state();
+    let max_size = node.pool.max_size();
+
+    DB_POOL_CONNECTIONS.observe(
+        cx,
+        (state.connections - state.idle_connections) as u64,
+        &[KeyValue::new("status", "in_use")],
+    );
+    DB_POOL_CONNECTIONS.observe(
+        cx,
+        state.idle_connections as u64,
+        &[KeyValue::new("status", "idle")],
+    );
+    DB_POOL_CONNECTIONS.observe(cx, max_size as u64, &[KeyValue::new("status", "max")]);
+}
+
 fn position_metrics(cx: &Context, node: &Node) {
     let mut conn = match node.pool.get() {
         Ok(conn) => conn,
@@ -240,3 +415,40 @@ fn node_metrics(
         ],
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    #[test]
+    fn completed_protocol_records_duration_sample() {
+        let sample = DlcProtocolSample::new(
+            ProtocolKind::Rollover,
+            ProtocolOutcome::Success,
+            datetime!(2024-03-25 10:00:00 UTC),
+            datetime!(2024-03-25 10:00:12.5 UTC),
+        );
+
+        assert_eq!(sample.duration, Duration::from_millis(12_500));
+        assert_eq!(
+            sample.labels(),
+            [
+                KeyValue::new("protocol_type", "rollover"),
+                KeyValue::new("outcome", "success"),
+            ]
+        );
+    }
+
+    #[test]
+    fn protocol_ending_before_it_started_records_zero_duration() {
+        let sample = DlcProtocolSample::new(
+            ProtocolKind::Open,
+            ProtocolOutcome::Failed,
+            datetime!(2024-03-25 10:00:01 UTC),
+            datetime!(2024-03-25 10:00:00 UTC),
+        );
+
+        assert_eq!(sample.duration, Duration::ZERO);
+    }
+}
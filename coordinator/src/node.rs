@@ -1,10 +1,19 @@
 use crate::db;
 use crate::dlc_protocol;
+use crate::dlc_protocol::DlcProtocolType;
 use crate::dlc_protocol::ProtocolId;
+use crate::dlc_protocol::ProtocolKind;
+use crate::metrics;
+use crate::node::index_price::IndexPriceFeed;
+use crate::node::index_price::IndexPriceGuardSettings;
+use crate::node::payout_reconciliation::PayoutReconciliationSettings;
 use crate::node::storage::NodeStorage;
+use crate::node::stuck_protocols::DlcProtocolTimeouts;
 use crate::position::models::PositionState;
+use crate::position::settlement_price::SettlementPriceSettings;
 use crate::storage::CoordinatorTenTenOneStorage;
 use crate::trade::websocket::InternalPositionUpdateMessage;
+use crate::webhook::PositionWebhookSettings;
 use anyhow::bail;
 use anyhow::Context;
 use anyhow::Result;
@@ -15,6 +24,9 @@ use diesel::PgConnection;
 use dlc_manager::channel::signed_channel::SignedChannel;
 use dlc_manager::channel::signed_channel::SignedChannelState;
 use dlc_manager::channel::Channel;
+use dlc_manager::ContractId;
+use dlc_manager::DlcChannelId;
+use dlc_manager::ReferenceId;
 use dlc_messages::channel::AcceptChannel;
 use dlc_messages::channel::Reject;
 use dlc_messages::channel::RenewFinalize;
@@ -29,14 +41,22 @@ use ln_dlc_node::dlc_message::SerializedDlcMessage;
 use ln_dlc_node::node;
 use ln_dlc_node::node::dlc_message_name;
 use ln_dlc_node::node::event::NodeEvent;
-use ln_dlc_node::node::RunningNode;
+use opentelemetry::KeyValue;
 use std::sync::Arc;
 use tokio::sync::broadcast::Sender;
 use tokio::sync::RwLock;
+use tokio::task::spawn_blocking;
 
+pub mod dead_mans_switch;
 pub mod expired_positions;
+pub mod force_close;
+pub mod identity;
+pub mod index_price;
+pub mod payout_reconciliation;
+pub mod reconcile;
 pub mod rollover;
 pub mod storage;
+pub mod stuck_protocols;
 pub mod unrealized_pnl;
 
 #[derive(Debug, Clone)]
@@ -44,6 +64,22 @@ pub struct NodeSettings {
     // At times, we want to disallow opening new positions (e.g. before
     // scheduled upgrade)
     pub allow_opening_positions: bool,
+    /// The minimum leverage a trader is allowed to trade with.
+    pub min_leverage: f32,
+    /// The maximum leverage a trader is allowed to trade with.
+    pub max_leverage: f32,
+    /// Where to deliver position lifecycle events to, if anywhere.
+    pub position_webhook: Option<PositionWebhookSettings>,
+    /// The fee in sats charged to the trader for rolling over their position.
+    pub rollover_fee_sats: u64,
+    /// How long a DLC protocol may stay pending before it is considered stuck.
+    pub dlc_protocol_timeouts: DlcProtocolTimeouts,
+    /// When to ignore an index price update as a glitch of the feed.
+    pub index_price_guard: IndexPriceGuardSettings,
+    /// Where the reference settlement price of each contract symbol comes from.
+    pub settlement_price: SettlementPriceSettings,
+    /// When to report a mismatch between the payout of an oracle-settled contract and our PnL.
+    pub payout_reconciliation: PayoutReconciliationSettings,
 }
 
 #[derive(Clone)]
@@ -55,9 +91,9 @@ pub struct Node {
             NodeStorage,
         >,
     >,
-    _running: Arc<RunningNode>,
     pub pool: Pool<ConnectionManager<PgConnection>>,
     pub settings: Arc<RwLock<NodeSettings>>,
+    pub index_price: Arc<IndexPriceFeed>,
     tx_position_feed: Sender<InternalPositionUpdateMessage>,
 }
 
@@ -70,7 +106,6 @@ impl Node {
                 NodeStorage,
             >,
         >,
-        running: RunningNode,
         pool: Pool<ConnectionManager<PgConnection>>,
         settings: NodeSettings,
         tx_position_feed: Sender<InternalPositionUpdateMessage>,
@@ -79,11 +114,37 @@ impl Node {
             inner,
             pool,
             settings: Arc::new(RwLock::new(settings)),
-            _running: Arc::new(running),
+            index_price: Arc::new(IndexPriceFeed::default()),
             tx_position_feed,
         }
     }
 
+    /// Start a DLC protocol, see [`dlc_protocol::DlcProtocolExecutor::start_dlc_protocol`].
+    ///
+    /// Starting a protocol may back off before retrying a transient database error, hence it is
+    /// run on a blocking thread rather than on the async runtime.
+    pub async fn start_dlc_protocol(
+        &self,
+        protocol_id: ProtocolId,
+        previous_protocol_id: Option<ProtocolId>,
+        contract_id: ContractId,
+        channel_id: DlcChannelId,
+        protocol_type: DlcProtocolType,
+    ) -> Result<()> {
+        let pool = self.pool.clone();
+        spawn_blocking(move || {
+            dlc_protocol::DlcProtocolExecutor::new(pool).start_dlc_protocol(
+                protocol_id,
+                previous_protocol_id,
+                &contract_id,
+                &channel_id,
+                protocol_type,
+            )
+        })
+        .await
+        .expect("task to complete")
+    }
+
     /// Returns true or false, whether the given peer_id is connected with us.
     pub fn is_connected(&self, peer_id: PublicKey) -> bool {
         self.inner
@@ -110,7 +171,7 @@ impl Node {
         for (node_id, msg) in messages {
             let msg_name = dlc_message_name(&msg);
             if let Err(e) = self.process_dlc_message(to_secp_pk_30(node_id), &msg) {
-                if let Err(e) = self.set_dlc_protocol_to_failed(&msg) {
+                if let Err(e) = self.set_dlc_protocol_to_failed(&msg, &format!("{e:#}")) {
                     tracing::error!(
                         from = %node_id,
                         "Failed to set dlc protocol to failed. {e:#}"
@@ -126,7 +187,7 @@ impl Node {
         }
     }
 
-    fn set_dlc_protocol_to_failed(&self, msg: &Message) -> Result<()> {
+    fn set_dlc_protocol_to_failed(&self, msg: &Message, reason: &str) -> Result<()> {
         let msg = match msg {
             Message::OnChain(_) => return Ok(()),
             Message::Channel(msg) => msg,
@@ -136,7 +197,7 @@ impl Node {
         if let Some(protocol_id) = msg.get_reference_id() {
             let protocol_id = ProtocolId::try_from(protocol_id)?;
             dlc_protocol::DlcProtocolExecutor::new(self.pool.clone())
-                .fail_dlc_protocol(protocol_id)?;
+                .fail_dlc_protocol(protocol_id, reason)?;
         }
 
         Ok(())
@@ -169,6 +230,34 @@ impl Node {
                 None
             }
             Message::Channel(channel_msg) => {
+                let is_dropped = drop_unknown_protocol_message(
+                    channel_msg.get_reference_id(),
+                    matches!(channel_msg, ChannelMessage::CollaborativeCloseOffer(_)),
+                    |reference_id| {
+                        dlc_protocol::DlcProtocolExecutor::new(self.pool.clone())
+                            .is_known_protocol(reference_id)
+                    },
+                    |reference_id| {
+                        tracing::warn!(
+                            from = %node_id,
+                            kind = %dlc_message_name(msg),
+                            reference_id = %hex::encode(reference_id),
+                            "Dropping DLC message referring to an unknown DLC protocol"
+                        );
+
+                        let cx = opentelemetry::Context::current();
+                        metrics::UNKNOWN_PROTOCOL_DLC_MESSAGES.add(
+                            &cx,
+                            1,
+                            &[KeyValue::new("kind", dlc_message_name(msg))],
+                        );
+                    },
+                )?;
+
+                if is_dropped {
+                    return Ok(());
+                }
+
                 let protocol_id = match channel_msg.get_reference_id() {
                     Some(reference_id) => Some(ProtocolId::try_from(reference_id)?),
                     None => None,
@@ -379,7 +468,8 @@ impl Node {
 
                         let protocol_executor =
                             dlc_protocol::DlcProtocolExecutor::new(self.pool.clone());
-                        protocol_executor.fail_dlc_protocol(protocol_id)?;
+                        protocol_executor
+                            .fail_dlc_protocol(protocol_id, "Rejected by the trader")?;
 
                         let channel = self.inner.get_dlc_channel_by_id(channel_id)?;
                         let mut connection = self.pool.get()?;
@@ -402,21 +492,40 @@ impl Node {
                                 state: SignedChannelState::Established { .. },
                                 ..
                             }) => {
-                                // TODO(holzeis): Reverting the position state back from `Closing`
-                                // to `Open` only works as long as we do not support resizing. This
-                                // logic needs to be adapted when we implement resize.
-
-                                tracing::info!(
-                                    channel_id = channel_id_hex_string,
-                                    node_id = node_id.to_string(),
-                                    "DLC Channel settle offer has been rejected. Setting position to back to open."
-                                );
-
-                                db::positions::Position::update_closing_position(
+                                let protocol = db::dlc_protocols::get_protocol_record(
                                     &mut connection,
-                                    node_id.to_string(),
-                                    PositionState::Open,
+                                    protocol_id,
                                 )?;
+
+                                // Renewing an established channel resizes the position.
+                                if matches!(
+                                    protocol.kind,
+                                    ProtocolKind::PartialSettle | ProtocolKind::Renew
+                                ) {
+                                    tracing::info!(
+                                        channel_id = channel_id_hex_string,
+                                        node_id = node_id.to_string(),
+                                        "DLC Channel renew offer to resize position has been rejected. Setting position to back to open."
+                                    );
+
+                                    db::positions::Position::update_resizing_position(
+                                        &mut connection,
+                                        node_id.to_string(),
+                                        PositionState::Open,
+                                    )?;
+                                } else {
+                                    tracing::info!(
+                                        channel_id = channel_id_hex_string,
+                                        node_id = node_id.to_string(),
+                                        "DLC Channel settle offer has been rejected. Setting position to back to open."
+                                    );
+
+                                    db::positions::Position::update_closing_position(
+                                        &mut connection,
+                                        node_id.to_string(),
+                                        PositionState::Open,
+                                    )?;
+                                }
                             }
                             Channel::Signed(SignedChannel {
                                 state: SignedChannelState::Settled { .. },
@@ -510,3 +619,89 @@ impl Node {
         Ok(())
     }
 }
+
+/// Whether to drop a channel message with the given reference id, because it refers to a DLC
+/// protocol we do not know of, e.g. after a replay. `on_drop` is called for a dropped message.
+///
+/// A collaborative close offer is initiated by the trader, hence it does not have to refer to a
+/// protocol we have started.
+fn drop_unknown_protocol_message(
+    reference_id: Option<ReferenceId>,
+    is_collab_close_offer: bool,
+    is_known_protocol: impl FnOnce(ReferenceId) -> Result<bool>,
+    on_drop: impl FnOnce(ReferenceId),
+) -> Result<bool> {
+    let reference_id = match reference_id {
+        Some(reference_id) if !is_collab_close_offer => reference_id,
+        _ => return Ok(false),
+    };
+
+    if is_known_protocol(reference_id)? {
+        return Ok(false);
+    }
+
+    on_drop(reference_id);
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn message_referring_to_unknown_protocol_is_dropped_and_counted() {
+        let dropped = Cell::new(0);
+
+        let is_dropped = drop_unknown_protocol_message(
+            Some(ProtocolId::new().into()),
+            false,
+            |_| Ok(false),
+            |_| dropped.set(dropped.get() + 1),
+        )
+        .unwrap();
+
+        assert!(is_dropped);
+        assert_eq!(dropped.get(), 1);
+    }
+
+    #[test]
+    fn message_referring_to_known_protocol_is_processed() {
+        let dropped = Cell::new(0);
+
+        let is_dropped = drop_unknown_protocol_message(
+            Some(ProtocolId::new().into()),
+            false,
+            |_| Ok(true),
+            |_| dropped.set(dropped.get() + 1),
+        )
+        .unwrap();
+
+        assert!(!is_dropped);
+        assert_eq!(dropped.get(), 0);
+    }
+
+    #[test]
+    fn collab_close_offer_is_processed_without_known_protocol() {
+        let dropped = Cell::new(0);
+
+        let is_dropped = drop_unknown_protocol_message(
+            Some(ProtocolId::new().into()),
+            true,
+            |_| panic!("collaborative close offer must not be looked up"),
+            |_| dropped.set(dropped.get() + 1),
+        )
+        .unwrap();
+
+        assert!(!is_dropped);
+        assert_eq!(dropped.get(), 0);
+    }
+
+    #[test]
+    fn message_without_reference_id_is_processed() {
+        let is_dropped = drop_unknown_protocol_message(None, false, |_| Ok(false), |_| {}).unwrap();
+
+        assert!(!is_dropped);
+    }
+}
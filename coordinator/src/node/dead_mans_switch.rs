@@ -0,0 +1,221 @@
+use crate::db;
+use crate::db::user::User;
+use crate::node::Node;
+use crate::orderbook;
+use anyhow::ensure;
+use anyhow::Context;
+use anyhow::Result;
+use bitcoin::secp256k1::PublicKey;
+use commons::Message;
+use commons::MIN_DEAD_MANS_SWITCH_TIMEOUT_SECS;
+use std::str::FromStr;
+use time::Duration;
+use time::OffsetDateTime;
+use tokio::sync::broadcast;
+
+/// Halt the trading of all traders whose dead man's switch has fired.
+///
+/// The switch is opt-in: only traders who have explicitly enabled it are considered. A trader who
+/// is connected to our node is never considered gone, even if they do not send heartbeats.
+///
+/// The open orders of the trader are cancelled and their position is no longer rolled over, so
+/// that it is closed once it expires. We do not close the position right away, as settling it
+/// needs the trader, who is gone.
+pub async fn halt(node: Node, tx_price_feed: broadcast::Sender<Message>) -> Result<()> {
+    let mut conn = node.pool.get()?;
+
+    let users = db::user::get_users_with_dead_mans_switch(&mut conn)
+        .context("Failed to fetch users with dead man's switch")?;
+
+    let now = OffsetDateTime::now_utc();
+    let triggered = triggered_traders(&users, now, |trader_id| node.is_connected(trader_id));
+    for (trader_id, user) in triggered {
+        let (cancelled, _) = orderbook::db::orders::cancel_all_for_trader(&mut conn, trader_id)
+            .context("Failed to cancel orders")?;
+        let cancelled = cancelled
+            .into_iter()
+            .map(|order| order.id)
+            .collect::<Vec<_>>();
+
+        for order_id in cancelled.iter() {
+            if let Err(e) = tx_price_feed.send(Message::DeleteOrder(*order_id)) {
+                tracing::error!(%order_id, "Could not update price feed: {e:#}");
+            }
+        }
+
+        let position_id = db::positions::Position::disable_auto_rollover(&mut conn, trader_id)
+            .context("Failed to disable auto-rollover")?;
+
+        // The trader has been halted already.
+        if cancelled.is_empty() && position_id.is_none() {
+            continue;
+        }
+
+        tracing::warn!(
+            %trader_id,
+            ?cancelled,
+            ?position_id,
+            last_seen = %user.last_seen(),
+            timeout_secs = ?user.dead_mans_switch_timeout_secs,
+            "Dead man's switch fired, halted trader"
+        );
+    }
+
+    Ok(())
+}
+
+/// Validate the timeout of a request to update a dead man's switch, converting it into what we
+/// store.
+pub fn parse_timeout(timeout_secs: Option<u32>) -> Result<Option<i32>> {
+    let timeout_secs = match timeout_secs {
+        Some(timeout_secs) => timeout_secs,
+        None => return Ok(None),
+    };
+
+    ensure!(
+        timeout_secs >= MIN_DEAD_MANS_SWITCH_TIMEOUT_SECS,
+        "Timeout must be at least {MIN_DEAD_MANS_SWITCH_TIMEOUT_SECS} seconds"
+    );
+
+    let timeout_secs = i32::try_from(timeout_secs).context("Timeout is too large")?;
+
+    Ok(Some(timeout_secs))
+}
+
+/// The traders among `users` whose dead man's switch has fired at `now`, unless they are
+/// connected to our node.
+fn triggered_traders<'a>(
+    users: &'a [User],
+    now: OffsetDateTime,
+    is_connected: impl Fn(PublicKey) -> bool,
+) -> Vec<(PublicKey, &'a User)> {
+    users
+        .iter()
+        .filter(|user| is_triggered(user, now))
+        .filter_map(|user| match PublicKey::from_str(&user.pubkey) {
+            Ok(trader_id) => Some((trader_id, user)),
+            Err(e) => {
+                tracing::error!(pubkey = user.pubkey, "Invalid trader pubkey: {e:#}");
+                None
+            }
+        })
+        .filter(|(trader_id, _)| !is_connected(*trader_id))
+        .collect()
+}
+
+/// Whether the dead man's switch of the given user has fired at `now`.
+fn is_triggered(user: &User, now: OffsetDateTime) -> bool {
+    match user.dead_mans_switch_timeout_secs {
+        Some(timeout_secs) => now - user.last_seen() > Duration::seconds(timeout_secs as i64),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn switch_fires_after_timeout_without_heartbeat() {
+        let now = OffsetDateTime::now_utc();
+        let user = User {
+            last_login: now - Duration::hours(2),
+            last_heartbeat: Some(now - Duration::minutes(61)),
+            dead_mans_switch_timeout_secs: Some(3600),
+            ..dummy_user(now)
+        };
+
+        assert!(is_triggered(&user, now));
+    }
+
+    #[test]
+    fn heartbeat_keeps_switch_from_firing() {
+        let now = OffsetDateTime::now_utc();
+        let user = User {
+            last_login: now - Duration::hours(2),
+            last_heartbeat: Some(now - Duration::minutes(59)),
+            dead_mans_switch_timeout_secs: Some(3600),
+            ..dummy_user(now)
+        };
+
+        assert!(!is_triggered(&user, now));
+    }
+
+    #[test]
+    fn switch_is_disabled_by_default() {
+        let now = OffsetDateTime::now_utc();
+        let user = User {
+            last_login: now - Duration::days(365),
+            ..dummy_user(now)
+        };
+
+        assert!(!is_triggered(&user, now));
+    }
+
+    #[test]
+    fn connected_traders_are_never_considered_gone() {
+        let now = OffsetDateTime::now_utc();
+        let gone = User {
+            last_login: now - Duration::hours(2),
+            dead_mans_switch_timeout_secs: Some(3600),
+            ..dummy_user(now)
+        };
+        let connected = User {
+            pubkey: "0218845781f631c48f1c9709e23092067d06837f30aa0cd0544ac887fe91ddd166"
+                .to_string(),
+            ..gone.clone()
+        };
+        let users = [gone.clone(), connected.clone()];
+
+        let triggered = triggered_traders(&users, now, |trader_id| {
+            trader_id.to_string() == connected.pubkey
+        });
+
+        let triggered = triggered
+            .into_iter()
+            .map(|(trader_id, _)| trader_id.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(triggered, vec![gone.pubkey]);
+    }
+
+    #[test]
+    fn invalid_pubkey_is_skipped() {
+        let now = OffsetDateTime::now_utc();
+        let user = User {
+            pubkey: "not a pubkey".to_string(),
+            last_login: now - Duration::hours(2),
+            dead_mans_switch_timeout_secs: Some(3600),
+            ..dummy_user(now)
+        };
+
+        assert!(triggered_traders(&[user], now, |_| false).is_empty());
+    }
+
+    #[test]
+    fn timeout_below_minimum_is_rejected() {
+        assert!(parse_timeout(Some(MIN_DEAD_MANS_SWITCH_TIMEOUT_SECS - 1)).is_err());
+        assert!(parse_timeout(Some(u32::MAX)).is_err());
+
+        assert_eq!(
+            parse_timeout(Some(MIN_DEAD_MANS_SWITCH_TIMEOUT_SECS)).unwrap(),
+            Some(MIN_DEAD_MANS_SWITCH_TIMEOUT_SECS as i32)
+        );
+        assert_eq!(parse_timeout(None).unwrap(), None);
+    }
+
+    fn dummy_user(now: OffsetDateTime) -> User {
+        User {
+            id: None,
+            pubkey: "02d5aa8fce495f6301b466594af056a46104dcdc6d735ec4793aa43108854cbd4a"
+                .to_string(),
+            contact: "".to_string(),
+            timestamp: now,
+            fcm_token: "".to_string(),
+            last_login: now,
+            nickname: None,
+            version: None,
+            dead_mans_switch_timeout_secs: None,
+            last_heartbeat: None,
+        }
+    }
+}
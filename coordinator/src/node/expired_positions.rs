@@ -78,23 +78,7 @@ pub async fn close(node: Node, trading_sender: mpsc::Sender<NewOrderMessage>) ->
 
         tracing::debug!(trader_pk=%position.trader, %position.expiry_timestamp, "Attempting to close expired position");
 
-        let new_order = NewOrder {
-            id: uuid::Uuid::new_v4(),
-            contract_symbol: position.contract_symbol,
-            // TODO(holzeis): we should not have to set the price for a market order. we propably
-            // need separate models for a limit and a market order.
-            price: Decimal::ZERO,
-            quantity: Decimal::try_from(position.quantity).expect("to fit into decimal"),
-            trader_id: position.trader,
-            direction: position.trader_direction.opposite(),
-            leverage: Decimal::from_f32(position.trader_leverage).expect("to fit into decimal"),
-            order_type: OrderType::Market,
-            // This order can basically not expire, but if the user does not come back online within
-            // a certain time period we can assume the channel to be abandoned and we should force
-            // close.
-            expiry: OffsetDateTime::now_utc().add(EXPIRED_POSITION_TIMEOUT),
-            stable: position.stable,
-        };
+        let new_order = closing_order(&position);
 
         let message = NewOrderMessage {
             new_order: new_order.clone(),
@@ -110,3 +94,25 @@ pub async fn close(node: Node, trading_sender: mpsc::Sender<NewOrderMessage>) ->
 
     Ok(())
 }
+
+/// A market order closing the given position on behalf of a trader who is not online.
+fn closing_order(position: &Position) -> NewOrder {
+    NewOrder {
+        id: uuid::Uuid::new_v4(),
+        contract_symbol: position.contract_symbol,
+        // TODO(holzeis): we should not have to set the price for a market order. we propably
+        // need separate models for a limit and a market order.
+        price: Decimal::ZERO,
+        quantity: Decimal::try_from(position.quantity).expect("to fit into decimal"),
+        trader_id: position.trader,
+        direction: position.trader_direction.opposite(),
+        leverage: Decimal::from_f32(position.trader_leverage).expect("to fit into decimal"),
+        order_type: OrderType::Market,
+        // This order can basically not expire, but if the user does not come back online within
+        // a certain time period we can assume the channel to be abandoned and we should force
+        // close.
+        expiry: OffsetDateTime::now_utc().add(EXPIRED_POSITION_TIMEOUT),
+        stable: position.stable,
+        auto_rollover: position.auto_rollover,
+    }
+}
@@ -0,0 +1,86 @@
+use crate::db;
+use crate::dlc_protocol;
+use crate::dlc_protocol::DlcProtocolType;
+use crate::dlc_protocol::ProtocolId;
+use crate::node::Node;
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+use dlc_manager::channel::Channel;
+use dlc_manager::DlcChannelId;
+use ln_dlc_node::bitcoin_conversion::to_secp_pk_30;
+use ln_dlc_node::node::dlc_channel::ForceCloseDlcChannelError;
+
+impl Node {
+    /// Force-close the DLC channel with the given ID and record it as a
+    /// [`DlcProtocolType::ForceClose`] protocol.
+    pub fn force_close_dlc_channel(
+        &self,
+        dlc_channel_id: DlcChannelId,
+    ) -> Result<(), ForceCloseDlcChannelError> {
+        // We load the channel before force-closing it, to know which contract and trader the
+        // force-close applies to.
+        let channel = self.inner.get_dlc_channel_by_id(&dlc_channel_id).ok();
+
+        self.inner.force_close_dlc_channel(dlc_channel_id)?;
+
+        if let Some(channel) = channel {
+            if let Err(e) = self.record_force_close(&channel) {
+                tracing::error!(
+                    channel_id = hex::encode(dlc_channel_id),
+                    "Failed to record force-close of DLC channel: {e:#}"
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    fn record_force_close(&self, channel: &Channel) -> Result<()> {
+        let signed_channel = match channel {
+            Channel::Signed(signed_channel) => signed_channel,
+            _ => bail!("Force-closed DLC channel was not signed"),
+        };
+
+        let previous_id = match channel.get_reference_id() {
+            Some(reference_id) => Some(ProtocolId::try_from(reference_id)?),
+            None => None,
+        };
+
+        // A settled channel does not have a contract anymore, so we fall back to the contract of
+        // the last protocol.
+        let contract_id = match (signed_channel.get_contract_id(), previous_id) {
+            (Some(contract_id), _) => contract_id,
+            (None, Some(previous_id)) => {
+                let mut conn = self.pool.get()?;
+                db::dlc_protocols::get_dlc_protocol(&mut conn, previous_id)?.contract_id
+            }
+            (None, None) => bail!("No contract to record force-close for"),
+        };
+
+        let protocol_id = ProtocolId::new();
+        let trader = to_secp_pk_30(signed_channel.counter_party);
+
+        tracing::info!(
+            %protocol_id,
+            %trader,
+            channel_id = hex::encode(signed_channel.channel_id),
+            contract_id = hex::encode(contract_id),
+            "Recording force-close of DLC channel"
+        );
+
+        let protocol_executor = dlc_protocol::DlcProtocolExecutor::new(self.pool.clone());
+        protocol_executor.start_dlc_protocol(
+            protocol_id,
+            previous_id,
+            &contract_id,
+            &signed_channel.channel_id,
+            DlcProtocolType::ForceClose { trader },
+        )?;
+        protocol_executor
+            .finish_force_close_dlc_protocol(protocol_id, &contract_id, &signed_channel.channel_id)
+            .context("Failed to finish force-close protocol")?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,111 @@
+use crate::db;
+use anyhow::bail;
+use anyhow::Result;
+use bitcoin::secp256k1::PublicKey;
+use diesel::PgConnection;
+
+/// Check that the node id derived from the seed matches the one the coordinator was last started
+/// with.
+///
+/// A different node id means that the coordinator was started with the wrong seed or data
+/// directory against an existing database. Continuing would leave every channel and position in
+/// the database unusable, so we refuse to start unless the change is acknowledged with
+/// `accept_change`.
+pub fn check_node_id(
+    conn: &mut PgConnection,
+    node_id: PublicKey,
+    accept_change: bool,
+) -> Result<()> {
+    let persisted = db::node_identity::get(conn)?;
+
+    match verify(persisted, node_id, accept_change)? {
+        Verification::Unchanged => {}
+        Verification::FirstStart => {
+            tracing::info!(%node_id, "Persisting node id");
+            db::node_identity::set(conn, node_id)?;
+        }
+        Verification::AcceptedChange { previous } => {
+            tracing::warn!(
+                %previous,
+                %node_id,
+                "Node id changed; continuing since the change was acknowledged"
+            );
+            db::node_identity::set(conn, node_id)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, PartialEq)]
+enum Verification {
+    Unchanged,
+    FirstStart,
+    AcceptedChange { previous: PublicKey },
+}
+
+fn verify(
+    persisted: Option<PublicKey>,
+    node_id: PublicKey,
+    accept_change: bool,
+) -> Result<Verification> {
+    match persisted {
+        None => Ok(Verification::FirstStart),
+        Some(previous) if previous == node_id => Ok(Verification::Unchanged),
+        Some(previous) if accept_change => Ok(Verification::AcceptedChange { previous }),
+        Some(previous) => {
+            tracing::error!(
+                %previous,
+                %node_id,
+                "Node id does not match the one the coordinator was last started with! Check \
+                 that the seed and the data directory belong to this database. If the change is \
+                 intentional, restart with --accept-node-id-change"
+            );
+
+            bail!("Node id changed from {previous} to {node_id}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn node_id_is_persisted_on_first_start() {
+        assert_eq!(
+            verify(None, node_id_a(), false).unwrap(),
+            Verification::FirstStart
+        );
+    }
+
+    #[test]
+    fn unchanged_node_id_is_accepted() {
+        assert_eq!(
+            verify(Some(node_id_a()), node_id_a(), false).unwrap(),
+            Verification::Unchanged
+        );
+    }
+
+    #[test]
+    fn changed_node_id_is_rejected_unless_acknowledged() {
+        assert!(verify(Some(node_id_a()), node_id_b(), false).is_err());
+        assert_eq!(
+            verify(Some(node_id_a()), node_id_b(), true).unwrap(),
+            Verification::AcceptedChange {
+                previous: node_id_a()
+            }
+        );
+    }
+
+    fn node_id_a() -> PublicKey {
+        PublicKey::from_str("02bd998ebd176715fe92b7467cf6b1df8023950a4dd911db4c94dfc89cc9f5a655")
+            .unwrap()
+    }
+
+    fn node_id_b() -> PublicKey {
+        PublicKey::from_str("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798")
+            .unwrap()
+    }
+}
@@ -0,0 +1,184 @@
+use crate::node::Node;
+use anyhow::Context;
+use anyhow::Result;
+use parking_lot::Mutex;
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde::Serialize;
+use std::time::Duration;
+use time::OffsetDateTime;
+use trade::bitmex_client::BitmexClient;
+use trade::bitmex_client::Quote;
+
+/// Bounds on how far the index price may move within a short window before an update is
+/// considered a glitch of the feed.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+pub struct IndexPriceGuardSettings {
+    /// The maximum deviation in percent from the last accepted index price.
+    pub max_deviation_percent: f32,
+    /// How long the last accepted index price serves as reference. Updates arriving later are
+    /// accepted regardless of their deviation, so that the feed recovers from a real price move.
+    pub window_secs: u64,
+}
+
+impl Default for IndexPriceGuardSettings {
+    fn default() -> Self {
+        Self {
+            max_deviation_percent: 10.0,
+            window_secs: 5 * 60,
+        }
+    }
+}
+
+/// The index price as reported by BitMEX, guarded against single bad ticks.
+///
+/// An update deviating too much from the last accepted index price is ignored and marks the feed
+/// as suspect until the next update is accepted. Liquidations must not be triggered while the
+/// feed is suspect; trading is not affected.
+#[derive(Default)]
+pub struct IndexPriceFeed {
+    inner: Mutex<IndexPriceFeedState>,
+}
+
+#[derive(Default)]
+struct IndexPriceFeedState {
+    last_accepted: Option<Quote>,
+    suspect: bool,
+}
+
+impl IndexPriceFeed {
+    /// Feed a new index price update through the guard.
+    ///
+    /// Returns `false` if the update was rejected.
+    pub fn update(&self, settings: &IndexPriceGuardSettings, quote: Quote) -> bool {
+        let mut state = self.inner.lock();
+
+        if let Some(last_accepted) = &state.last_accepted {
+            let window = Duration::from_secs(settings.window_secs);
+            let deviation = deviation_percent(last_accepted, &quote);
+
+            if quote.timestamp - last_accepted.timestamp <= window
+                && deviation > max_deviation(settings)
+            {
+                tracing::warn!(
+                    ?last_accepted,
+                    ?quote,
+                    %deviation,
+                    "Ignoring index price update; marking index price feed as suspect"
+                );
+
+                state.suspect = true;
+                return false;
+            }
+        }
+
+        state.last_accepted = Some(quote);
+        state.suspect = false;
+
+        true
+    }
+
+    /// The last accepted index price, if any.
+    pub fn quote(&self) -> Option<Quote> {
+        self.inner.lock().last_accepted.clone()
+    }
+
+    /// Whether the last index price update was rejected.
+    pub fn is_suspect(&self) -> bool {
+        self.inner.lock().suspect
+    }
+}
+
+/// Fetch the current index price and feed it through the guard.
+pub async fn sync(node: Node) -> Result<()> {
+    let quote = BitmexClient::get_quote(&node.inner.network, &OffsetDateTime::now_utc())
+        .await
+        .context("Failed to fetch quote from BitMEX")?;
+
+    let settings = node.settings.read().await.index_price_guard;
+    node.index_price.update(&settings, quote);
+
+    Ok(())
+}
+
+fn max_deviation(settings: &IndexPriceGuardSettings) -> Decimal {
+    Decimal::from_f32(settings.max_deviation_percent).unwrap_or(Decimal::MAX)
+}
+
+fn deviation_percent(reference: &Quote, quote: &Quote) -> Decimal {
+    let reference = mid_price(reference);
+    if reference.is_zero() {
+        return Decimal::MAX;
+    }
+
+    ((mid_price(quote) - reference) / reference).abs() * Decimal::ONE_HUNDRED
+}
+
+fn mid_price(quote: &Quote) -> Decimal {
+    (quote.bid_price + quote.ask_price) / Decimal::TWO
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+    use time::macros::datetime;
+
+    #[test]
+    fn glitch_tick_is_ignored() {
+        let settings = IndexPriceGuardSettings {
+            max_deviation_percent: 10.0,
+            window_secs: 60,
+        };
+        let feed = IndexPriceFeed::default();
+
+        assert!(feed.update(
+            &settings,
+            quote(dec!(50_000), datetime!(2024-03-27 10:00:00 UTC))
+        ));
+
+        let glitch = quote(dec!(5_000), datetime!(2024-03-27 10:00:10 UTC));
+        assert!(!feed.update(&settings, glitch));
+        assert!(feed.is_suspect());
+        assert_eq!(feed.quote().unwrap().bid_price, dec!(50_000));
+
+        assert!(feed.update(
+            &settings,
+            quote(dec!(50_500), datetime!(2024-03-27 10:00:20 UTC))
+        ));
+        assert!(!feed.is_suspect());
+        assert_eq!(feed.quote().unwrap().bid_price, dec!(50_500));
+    }
+
+    #[test]
+    fn large_move_outside_window_is_accepted() {
+        let settings = IndexPriceGuardSettings {
+            max_deviation_percent: 10.0,
+            window_secs: 60,
+        };
+        let feed = IndexPriceFeed::default();
+
+        feed.update(
+            &settings,
+            quote(dec!(50_000), datetime!(2024-03-27 10:00:00 UTC)),
+        );
+
+        assert!(feed.update(
+            &settings,
+            quote(dec!(60_000), datetime!(2024-03-27 10:01:01 UTC))
+        ));
+        assert!(!feed.is_suspect());
+    }
+
+    fn quote(price: Decimal, timestamp: OffsetDateTime) -> Quote {
+        Quote {
+            bid_size: 100,
+            ask_size: 100,
+            bid_price: price,
+            ask_price: price,
+            symbol: "XBTUSD".to_string(),
+            timestamp,
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use crate::db;
+use crate::dlc_protocol::DlcProtocolState;
+use crate::metrics;
+use crate::node::Node;
+use crate::position::models::Position;
+use crate::position::settlement_price::attested_price;
+use anyhow::Context;
+use anyhow::Result;
+use dlc_manager::contract::Contract;
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde::Serialize;
+use time::OffsetDateTime;
+
+/// How far the payout of a contract settled with an oracle attestation may diverge from the PnL
+/// we compute for the position.
+///
+/// The payout curve is discretized and the CET fee is shared between the parties, so the two
+/// never match exactly. A mismatch is only reported if it exceeds both `tolerance_sats` and
+/// `tolerance_percent` of the position's total margin.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+pub struct PayoutReconciliationSettings {
+    pub tolerance_sats: u64,
+    pub tolerance_percent: f32,
+}
+
+impl Default for PayoutReconciliationSettings {
+    fn default() -> Self {
+        Self {
+            tolerance_sats: 5_000,
+            tolerance_percent: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct PayoutMismatch {
+    pub position_id: i32,
+    pub expected_coordinator_pnl: i64,
+    pub actual_coordinator_pnl: i64,
+}
+
+/// Compare the payout of the contracts settled on-chain with an oracle attestation against the
+/// coordinator PnL we compute for the corresponding positions at the attested price.
+///
+/// Only expired positions which have not been closed collaboratively are checked, since those
+/// are the ones settled by the oracle. Mismatches are logged and recorded as a metric.
+pub async fn check(node: Node) -> Result<()> {
+    let settings = node.settings.read().await.payout_reconciliation;
+
+    let mut conn = node.pool.get()?;
+    let now = OffsetDateTime::now_utc();
+    let positions = db::positions::Position::get_all_open_or_closing_positions(&mut conn)?
+        .into_iter()
+        .filter(|position| position.expiry_timestamp <= now)
+        .collect::<Vec<_>>();
+
+    let mut mismatches = vec![];
+    for position in positions.iter() {
+        // The contract of the position is the one of the last successful protocol with the
+        // trader.
+        let contract_id =
+            match db::dlc_protocols::get_dlc_protocols_by_trader(&mut conn, &position.trader)?
+                .into_iter()
+                .rev()
+                .find(|protocol| protocol.state == DlcProtocolState::Success)
+            {
+                Some(protocol) => protocol.contract_id,
+                None => continue,
+            };
+
+        let closed_contract = match node.inner.get_contract_by_id(&contract_id)? {
+            Some(Contract::Closed(closed_contract)) => closed_contract,
+            _ => continue,
+        };
+
+        // Contracts closed without an attestation have been settled collaboratively.
+        let attestation = match closed_contract
+            .attestations
+            .as_ref()
+            .and_then(|attestations| attestations.first())
+        {
+            Some(attestation) => attestation,
+            None => continue,
+        };
+
+        let price = attested_price(attestation)?;
+        let expected_coordinator_pnl = position
+            .calculate_coordinator_pnl_at_price(price)
+            .with_context(|| format!("Failed to calculate PnL of position {}", position.id))?;
+
+        if let Some(mismatch) = find_mismatch(
+            &settings,
+            position,
+            expected_coordinator_pnl,
+            closed_contract.pnl,
+        ) {
+            tracing::error!(
+                position_id = position.id,
+                trader_id = %position.trader,
+                contract_id = hex::encode(contract_id),
+                %price,
+                expected_coordinator_pnl,
+                actual_coordinator_pnl = closed_contract.pnl,
+                "Payout of oracle-settled contract does not match the computed PnL"
+            );
+
+            mismatches.push(mismatch);
+        }
+    }
+
+    metrics::record_payout_mismatches(mismatches.len());
+
+    Ok(())
+}
+
+fn find_mismatch(
+    settings: &PayoutReconciliationSettings,
+    position: &Position,
+    expected_coordinator_pnl: i64,
+    actual_coordinator_pnl: i64,
+) -> Option<PayoutMismatch> {
+    let difference = expected_coordinator_pnl.abs_diff(actual_coordinator_pnl);
+
+    let total_margin = position.coordinator_margin + position.trader_margin;
+    let relative_tolerance = Decimal::from(total_margin)
+        * Decimal::from_f32(settings.tolerance_percent).unwrap_or_default()
+        / Decimal::ONE_HUNDRED;
+    let relative_tolerance = relative_tolerance.to_u64().unwrap_or_default();
+
+    if difference <= settings.tolerance_sats.max(relative_tolerance) {
+        return None;
+    }
+
+    Some(PayoutMismatch {
+        position_id: position.id,
+        expected_coordinator_pnl,
+        actual_coordinator_pnl,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::models::PositionState;
+    use bitcoin::secp256k1::PublicKey;
+    use std::str::FromStr;
+    use trade::ContractSymbol;
+    use trade::Direction;
+
+    #[test]
+    fn payout_within_tolerance_is_not_a_mismatch() {
+        let settings = PayoutReconciliationSettings {
+            tolerance_sats: 1_000,
+            tolerance_percent: 1.0,
+        };
+
+        // 1% of the total margin of 500_000 sats.
+        assert_eq!(find_mismatch(&settings, &position(), 100_000, 95_000), None);
+    }
+
+    #[test]
+    fn payout_beyond_tolerance_is_a_mismatch() {
+        let settings = PayoutReconciliationSettings {
+            tolerance_sats: 1_000,
+            tolerance_percent: 1.0,
+        };
+
+        assert_eq!(
+            find_mismatch(&settings, &position(), 100_000, 94_999),
+            Some(PayoutMismatch {
+                position_id: 1,
+                expected_coordinator_pnl: 100_000,
+                actual_coordinator_pnl: 94_999,
+            })
+        );
+        assert!(find_mismatch(&settings, &position(), -100_000, 100_000).is_some());
+    }
+
+    fn position() -> Position {
+        Position {
+            id: 1,
+            contract_symbol: ContractSymbol::BtcUsd,
+            trader_leverage: 2.0,
+            quantity: 100.0,
+            trader_direction: Direction::Long,
+            average_entry_price: 40_000.0,
+            trader_liquidation_price: 26_666.0,
+            position_state: PositionState::Open,
+            coordinator_margin: 250_000,
+            creation_timestamp: OffsetDateTime::now_utc(),
+            expiry_timestamp: OffsetDateTime::now_utc(),
+            update_timestamp: OffsetDateTime::now_utc(),
+            trader: PublicKey::from_str(
+                "02bd998ebd176715fe92b7467cf6b1df8023950a4dd911db4c94dfc89cc9f5a655",
+            )
+            .unwrap(),
+            coordinator_leverage: 2.0,
+            temporary_contract_id: None,
+            closing_price: None,
+            trader_margin: 250_000,
+            stable: false,
+            auto_rollover: true,
+            trader_realized_pnl_sat: None,
+        }
+    }
+}
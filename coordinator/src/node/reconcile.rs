@@ -0,0 +1,258 @@
+use crate::db;
+use crate::dlc_protocol::DlcProtocolState;
+use crate::node::Node;
+use crate::position::models::PositionState;
+use anyhow::Context;
+use anyhow::Result;
+use bitcoin::secp256k1::PublicKey;
+use dlc_manager::channel::signed_channel::SignedChannelState;
+use dlc_manager::channel::Channel;
+use dlc_manager::DlcChannelId;
+use ln_dlc_node::bitcoin_conversion::to_secp_pk_30;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// What to do about inconsistencies between positions and DLC channels found on startup.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ReconciliationPolicy {
+    /// Only log the inconsistencies.
+    #[default]
+    Report,
+    /// Close open positions without a DLC channel. DLC channels without a position are only
+    /// reported, since the position cannot be recovered from the channel alone.
+    ///
+    /// Positions with a pending DLC protocol, or whose trader has a channel we cannot classify,
+    /// are only reported as well.
+    Repair,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Discrepancy {
+    /// The position is open, but there is no signed DLC channel with the trader.
+    PositionWithoutChannel { trader: PublicKey, position_id: i32 },
+    /// The DLC channel has a contract, but the trader has no open or closing position.
+    ChannelWithoutPosition {
+        trader: PublicKey,
+        channel_id: DlcChannelId,
+    },
+}
+
+struct PositionSummary {
+    id: i32,
+    trader: PublicKey,
+    state: PositionState,
+}
+
+struct ChannelSummary {
+    channel_id: DlcChannelId,
+    trader: PublicKey,
+    has_contract: bool,
+}
+
+/// Cross-check the open positions in the database against the signed DLC channels, logging every
+/// inconsistency and repairing them according to the given [`ReconciliationPolicy`].
+pub fn reconcile_positions_with_dlc_channels(
+    node: &Node,
+    policy: ReconciliationPolicy,
+) -> Result<Vec<Discrepancy>> {
+    let mut conn = node.pool.get()?;
+
+    let positions = db::positions::Position::get_all_open_or_closing_positions(&mut conn)
+        .context("Failed to load open positions")?
+        .into_iter()
+        .map(|position| PositionSummary {
+            id: position.id,
+            trader: position.trader,
+            state: position.position_state,
+        })
+        .collect::<Vec<_>>();
+
+    let channels = node
+        .inner
+        .list_signed_dlc_channels()
+        .context("Failed to load signed DLC channels")?
+        .into_iter()
+        .map(|channel| ChannelSummary {
+            channel_id: channel.channel_id,
+            trader: to_secp_pk_30(channel.counter_party),
+            has_contract: matches!(channel.state, SignedChannelState::Established { .. }),
+        })
+        .collect::<Vec<_>>();
+
+    // The channel with these traders may back their position without being a signed DLC channel:
+    // it is either still being set up, or it is a legacy DLC channel inside a Lightning channel.
+    let unclassified_traders = node
+        .inner
+        .list_dlc_channels()
+        .context("Failed to load DLC channels")?
+        .into_iter()
+        .filter(|channel| matches!(channel, Channel::Offered(_) | Channel::Accepted(_)))
+        .map(|channel| to_secp_pk_30(channel.get_counter_party_id()))
+        .chain(
+            node.inner
+                .channel_manager
+                .list_channels()
+                .into_iter()
+                .map(|channel| to_secp_pk_30(channel.counterparty.node_id)),
+        )
+        .collect::<HashSet<_>>();
+
+    let discrepancies = find_discrepancies(&positions, &channels);
+
+    if discrepancies.is_empty() {
+        tracing::info!("Positions are consistent with DLC channels");
+        return Ok(discrepancies);
+    }
+
+    for discrepancy in discrepancies.iter() {
+        match discrepancy {
+            Discrepancy::PositionWithoutChannel {
+                trader,
+                position_id,
+            } => {
+                tracing::warn!(%trader, position_id, "Found open position without DLC channel");
+
+                if policy != ReconciliationPolicy::Repair {
+                    continue;
+                }
+
+                let has_pending_protocol =
+                    db::dlc_protocols::get_dlc_protocols_by_trader(&mut conn, trader)?
+                        .iter()
+                        .any(|protocol| protocol.state == DlcProtocolState::Pending);
+                if has_pending_protocol || unclassified_traders.contains(trader) {
+                    tracing::warn!(
+                        %trader,
+                        position_id,
+                        has_pending_protocol,
+                        "Not closing position, as its DLC channel cannot be classified"
+                    );
+                    continue;
+                }
+
+                db::positions::Position::set_position_to_closed(&mut conn, *position_id)?;
+                tracing::info!(%trader, position_id, "Closed position without DLC channel");
+            }
+            Discrepancy::ChannelWithoutPosition { trader, channel_id } => {
+                tracing::warn!(
+                    %trader,
+                    channel_id = hex::encode(channel_id),
+                    "Found DLC channel with contract but without position"
+                );
+            }
+        }
+    }
+
+    tracing::warn!(
+        n_discrepancies = discrepancies.len(),
+        ?policy,
+        "Positions are inconsistent with DLC channels"
+    );
+
+    Ok(discrepancies)
+}
+
+fn find_discrepancies(
+    positions: &[PositionSummary],
+    channels: &[ChannelSummary],
+) -> Vec<Discrepancy> {
+    let positions_without_channel = positions
+        .iter()
+        .filter(|position| position.state == PositionState::Open)
+        .filter(|position| !channels.iter().any(|c| c.trader == position.trader))
+        .map(|position| Discrepancy::PositionWithoutChannel {
+            trader: position.trader,
+            position_id: position.id,
+        });
+
+    let channels_without_position = channels
+        .iter()
+        .filter(|channel| channel.has_contract)
+        .filter(|channel| !positions.iter().any(|p| p.trader == channel.trader))
+        .map(|channel| Discrepancy::ChannelWithoutPosition {
+            trader: channel.trader,
+            channel_id: channel.channel_id,
+        });
+
+    positions_without_channel
+        .chain(channels_without_position)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn consistent_positions_and_channels() {
+        let trader = trader_a();
+
+        let positions = vec![PositionSummary {
+            id: 1,
+            trader,
+            state: PositionState::Open,
+        }];
+        let channels = vec![ChannelSummary {
+            channel_id: [1; 32],
+            trader,
+            has_contract: true,
+        }];
+
+        assert!(find_discrepancies(&positions, &channels).is_empty());
+    }
+
+    #[test]
+    fn open_position_without_channel() {
+        let positions = vec![PositionSummary {
+            id: 1,
+            trader: trader_a(),
+            state: PositionState::Open,
+        }];
+
+        assert_eq!(
+            find_discrepancies(&positions, &[]),
+            vec![Discrepancy::PositionWithoutChannel {
+                trader: trader_a(),
+                position_id: 1
+            }]
+        );
+    }
+
+    #[test]
+    fn channel_with_contract_without_position() {
+        let channels = vec![
+            ChannelSummary {
+                channel_id: [1; 32],
+                trader: trader_a(),
+                has_contract: true,
+            },
+            // A settled channel does not need a position.
+            ChannelSummary {
+                channel_id: [2; 32],
+                trader: trader_b(),
+                has_contract: false,
+            },
+        ];
+
+        assert_eq!(
+            find_discrepancies(&[], &channels),
+            vec![Discrepancy::ChannelWithoutPosition {
+                trader: trader_a(),
+                channel_id: [1; 32]
+            }]
+        );
+    }
+
+    fn trader_a() -> PublicKey {
+        PublicKey::from_str("02bd998ebd176715fe92b7467cf6b1df8023950a4dd911db4c94dfc89cc9f5a655")
+            .unwrap()
+    }
+
+    fn trader_b() -> PublicKey {
+        PublicKey::from_str("0218845781f631c48f1c9709e23092067d06837f30aa0cd0544ac887fe91ddd166")
+            .unwrap()
+    }
+}
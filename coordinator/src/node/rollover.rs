@@ -1,18 +1,20 @@
 use crate::check_version::check_version;
 use crate::db;
 use crate::db::positions;
-use crate::dlc_protocol;
 use crate::dlc_protocol::DlcProtocolType;
 use crate::dlc_protocol::ProtocolId;
 use crate::message::NewUserMessage;
 use crate::message::OrderbookMessage;
 use crate::node::Node;
+use crate::payout_curve;
+use crate::position::models::Position;
 use crate::position::models::PositionState;
 use anyhow::bail;
 use anyhow::Context;
 use anyhow::Result;
 use bitcoin::secp256k1::PublicKey;
 use bitcoin::secp256k1::XOnlyPublicKey;
+use bitcoin::Amount;
 use bitcoin::Network;
 use commons::Message;
 use diesel::r2d2::ConnectionManager;
@@ -154,6 +156,60 @@ impl Rollover {
     pub fn maturity_time(&self) -> OffsetDateTime {
         commons::calculate_next_expiry(OffsetDateTime::now_utc(), self.network)
     }
+
+    /// Charges the trader the `rollover_fee` by rebuilding the contract descriptor with the fee
+    /// moved from the trader's reserve to the coordinator's reserve.
+    ///
+    /// Returns the fee which is actually charged, see [`rollover_fee_to_charge`].
+    fn charge_fee(
+        &mut self,
+        position: &Position,
+        coordinator_reserve: Amount,
+        trader_reserve: Amount,
+        rollover_fee: Amount,
+    ) -> Result<Amount> {
+        let fee = rollover_fee_to_charge(rollover_fee, trader_reserve);
+        if fee < rollover_fee {
+            tracing::warn!(
+                trader_id = %self.counterparty_pubkey,
+                position_id = position.id,
+                %rollover_fee,
+                %trader_reserve,
+                "Trader cannot pay the full rollover fee from their reserve. Charging only what \
+                 the reserve covers"
+            );
+        }
+
+        if fee == Amount::ZERO {
+            return Ok(fee);
+        }
+
+        self.contract_descriptor = payout_curve::build_contract_descriptor(
+            crate::decimal_from_f32(position.average_entry_price),
+            position.coordinator_margin as u64,
+            position.trader_margin as u64,
+            position.coordinator_leverage,
+            position.trader_leverage,
+            position.trader_direction.opposite(),
+            (coordinator_reserve + fee).to_sat(),
+            (trader_reserve - fee).to_sat(),
+            position.quantity,
+            position.contract_symbol,
+        )
+        .context("Could not build contract descriptor")?;
+
+        Ok(fee)
+    }
+}
+
+/// The part of the `rollover_fee` which can be charged to a trader with the given reserve.
+///
+/// The fee is only ever paid from the trader's reserve, i.e. the coins in the DLC channel which
+/// are not at stake in the position. Charging the fee from the margin instead would move the
+/// liquidation price and could liquidate the position, so we rather waive what the reserve
+/// cannot cover.
+fn rollover_fee_to_charge(rollover_fee: Amount, trader_reserve: Amount) -> Amount {
+    rollover_fee.min(trader_reserve)
 }
 
 impl Node {
@@ -186,16 +242,7 @@ impl Node {
 
             let contract_id = signed_channel.get_contract_id();
 
-            if commons::is_eligible_for_rollover(OffsetDateTime::now_utc(), network)
-                && !position.is_expired()
-            {
-                let next_expiry =
-                    commons::calculate_next_expiry(OffsetDateTime::now_utc(), network);
-                if position.expiry_timestamp == next_expiry {
-                    tracing::trace!(%trader_id, position_id=position.id, "Position has already been rolled over");
-                    return Ok(());
-                }
-
+            if position.is_due_for_rollover(OffsetDateTime::now_utc(), network) {
                 tracing::debug!(%trader_id, position_id=position.id, "Proposing to rollover user's position");
 
                 let message = OrderbookMessage::TraderMessage {
@@ -223,11 +270,30 @@ impl Node {
         network: Network,
     ) -> Result<()> {
         let contract = self.inner.get_contract_by_dlc_channel_id(dlc_channel_id)?;
-        let rollover = Rollover::new(contract, network)?;
+        let mut rollover = Rollover::new(contract, network)?;
         let protocol_id = ProtocolId::new();
 
         tracing::debug!(node_id=%rollover.counterparty_pubkey, %protocol_id, "Rollover dlc channel");
 
+        let position = db::positions::Position::get_position_by_trader(
+            &mut self.pool.get()?,
+            rollover.counterparty_pubkey,
+            vec![PositionState::Open, PositionState::Rollover],
+        )?
+        .context("No position to rollover")?;
+
+        let rollover_fee = Amount::from_sat(self.settings.read().await.rollover_fee_sats);
+        let rollover_fee = if rollover_fee > Amount::ZERO {
+            let coordinator_reserve = self.inner.get_dlc_channel_usable_balance(dlc_channel_id)?;
+            let trader_reserve = self
+                .inner
+                .get_dlc_channel_usable_balance_counterparty(dlc_channel_id)?;
+
+            rollover.charge_fee(&position, coordinator_reserve, trader_reserve, rollover_fee)?
+        } else {
+            Amount::ZERO
+        };
+
         let contract_input: ContractInput = rollover.clone().into();
 
         let channel = self.inner.get_dlc_channel_by_id(dlc_channel_id)?;
@@ -241,19 +307,29 @@ impl Node {
             .propose_dlc_channel_update(dlc_channel_id, contract_input, protocol_id.into())
             .await?;
 
-        let protocol_executor = dlc_protocol::DlcProtocolExecutor::new(self.pool.clone());
-        protocol_executor.start_dlc_protocol(
+        self.start_dlc_protocol(
             protocol_id,
             previous_id,
-            &contract_id,
-            dlc_channel_id,
+            contract_id,
+            *dlc_channel_id,
             DlcProtocolType::Rollover {
                 trader: rollover.counterparty_pubkey,
             },
-        )?;
+        )
+        .await?;
 
-        // Sets the position state to rollover indicating that a rollover is in progress.
         let mut connection = self.pool.get()?;
+        if rollover_fee > Amount::ZERO {
+            db::rollover_fees::insert(
+                &mut connection,
+                protocol_id,
+                position.id,
+                rollover.counterparty_pubkey,
+                rollover_fee,
+            )?;
+        }
+
+        // Sets the position state to rollover indicating that a rollover is in progress.
         db::positions::Position::rollover_position(
             &mut connection,
             rollover.counterparty_pubkey.to_string(),
@@ -354,6 +430,22 @@ mod tests {
         assert_eq!(contract_input.contract_infos.len(), 1);
     }
 
+    #[test]
+    fn rollover_fee_is_capped_at_trader_reserve() {
+        assert_eq!(
+            rollover_fee_to_charge(Amount::from_sat(1_000), Amount::from_sat(50_000)),
+            Amount::from_sat(1_000)
+        );
+        assert_eq!(
+            rollover_fee_to_charge(Amount::from_sat(1_000), Amount::from_sat(400)),
+            Amount::from_sat(400)
+        );
+        assert_eq!(
+            rollover_fee_to_charge(Amount::from_sat(1_000), Amount::ZERO),
+            Amount::ZERO
+        );
+    }
+
     #[test]
     fn test_rollover_expired_position() {
         let expiry_timestamp = OffsetDateTime::now_utc().unix_timestamp() - 10_000;
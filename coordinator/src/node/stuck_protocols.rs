@@ -0,0 +1,146 @@
+use crate::db;
+use crate::dlc_protocol::ProtocolKind;
+use crate::metrics;
+use crate::node::Node;
+use crate::position::timeline::ProtocolRecord;
+use anyhow::Result;
+use serde::Deserialize;
+use serde::Serialize;
+use std::time::Duration;
+use time::OffsetDateTime;
+
+/// How long a DLC protocol may stay pending before it is considered stuck, per protocol type.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+pub struct DlcProtocolTimeouts {
+    pub open_secs: u64,
+    pub renew_secs: u64,
+    pub settle_secs: u64,
+    pub close_secs: u64,
+    pub rollover_secs: u64,
+}
+
+impl Default for DlcProtocolTimeouts {
+    fn default() -> Self {
+        Self {
+            open_secs: 10 * 60,
+            renew_secs: 10 * 60,
+            settle_secs: 10 * 60,
+            close_secs: 10 * 60,
+            // A rollover does not involve a trade, so there is no reason for it to take long.
+            rollover_secs: 5 * 60,
+        }
+    }
+}
+
+impl DlcProtocolTimeouts {
+    /// The timeout for the given kind of protocol.
+    ///
+    /// A force-close does not wait for the trader, hence it never times out.
+    pub fn timeout(&self, kind: ProtocolKind) -> Option<Duration> {
+        let secs = match kind {
+            ProtocolKind::Open => self.open_secs,
+            ProtocolKind::Renew => self.renew_secs,
+            ProtocolKind::Settle | ProtocolKind::PartialSettle => self.settle_secs,
+            ProtocolKind::Close => self.close_secs,
+            ProtocolKind::Rollover => self.rollover_secs,
+            ProtocolKind::ForceClose => return None,
+        };
+
+        Some(Duration::from_secs(secs))
+    }
+}
+
+/// Report the DLC protocols which have been pending for longer than their timeout.
+///
+/// The protocols are not failed automatically: the trader may still complete them, in which case
+/// the position has to be updated.
+pub async fn check(node: Node) -> Result<()> {
+    let timeouts = node.settings.read().await.dlc_protocol_timeouts;
+
+    let mut conn = node.pool.get()?;
+    let pending = db::dlc_protocols::get_pending_dlc_protocols(&mut conn)?;
+
+    let now = OffsetDateTime::now_utc();
+    let stuck = pending
+        .iter()
+        .filter(|protocol| is_stuck(&timeouts, protocol, now))
+        .collect::<Vec<_>>();
+
+    for protocol in stuck.iter() {
+        tracing::warn!(
+            protocol_id = %protocol.id,
+            kind = protocol.kind.as_str(),
+            started_at = %protocol.timestamp,
+            "DLC protocol is stuck"
+        );
+    }
+
+    metrics::record_stuck_dlc_protocols(&stuck);
+
+    Ok(())
+}
+
+fn is_stuck(
+    timeouts: &DlcProtocolTimeouts,
+    protocol: &ProtocolRecord,
+    now: OffsetDateTime,
+) -> bool {
+    match timeouts.timeout(protocol.kind) {
+        Some(timeout) => now - protocol.timestamp > timeout,
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dlc_protocol::DlcProtocolState;
+    use crate::dlc_protocol::ProtocolId;
+    use time::macros::datetime;
+
+    #[test]
+    fn rollover_times_out_before_open() {
+        let timeouts = DlcProtocolTimeouts {
+            open_secs: 600,
+            rollover_secs: 300,
+            ..DlcProtocolTimeouts::default()
+        };
+
+        let started_at = datetime!(2024-03-25 10:00:00 UTC);
+        let now = datetime!(2024-03-25 10:06:00 UTC);
+
+        let rollover = pending_protocol(ProtocolKind::Rollover, started_at);
+        let open = pending_protocol(ProtocolKind::Open, started_at);
+
+        assert!(is_stuck(&timeouts, &rollover, now));
+        assert!(!is_stuck(&timeouts, &open, now));
+        assert!(is_stuck(
+            &timeouts,
+            &open,
+            datetime!(2024-03-25 10:10:01 UTC)
+        ));
+    }
+
+    #[test]
+    fn force_close_is_never_stuck() {
+        let force_close =
+            pending_protocol(ProtocolKind::ForceClose, datetime!(2024-03-25 10:00:00 UTC));
+
+        assert!(!is_stuck(
+            &DlcProtocolTimeouts::default(),
+            &force_close,
+            datetime!(2024-03-26 10:00:00 UTC)
+        ));
+    }
+
+    fn pending_protocol(kind: ProtocolKind, timestamp: OffsetDateTime) -> ProtocolRecord {
+        ProtocolRecord {
+            id: ProtocolId::new(),
+            previous_id: None,
+            contract_id: [0; 32],
+            state: DlcProtocolState::Pending,
+            kind,
+            timestamp,
+        }
+    }
+}
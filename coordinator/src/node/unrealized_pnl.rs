@@ -6,8 +6,6 @@ use anyhow::Result;
 use diesel::r2d2::ConnectionManager;
 use diesel::r2d2::PooledConnection;
 use diesel::PgConnection;
-use time::OffsetDateTime;
-use trade::bitmex_client::BitmexClient;
 use trade::bitmex_client::Quote;
 
 pub async fn sync(node: Node) -> Result<()> {
@@ -16,9 +14,10 @@ pub async fn sync(node: Node) -> Result<()> {
     let positions = db::positions::Position::get_all_open_or_closing_positions(&mut conn)?;
 
     // TODO(holzeis): we should not use the bitmex quote here, but rather our own orderbook.
-    let current_quote = BitmexClient::get_quote(&node.inner.network, &OffsetDateTime::now_utc())
-        .await
-        .context("Failed to fetch quote from BitMEX")?;
+    let current_quote = node
+        .index_price
+        .quote()
+        .context("No index price available")?;
 
     for position in positions.iter() {
         if let Err(e) = sync_position(&mut conn, position, current_quote.clone()) {
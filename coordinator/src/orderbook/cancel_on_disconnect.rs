@@ -0,0 +1,69 @@
+use crate::orderbook::db::orders;
+use bitcoin::secp256k1::PublicKey;
+use commons::Order;
+use diesel::PgConnection;
+use diesel::QueryResult;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Keeps track of the websocket sessions which asked for the trader's orders to be cancelled
+/// once they disconnect, and of the orders placed while such a session was open.
+#[derive(Default)]
+pub struct CancelOnDisconnect {
+    sessions: Mutex<HashMap<PublicKey, FlaggedSessions>>,
+}
+
+#[derive(Default)]
+struct FlaggedSessions {
+    /// The number of open sessions with the flag.
+    count: usize,
+    /// The orders placed while a session with the flag was open.
+    orders: Vec<Uuid>,
+}
+
+impl CancelOnDisconnect {
+    pub fn register(&self, trader_id: PublicKey) {
+        self.sessions.lock().entry(trader_id).or_default().count += 1;
+    }
+
+    /// Tie the order to the trader's sessions with the flag, if they have any open.
+    ///
+    /// Orders placed while the trader has no such session are never cancelled on disconnect.
+    pub fn track_order(&self, trader_id: PublicKey, order_id: Uuid) {
+        if let Some(sessions) = self.sessions.lock().get_mut(&trader_id) {
+            sessions.orders.push(order_id);
+        }
+    }
+
+    /// Cancel the open orders tied to the trader's sessions with the flag, if the closed session
+    /// was the last of them.
+    ///
+    /// Returns the cancelled orders.
+    pub fn session_closed(
+        &self,
+        conn: &mut PgConnection,
+        trader_id: PublicKey,
+    ) -> QueryResult<Vec<Order>> {
+        match self.unregister(trader_id) {
+            Some(order_ids) => orders::cancel_open_orders(conn, &order_ids),
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Returns the orders tied to the trader's sessions with the flag, if this was the last of
+    /// them.
+    fn unregister(&self, trader_id: PublicKey) -> Option<Vec<Uuid>> {
+        let mut sessions = self.sessions.lock();
+
+        let flagged_sessions = sessions.get_mut(&trader_id)?;
+        if flagged_sessions.count > 1 {
+            flagged_sessions.count -= 1;
+            return None;
+        }
+
+        sessions
+            .remove(&trader_id)
+            .map(|flagged_sessions| flagged_sessions.orders)
+    }
+}
@@ -18,6 +18,7 @@ use diesel::PgConnection;
 use rust_decimal::prelude::FromPrimitive;
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
+use std::collections::BTreeMap;
 use time::OffsetDateTime;
 use trade::Direction as OrderbookDirection;
 use uuid::Uuid;
@@ -102,6 +103,7 @@ struct Order {
     pub leverage: f32,
     pub order_reason: OrderReason,
     pub stable: bool,
+    pub auto_rollover: bool,
 }
 
 impl From<Order> for OrderbookOrder {
@@ -121,6 +123,7 @@ impl From<Order> for OrderbookOrder {
             order_state: value.order_state.into(),
             order_reason: value.order_reason.into(),
             stable: value.stable,
+            auto_rollover: value.auto_rollover,
         }
     }
 }
@@ -157,6 +160,7 @@ struct NewOrder {
     pub contract_symbol: ContractSymbol,
     pub leverage: f32,
     pub stable: bool,
+    pub auto_rollover: bool,
 }
 
 impl From<OrderbookNewOrder> for NewOrder {
@@ -184,6 +188,7 @@ impl From<OrderbookNewOrder> for NewOrder {
                 .to_f32()
                 .expect("To be able to convert decimal to f32"),
             stable: value.stable,
+            auto_rollover: value.auto_rollover,
         }
     }
 }
@@ -199,28 +204,64 @@ pub fn all_limit_orders(conn: &mut PgConnection) -> QueryResult<Vec<OrderbookOrd
 }
 
 /// Loads all orders by the given order direction and type
+///
+/// The orders are returned in price-time priority: best price first, i.e. descending for long
+/// and ascending for short orders, and the earliest order first at each price level.
 pub fn all_by_direction_and_type(
     conn: &mut PgConnection,
     direction: OrderbookDirection,
     order_type: OrderBookOrderType,
     filter_expired: bool,
 ) -> QueryResult<Vec<OrderbookOrder>> {
-    let filters = orders::table
+    let mut query = orders::table
         .filter(orders::direction.eq(Direction::from(direction)))
         .filter(orders::order_type.eq(OrderType::from(order_type)))
-        .filter(orders::order_state.eq(OrderState::Open));
+        .filter(orders::order_state.eq(OrderState::Open))
+        .into_boxed();
 
-    let orders: Vec<Order> = if filter_expired {
-        filters
-            .filter(orders::expiry.gt(OffsetDateTime::now_utc()))
-            .load::<Order>(conn)?
-    } else {
-        filters.load::<Order>(conn)?
+    if filter_expired {
+        query = query.filter(orders::expiry.gt(OffsetDateTime::now_utc()));
+    }
+
+    query = match direction {
+        OrderbookDirection::Long => query.order_by(orders::price.desc()),
+        OrderbookDirection::Short => query.order_by(orders::price.asc()),
     };
 
+    // The `timestamp` is set by the database on insertion. The id breaks ties between orders
+    // inserted at the same time.
+    let orders = query
+        .then_order_by(orders::timestamp.asc())
+        .then_order_by(orders::id.asc())
+        .load::<Order>(conn)?;
+
     Ok(orders.into_iter().map(OrderbookOrder::from).collect())
 }
 
+/// Aggregates the open limit orders in the given direction into price levels.
+///
+/// Returns the total quantity per price, best price first: descending for bids (long) and
+/// ascending for asks (short).
+pub fn aggregated_depth(
+    conn: &mut PgConnection,
+    direction: OrderbookDirection,
+) -> QueryResult<Vec<(Decimal, Decimal)>> {
+    let orders = all_by_direction_and_type(conn, direction, OrderBookOrderType::Limit, true)?;
+
+    let mut depth = BTreeMap::<Decimal, Decimal>::new();
+    for order in orders {
+        *depth.entry(order.price).or_default() += order.quantity;
+    }
+
+    let depth = depth.into_iter();
+    let depth = match direction {
+        OrderbookDirection::Long => depth.rev().collect(),
+        OrderbookDirection::Short => depth.collect(),
+    };
+
+    Ok(depth)
+}
+
 pub fn get_all_orders(
     conn: &mut PgConnection,
     order_type: OrderBookOrderType,
@@ -271,11 +312,85 @@ pub fn set_is_taken(
     }
 }
 
+/// Updates the price of an open limit order.
+///
+/// The order's timestamp is reset, so that the amended order loses its time priority at its
+/// previous price level.
+///
+/// Returns `None` if there is no open limit order with the given id, e.g. because it has been
+/// taken already.
+pub fn update_price(
+    conn: &mut PgConnection,
+    id: Uuid,
+    price: Decimal,
+) -> QueryResult<Option<OrderbookOrder>> {
+    let price = price
+        .round_dp(2)
+        .to_f32()
+        .expect("To be able to convert decimal to f32");
+
+    let order: Option<Order> = diesel::update(orders::table)
+        .filter(orders::trader_order_id.eq(id))
+        .filter(orders::order_type.eq(OrderType::Limit))
+        .filter(orders::order_state.eq(OrderState::Open))
+        .set((
+            orders::price.eq(price),
+            orders::timestamp.eq(OffsetDateTime::now_utc()),
+        ))
+        .get_result(conn)
+        .optional()?;
+
+    Ok(order.map(OrderbookOrder::from))
+}
+
 /// Updates the order state to `Deleted`
 pub fn delete(conn: &mut PgConnection, id: Uuid) -> QueryResult<OrderbookOrder> {
     set_order_state(conn, id, commons::OrderState::Deleted)
 }
 
+/// Updates the state of all open orders of the trader to `Deleted` in one go.
+///
+/// Orders which have been taken already are left alone and returned separately, so that the
+/// trader knows which of their orders are still being executed.
+pub fn cancel_all_for_trader(
+    conn: &mut PgConnection,
+    trader_id: PublicKey,
+) -> QueryResult<(Vec<OrderbookOrder>, Vec<OrderbookOrder>)> {
+    conn.transaction(|conn| {
+        let cancelled: Vec<Order> = diesel::update(orders::table)
+            .filter(orders::trader_id.eq(trader_id.to_string()))
+            .filter(orders::order_state.eq(OrderState::Open))
+            .set(orders::order_state.eq(OrderState::Deleted))
+            .get_results(conn)?;
+
+        let taken: Vec<Order> = orders::table
+            .filter(orders::trader_id.eq(trader_id.to_string()))
+            .filter(orders::order_state.eq(OrderState::Taken))
+            .load(conn)?;
+
+        Ok((
+            cancelled.into_iter().map(OrderbookOrder::from).collect(),
+            taken.into_iter().map(OrderbookOrder::from).collect(),
+        ))
+    })
+}
+
+/// Cancel those of the given orders which are still open.
+///
+/// Returns the cancelled orders.
+pub fn cancel_open_orders(
+    conn: &mut PgConnection,
+    order_ids: &[Uuid],
+) -> QueryResult<Vec<OrderbookOrder>> {
+    let cancelled: Vec<Order> = diesel::update(orders::table)
+        .filter(orders::trader_order_id.eq_any(order_ids))
+        .filter(orders::order_state.eq(OrderState::Open))
+        .set(orders::order_state.eq(OrderState::Deleted))
+        .get_results(conn)?;
+
+    Ok(cancelled.into_iter().map(OrderbookOrder::from).collect())
+}
+
 /// Returns the number of affected rows: 1.
 pub fn set_order_state(
     conn: &mut PgConnection,
@@ -306,6 +421,30 @@ pub fn set_expired_limit_orders_to_expired(
         .collect())
 }
 
+/// Deletes the orders which expired before `now` without ever being matched.
+///
+/// Orders which have been matched are kept, as the matches refer to them. This includes orders
+/// which have been reopened after their match failed.
+///
+/// Returns the number of deleted orders.
+pub fn delete_expired(conn: &mut PgConnection, now: OffsetDateTime) -> QueryResult<usize> {
+    diesel::delete(orders::table)
+        .filter(orders::expiry.lt(now))
+        .filter(
+            orders::order_state
+                .eq(OrderState::Open)
+                .or(orders::order_state.eq(OrderState::Expired)),
+        )
+        .filter(diesel::dsl::not(diesel::dsl::exists(
+            matches::table.filter(
+                matches::order_id
+                    .eq(orders::trader_order_id)
+                    .or(matches::match_order_id.eq(orders::trader_order_id)),
+            ),
+        )))
+        .execute(conn)
+}
+
 /// Returns the order by id
 pub fn get_with_id(conn: &mut PgConnection, uid: Uuid) -> QueryResult<Option<OrderbookOrder>> {
     let x = orders::table
@@ -1,7 +1,9 @@
 pub mod async_match;
+pub mod cancel_on_disconnect;
 pub mod collaborative_revert;
 pub mod db;
 pub mod routes;
+pub mod throttle;
 pub mod trading;
 pub mod websocket;
 
@@ -11,6 +11,10 @@ use axum::extract::Path;
 use axum::extract::State;
 use axum::response::IntoResponse;
 use axum::Json;
+use commons::is_signed_request_valid;
+use commons::AmendOrderPriceRequest;
+use commons::CancelAllOrdersRequest;
+use commons::CancelledOrders;
 use commons::Message;
 use commons::NewOrderRequest;
 use commons::Order;
@@ -20,11 +24,14 @@ use commons::OrderType;
 use diesel::r2d2::ConnectionManager;
 use diesel::r2d2::PooledConnection;
 use diesel::PgConnection;
+use rust_decimal::Decimal;
 use serde::Deserialize;
 use serde::Serialize;
 use std::sync::Arc;
+use time::OffsetDateTime;
 use tokio::sync::broadcast::Sender;
 use tracing::instrument;
+use trade::Direction;
 use uuid::Uuid;
 
 #[instrument(skip_all, err(Debug))]
@@ -62,6 +69,43 @@ pub async fn get_orders(State(state): State<Arc<AppState>>) -> Result<Json<Vec<O
     Ok(Json(orders))
 }
 
+/// The aggregated open limit orders, best price first.
+#[derive(Serialize)]
+pub struct OrderbookDepth {
+    pub bids: Vec<PriceLevel>,
+    pub asks: Vec<PriceLevel>,
+}
+
+#[derive(Serialize)]
+pub struct PriceLevel {
+    #[serde(with = "rust_decimal::serde::float")]
+    pub price: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub quantity: Decimal,
+}
+
+#[instrument(skip_all, err(Debug))]
+pub async fn get_depth(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<OrderbookDepth>, AppError> {
+    let mut conn = get_db_connection(&state)?;
+    let mut price_levels = |direction| {
+        orderbook::db::orders::aggregated_depth(&mut conn, direction)
+            .map(|depth| {
+                depth
+                    .into_iter()
+                    .map(|(price, quantity)| PriceLevel { price, quantity })
+                    .collect::<Vec<_>>()
+            })
+            .map_err(|e| AppError::InternalServerError(format!("Failed to load depth: {e:#}")))
+    };
+
+    let bids = price_levels(Direction::Long)?;
+    let asks = price_levels(Direction::Short)?;
+
+    Ok(Json(OrderbookDepth { bids, asks }))
+}
+
 #[instrument(skip_all, err(Debug))]
 pub async fn post_order(
     State(state): State<Arc<AppState>>,
@@ -73,6 +117,20 @@ pub async fn post_order(
 
     let new_order = new_order_request.value;
 
+    let min_order_interval = state.settings.read().await.min_order_interval;
+    // Released again if the order is rejected below, i.e. if it is dropped without confirmation.
+    let reservation = state
+        .order_throttle
+        .try_reserve(new_order.trader_id, min_order_interval)
+        .map_err(|retry_after| {
+            tracing::debug!(
+                trader_id = %new_order.trader_id,
+                ?retry_after,
+                "Throttled order submission"
+            );
+            AppError::TooManyRequests(retry_after)
+        })?;
+
     // TODO(holzeis): We should add a similar check eventually for limit orders (makers).
     if new_order.order_type == OrderType::Market {
         let mut conn = state
@@ -94,6 +152,10 @@ pub async fn post_order(
         );
         return Err(AppError::Unauthorized);
     }
+
+    let trader_id = new_order.trader_id;
+    let order_id = new_order.id;
+    let order_type = new_order.order_type;
     let message = NewOrderMessage {
         new_order,
         channel_opening_params: new_order_request.channel_opening_params,
@@ -103,6 +165,12 @@ pub async fn post_order(
         AppError::InternalServerError(format!("Failed to send new order message: {e:#}"))
     })?;
 
+    reservation.confirm();
+
+    if order_type == OrderType::Limit {
+        state.cancel_on_disconnect.track_order(trader_id, order_id);
+    }
+
     Ok(())
 }
 
@@ -118,8 +186,12 @@ fn update_pricefeed(pricefeed_msg: Message, sender: Sender<Message>) {
 }
 
 #[derive(Deserialize, Serialize)]
-pub struct UpdateOrder {
-    pub taken: bool,
+#[serde(untagged)]
+pub enum UpdateOrder {
+    /// Marks the order as taken, or as open again.
+    Taken { taken: bool },
+    /// Amends the price of an open limit order, without removing it from the orderbook.
+    Price(AmendOrderPriceRequest),
 }
 
 #[instrument(skip_all, err(Debug))]
@@ -129,14 +201,54 @@ pub async fn put_order(
     Json(updated_order): Json<UpdateOrder>,
 ) -> Result<Json<Order>, AppError> {
     let mut conn = get_db_connection(&state)?;
-    let order = orderbook::db::orders::set_is_taken(&mut conn, order_id, updated_order.taken)
-        .map_err(|e| AppError::InternalServerError(format!("Failed to update order: {e:#}")))?;
+    let order = match updated_order {
+        UpdateOrder::Taken { taken } => {
+            orderbook::db::orders::set_is_taken(&mut conn, order_id, taken).map_err(|e| {
+                AppError::InternalServerError(format!("Failed to update order: {e:#}"))
+            })?
+        }
+        UpdateOrder::Price(request) => {
+            let order = orderbook::db::orders::get_with_id(&mut conn, order_id)
+                .map_err(|e| AppError::InternalServerError(format!("Failed to load order: {e:#}")))?
+                .context(format!("Order not found {order_id}"))
+                .map_err(|e| AppError::BadRequest(format!("{e:#}")))?;
+
+            request
+                .verify(&state.secp, order_id, &order.trader_id)
+                .map_err(|_| AppError::Unauthorized)?;
+
+            update_order_price(&mut conn, order_id, request.price)?
+        }
+    };
     let sender = state.tx_price_feed.clone();
     update_pricefeed(Message::Update(order.clone()), sender);
 
     Ok(Json(order))
 }
 
+fn update_order_price(
+    conn: &mut PgConnection,
+    order_id: Uuid,
+    price: Decimal,
+) -> Result<Order, AppError> {
+    let order = orderbook::db::orders::update_price(conn, order_id, price)
+        .map_err(|e| AppError::InternalServerError(format!("Failed to update order: {e:#}")))?;
+
+    if let Some(order) = order {
+        return Ok(order);
+    }
+
+    match orderbook::db::orders::get_with_id(conn, order_id)
+        .map_err(|e| AppError::InternalServerError(format!("Failed to load order: {e:#}")))?
+    {
+        Some(order) => Err(AppError::Conflict(format!(
+            "Cannot amend {:?} order {order_id} in state {:?}",
+            order.order_type, order.order_state
+        ))),
+        None => Err(AppError::BadRequest(format!("Order not found {order_id}"))),
+    }
+}
+
 #[instrument(skip_all, err(Debug))]
 pub async fn delete_order(
     Path(order_id): Path<Uuid>,
@@ -151,6 +263,46 @@ pub async fn delete_order(
     Ok(Json(order))
 }
 
+pub async fn cancel_all_orders(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<CancelAllOrdersRequest>,
+) -> Result<Json<CancelledOrders>, AppError> {
+    request
+        .verify(&state.secp)
+        .map_err(|_| AppError::Unauthorized)?;
+
+    if !is_signed_request_valid(request.timestamp, OffsetDateTime::now_utc()) {
+        return Err(AppError::BadRequest(
+            "Request to cancel all orders has expired".to_string(),
+        ));
+    }
+
+    let mut conn = get_db_connection(&state)?;
+    let (cancelled, taken) =
+        orderbook::db::orders::cancel_all_for_trader(&mut conn, request.trader_id).map_err(
+            |e| AppError::InternalServerError(format!("Failed to cancel orders: {e:#}")),
+        )?;
+
+    let cancelled = cancelled
+        .into_iter()
+        .map(|order| order.id)
+        .collect::<Vec<_>>();
+    let taken = taken.into_iter().map(|order| order.id).collect::<Vec<_>>();
+
+    tracing::info!(
+        trader_id = %request.trader_id,
+        ?cancelled,
+        ?taken,
+        "Cancelled all open orders of trader"
+    );
+
+    for order_id in cancelled.iter() {
+        update_pricefeed(Message::DeleteOrder(*order_id), state.tx_price_feed.clone());
+    }
+
+    Ok(Json(CancelledOrders { cancelled, taken }))
+}
+
 pub async fn websocket_handler(
     ws: WebSocketUpgrade,
     State(state): State<Arc<AppState>>,
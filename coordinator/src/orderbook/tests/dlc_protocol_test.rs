@@ -0,0 +1,709 @@
+use crate::db;
+use crate::db::user;
+use crate::dlc_protocol::DlcProtocolExecutor;
+use crate::dlc_protocol::DlcProtocolState;
+use crate::dlc_protocol::DlcProtocolType;
+use crate::dlc_protocol::ProtocolId;
+use crate::dlc_protocol::TradeParams;
+use crate::logger::init_tracing_for_test;
+use crate::orderbook::tests::setup_db;
+use crate::orderbook::tests::start_postgres;
+use crate::position::models::NewPosition;
+use crate::position::models::Position;
+use crate::position::models::PositionState;
+use crate::schema::trade_params;
+use crate::trade::websocket::InternalPositionUpdateMessage;
+use crate::webhook::PositionEventType;
+use bitcoin::secp256k1::PublicKey;
+use bitcoin::Amount;
+use diesel::r2d2;
+use diesel::r2d2::ConnectionManager;
+use diesel::ExpressionMethods;
+use diesel::PgConnection;
+use diesel::QueryDsl;
+use diesel::RunQueryDsl;
+use std::str::FromStr;
+use testcontainers::clients::Cli;
+use time::OffsetDateTime;
+use tokio::sync::broadcast;
+use trade::ContractSymbol;
+use trade::Direction;
+
+#[tokio::test]
+async fn concurrently_finished_dlc_protocol_is_only_applied_once() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    let mut conn = setup_db(conn_spec.clone());
+    let pool = r2d2::Pool::builder()
+        .build(ConnectionManager::<PgConnection>::new(conn_spec))
+        .unwrap();
+
+    let trader = dummy_public_key();
+    user::upsert_user(&mut conn, trader, None, None, None).unwrap();
+
+    let executor = DlcProtocolExecutor::new(pool);
+    let protocol_id = ProtocolId::new();
+    let channel_id = [1; 32];
+    executor
+        .start_dlc_protocol(
+            protocol_id,
+            None,
+            &[0; 32],
+            &channel_id,
+            DlcProtocolType::Rollover { trader },
+        )
+        .unwrap();
+
+    let (tx_position_feed, mut rx_position_feed) = broadcast::channel(100);
+
+    let executor = &executor;
+    let tx_position_feed = &tx_position_feed;
+    std::thread::scope(|s| {
+        let finishers = (0..2)
+            .map(|_| {
+                s.spawn(move || {
+                    executor.finish_dlc_protocol(
+                        protocol_id,
+                        &trader,
+                        Some([2; 32]),
+                        &channel_id,
+                        tx_position_feed.clone(),
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for finisher in finishers {
+            finisher.join().unwrap().unwrap();
+        }
+    });
+
+    let dlc_protocol = db::dlc_protocols::get_dlc_protocol(&mut conn, protocol_id).unwrap();
+    assert_eq!(dlc_protocol.protocol_state, DlcProtocolState::Success);
+
+    let mut n_position_events = 0;
+    while let Ok(message) = rx_position_feed.try_recv() {
+        if let InternalPositionUpdateMessage::PositionEvent(_) = message {
+            n_position_events += 1;
+        }
+    }
+    assert_eq!(n_position_events, 1);
+}
+
+#[tokio::test]
+async fn starting_dlc_protocol_twice_is_idempotent() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    let mut conn = setup_db(conn_spec.clone());
+    let pool = r2d2::Pool::builder()
+        .build(ConnectionManager::<PgConnection>::new(conn_spec))
+        .unwrap();
+
+    let trader = dummy_public_key();
+    user::upsert_user(&mut conn, trader, None, None, None).unwrap();
+
+    let executor = DlcProtocolExecutor::new(pool);
+    let protocol_id = ProtocolId::new();
+    let start = || {
+        executor.start_dlc_protocol(
+            protocol_id,
+            None,
+            &[0; 32],
+            &[1; 32],
+            DlcProtocolType::Close { trader },
+        )
+    };
+
+    start().unwrap();
+    start().unwrap();
+
+    let protocols = db::dlc_protocols::get_dlc_protocols_by_trader(&mut conn, &trader).unwrap();
+    assert_eq!(protocols.len(), 1);
+    assert_eq!(protocols[0].state, DlcProtocolState::Pending);
+
+    executor
+        .fail_dlc_protocol(protocol_id, "Rejected by the trader")
+        .unwrap();
+
+    assert!(start().is_err());
+}
+
+#[tokio::test]
+async fn starting_dlc_protocol_with_trade_params_twice_is_idempotent() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    let mut conn = setup_db(conn_spec.clone());
+    let pool = r2d2::Pool::builder()
+        .build(ConnectionManager::<PgConnection>::new(conn_spec))
+        .unwrap();
+
+    let trader = dummy_public_key();
+    user::upsert_user(&mut conn, trader, None, None, None).unwrap();
+
+    let executor = DlcProtocolExecutor::new(pool);
+
+    let protocol_types: [fn(TradeParams) -> DlcProtocolType; 2] = [
+        |trade_params| DlcProtocolType::Open { trade_params },
+        |trade_params| DlcProtocolType::Renew { trade_params },
+    ];
+    for protocol_type in protocol_types {
+        let protocol_id = ProtocolId::new();
+        let start = || {
+            executor.start_dlc_protocol(
+                protocol_id,
+                None,
+                &[0; 32],
+                &[1; 32],
+                protocol_type(dummy_trade_params(protocol_id, trader)),
+            )
+        };
+
+        start().unwrap();
+        start().unwrap();
+
+        let n_trade_params: i64 = trade_params::table
+            .filter(trade_params::protocol_id.eq(protocol_id.to_uuid()))
+            .count()
+            .get_result(&mut conn)
+            .unwrap();
+        assert_eq!(n_trade_params, 1);
+    }
+}
+
+#[tokio::test]
+async fn restarting_dlc_protocol_with_different_type_or_channel_fails() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    let mut conn = setup_db(conn_spec.clone());
+    let pool = r2d2::Pool::builder()
+        .build(ConnectionManager::<PgConnection>::new(conn_spec))
+        .unwrap();
+
+    let trader = dummy_public_key();
+    user::upsert_user(&mut conn, trader, None, None, None).unwrap();
+
+    let executor = DlcProtocolExecutor::new(pool);
+    let protocol_id = ProtocolId::new();
+    executor
+        .start_dlc_protocol(
+            protocol_id,
+            None,
+            &[0; 32],
+            &[1; 32],
+            DlcProtocolType::Close { trader },
+        )
+        .unwrap();
+
+    assert!(executor
+        .start_dlc_protocol(
+            protocol_id,
+            None,
+            &[0; 32],
+            &[1; 32],
+            DlcProtocolType::Rollover { trader },
+        )
+        .is_err());
+    assert!(executor
+        .start_dlc_protocol(
+            protocol_id,
+            None,
+            &[0; 32],
+            &[2; 32],
+            DlcProtocolType::Close { trader },
+        )
+        .is_err());
+}
+
+#[tokio::test]
+async fn failure_reason_is_persisted() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    let mut conn = setup_db(conn_spec.clone());
+    let pool = r2d2::Pool::builder()
+        .build(ConnectionManager::<PgConnection>::new(conn_spec))
+        .unwrap();
+
+    let trader = dummy_public_key();
+    user::upsert_user(&mut conn, trader, None, None, None).unwrap();
+
+    let executor = DlcProtocolExecutor::new(pool);
+    let protocol_id = ProtocolId::new();
+    executor
+        .start_dlc_protocol(
+            protocol_id,
+            None,
+            &[0; 32],
+            &[1; 32],
+            DlcProtocolType::Rollover { trader },
+        )
+        .unwrap();
+
+    let dlc_protocol = db::dlc_protocols::get_dlc_protocol(&mut conn, protocol_id).unwrap();
+    assert_eq!(dlc_protocol.failure_reason, None);
+
+    executor
+        .fail_dlc_protocol(protocol_id, "Invalid renew offer")
+        .unwrap();
+
+    let dlc_protocol = db::dlc_protocols::get_dlc_protocol(&mut conn, protocol_id).unwrap();
+    assert_eq!(dlc_protocol.protocol_state, DlcProtocolState::Failed);
+    assert_eq!(
+        dlc_protocol.failure_reason.as_deref(),
+        Some("Invalid renew offer")
+    );
+}
+
+#[tokio::test]
+async fn failing_finished_dlc_protocol_leaves_it_untouched() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    let mut conn = setup_db(conn_spec.clone());
+    let pool = r2d2::Pool::builder()
+        .build(ConnectionManager::<PgConnection>::new(conn_spec))
+        .unwrap();
+
+    let trader = dummy_public_key();
+    user::upsert_user(&mut conn, trader, None, None, None).unwrap();
+
+    let executor = DlcProtocolExecutor::new(pool);
+    let protocol_id = ProtocolId::new();
+    let channel_id = [1; 32];
+    executor
+        .start_dlc_protocol(
+            protocol_id,
+            None,
+            &[0; 32],
+            &channel_id,
+            DlcProtocolType::Rollover { trader },
+        )
+        .unwrap();
+
+    let (tx_position_feed, _rx_position_feed) = broadcast::channel(100);
+    executor
+        .finish_dlc_protocol(
+            protocol_id,
+            &trader,
+            Some([2; 32]),
+            &channel_id,
+            tx_position_feed,
+        )
+        .unwrap();
+
+    executor
+        .fail_dlc_protocol(protocol_id, "Rejected by the trader")
+        .unwrap();
+
+    let dlc_protocol = db::dlc_protocols::get_dlc_protocol(&mut conn, protocol_id).unwrap();
+    assert_eq!(dlc_protocol.protocol_state, DlcProtocolState::Success);
+    assert_eq!(dlc_protocol.failure_reason, None);
+}
+
+#[tokio::test]
+async fn protocols_are_loaded_by_channel_and_contract_id() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    let mut conn = setup_db(conn_spec.clone());
+    let pool = r2d2::Pool::builder()
+        .build(ConnectionManager::<PgConnection>::new(conn_spec))
+        .unwrap();
+
+    let trader = dummy_public_key();
+    user::upsert_user(&mut conn, trader, None, None, None).unwrap();
+
+    let executor = DlcProtocolExecutor::new(pool);
+    let channel_id = [1; 32];
+    let other_channel_id = [2; 32];
+
+    let first_rollover = ProtocolId::new();
+    executor
+        .start_dlc_protocol(
+            first_rollover,
+            None,
+            &[3; 32],
+            &channel_id,
+            DlcProtocolType::Rollover { trader },
+        )
+        .unwrap();
+
+    let second_rollover = ProtocolId::new();
+    executor
+        .start_dlc_protocol(
+            second_rollover,
+            Some(first_rollover),
+            &[3; 32],
+            &channel_id,
+            DlcProtocolType::Rollover { trader },
+        )
+        .unwrap();
+
+    let close = ProtocolId::new();
+    executor
+        .start_dlc_protocol(
+            close,
+            Some(second_rollover),
+            &[4; 32],
+            &channel_id,
+            DlcProtocolType::Close { trader },
+        )
+        .unwrap();
+
+    executor
+        .start_dlc_protocol(
+            ProtocolId::new(),
+            None,
+            &[5; 32],
+            &other_channel_id,
+            DlcProtocolType::Close { trader },
+        )
+        .unwrap();
+
+    let protocols = db::dlc_protocols::get_by_channel_id(&mut conn, &channel_id).unwrap();
+    assert_eq!(
+        protocols
+            .iter()
+            .map(|protocol| protocol.id)
+            .collect::<Vec<_>>(),
+        vec![first_rollover, second_rollover, close]
+    );
+
+    let protocols = db::dlc_protocols::get_by_contract_id(&mut conn, &[3; 32]).unwrap();
+    assert_eq!(
+        protocols
+            .iter()
+            .map(|protocol| protocol.id)
+            .collect::<Vec<_>>(),
+        vec![first_rollover, second_rollover]
+    );
+
+    assert!(db::dlc_protocols::get_by_contract_id(&mut conn, &[6; 32])
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn unknown_reference_id_is_not_a_known_protocol() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    let mut conn = setup_db(conn_spec.clone());
+    let pool = r2d2::Pool::builder()
+        .build(ConnectionManager::<PgConnection>::new(conn_spec))
+        .unwrap();
+
+    let trader = dummy_public_key();
+    user::upsert_user(&mut conn, trader, None, None, None).unwrap();
+
+    let executor = DlcProtocolExecutor::new(pool);
+    let protocol_id = ProtocolId::new();
+    executor
+        .start_dlc_protocol(
+            protocol_id,
+            None,
+            &[0; 32],
+            &[1; 32],
+            DlcProtocolType::Rollover { trader },
+        )
+        .unwrap();
+
+    assert!(executor.is_known_protocol(protocol_id.into()).unwrap());
+
+    let unknown_protocol_id = ProtocolId::new();
+    assert!(!executor
+        .is_known_protocol(unknown_protocol_id.into())
+        .unwrap());
+
+    // Not a valid protocol id.
+    assert!(!executor.is_known_protocol([0xff; 32]).unwrap());
+}
+
+#[tokio::test]
+async fn rollover_fee_is_only_confirmed_once_rollover_finished() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    let mut conn = setup_db(conn_spec.clone());
+    let pool = r2d2::Pool::builder()
+        .build(ConnectionManager::<PgConnection>::new(conn_spec))
+        .unwrap();
+
+    let trader = dummy_public_key();
+    user::upsert_user(&mut conn, trader, None, None, None).unwrap();
+
+    let position = db::positions::Position::insert(
+        &mut conn,
+        NewPosition {
+            contract_symbol: ContractSymbol::BtcUsd,
+            trader_leverage: 2.0,
+            quantity: 100.0,
+            trader_direction: Direction::Long,
+            trader,
+            average_entry_price: 50_000.0,
+            trader_liquidation_price: 33_333.0,
+            coordinator_margin: 200_000,
+            expiry_timestamp: OffsetDateTime::now_utc(),
+            temporary_contract_id: [0; 32],
+            coordinator_leverage: 1.0,
+            trader_margin: 100_000,
+            stable: false,
+            auto_rollover: true,
+        },
+    )
+    .unwrap();
+
+    let executor = DlcProtocolExecutor::new(pool);
+    let channel_id = [1; 32];
+
+    let finished_rollover = ProtocolId::new();
+    executor
+        .start_dlc_protocol(
+            finished_rollover,
+            None,
+            &[0; 32],
+            &channel_id,
+            DlcProtocolType::Rollover { trader },
+        )
+        .unwrap();
+    db::rollover_fees::insert(
+        &mut conn,
+        finished_rollover,
+        position.id,
+        trader,
+        Amount::from_sat(1_000),
+    )
+    .unwrap();
+
+    let pending_rollover = ProtocolId::new();
+    executor
+        .start_dlc_protocol(
+            pending_rollover,
+            Some(finished_rollover),
+            &[2; 32],
+            &channel_id,
+            DlcProtocolType::Rollover { trader },
+        )
+        .unwrap();
+    db::rollover_fees::insert(
+        &mut conn,
+        pending_rollover,
+        position.id,
+        trader,
+        Amount::from_sat(2_000),
+    )
+    .unwrap();
+
+    let (tx_position_feed, _rx_position_feed) = broadcast::channel(100);
+    executor
+        .finish_dlc_protocol(
+            finished_rollover,
+            &trader,
+            Some([2; 32]),
+            &channel_id,
+            tx_position_feed,
+        )
+        .unwrap();
+
+    let fees = db::rollover_fees::get_confirmed_by_trader(&mut conn, trader).unwrap();
+
+    assert_eq!(fees.len(), 1);
+    assert_eq!(fees[0].protocol_id, finished_rollover);
+    assert_eq!(fees[0].position_id, position.id);
+    assert_eq!(fees[0].fee, Amount::from_sat(1_000));
+}
+
+#[tokio::test]
+async fn renew_adding_to_position_emits_increased_event() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    let mut conn = setup_db(conn_spec.clone());
+    let pool = r2d2::Pool::builder()
+        .build(ConnectionManager::<PgConnection>::new(conn_spec))
+        .unwrap();
+
+    let trader = dummy_public_key();
+    user::upsert_user(&mut conn, trader, None, None, None).unwrap();
+
+    insert_resizing_position(&mut conn, trader, Direction::Long);
+
+    let executor = DlcProtocolExecutor::new(pool);
+    let protocol_id = ProtocolId::new();
+    let channel_id = [1; 32];
+    executor
+        .start_dlc_protocol(
+            protocol_id,
+            None,
+            &[0; 32],
+            &channel_id,
+            DlcProtocolType::Renew {
+                trade_params: dummy_trade_params(protocol_id, trader),
+            },
+        )
+        .unwrap();
+
+    let (tx_position_feed, mut rx_position_feed) = broadcast::channel(100);
+    executor
+        .finish_dlc_protocol(
+            protocol_id,
+            &trader,
+            Some([2; 32]),
+            &channel_id,
+            tx_position_feed,
+        )
+        .unwrap();
+
+    assert_eq!(
+        position_event_types(&mut rx_position_feed),
+        vec![PositionEventType::Increased]
+    );
+}
+
+#[tokio::test]
+async fn renew_flipping_position_emits_settled_and_opened_events() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    let mut conn = setup_db(conn_spec.clone());
+    let pool = r2d2::Pool::builder()
+        .build(ConnectionManager::<PgConnection>::new(conn_spec))
+        .unwrap();
+
+    let trader = dummy_public_key();
+    user::upsert_user(&mut conn, trader, None, None, None).unwrap();
+
+    let position = insert_resizing_position(&mut conn, trader, Direction::Long);
+
+    let executor = DlcProtocolExecutor::new(pool);
+    let protocol_id = ProtocolId::new();
+    let channel_id = [1; 32];
+    executor
+        .start_dlc_protocol(
+            protocol_id,
+            None,
+            &[0; 32],
+            &channel_id,
+            DlcProtocolType::Renew {
+                trade_params: TradeParams {
+                    quantity: 300.0,
+                    leverage: 3.0,
+                    direction: Direction::Short,
+                    ..dummy_trade_params(protocol_id, trader)
+                },
+            },
+        )
+        .unwrap();
+
+    let (tx_position_feed, mut rx_position_feed) = broadcast::channel(100);
+    executor
+        .finish_dlc_protocol(
+            protocol_id,
+            &trader,
+            Some([2; 32]),
+            &channel_id,
+            tx_position_feed,
+        )
+        .unwrap();
+
+    assert_eq!(
+        position_event_types(&mut rx_position_feed),
+        vec![PositionEventType::Settled, PositionEventType::Opened]
+    );
+
+    // The contracts of the flipped position are closed at the leverage they were opened with.
+    let closing_trades = db::trades::get_by_position(&mut conn, position.id).unwrap();
+    assert_eq!(closing_trades.len(), 1);
+    assert_eq!(closing_trades[0].quantity, 100.0);
+    assert_eq!(closing_trades[0].trader_leverage, 2.0);
+}
+
+fn insert_resizing_position(
+    conn: &mut PgConnection,
+    trader: PublicKey,
+    direction: Direction,
+) -> Position {
+    let position = db::positions::Position::insert(
+        conn,
+        NewPosition {
+            contract_symbol: ContractSymbol::BtcUsd,
+            trader_leverage: 2.0,
+            quantity: 100.0,
+            trader_direction: direction,
+            trader,
+            average_entry_price: 30_000.0,
+            trader_liquidation_price: 20_000.0,
+            coordinator_margin: 333_334,
+            expiry_timestamp: OffsetDateTime::now_utc(),
+            temporary_contract_id: [0; 32],
+            coordinator_leverage: 1.0,
+            trader_margin: 166_667,
+            stable: false,
+            auto_rollover: true,
+        },
+    )
+    .unwrap();
+    db::positions::Position::update_proposed_position(
+        conn,
+        trader.to_string(),
+        PositionState::Open,
+    )
+    .unwrap();
+    db::positions::Position::set_open_position_to_resizing(conn, trader.to_string()).unwrap();
+
+    position
+}
+
+fn position_event_types(
+    rx_position_feed: &mut broadcast::Receiver<InternalPositionUpdateMessage>,
+) -> Vec<PositionEventType> {
+    let mut event_types = vec![];
+    while let Ok(message) = rx_position_feed.try_recv() {
+        if let InternalPositionUpdateMessage::PositionEvent(event) = message {
+            event_types.push(event.event_type);
+        }
+    }
+    event_types
+}
+
+fn dummy_trade_params(protocol_id: ProtocolId, trader: PublicKey) -> TradeParams {
+    TradeParams {
+        protocol_id,
+        trader,
+        quantity: 100.0,
+        leverage: 2.0,
+        average_price: 30_000.0,
+        direction: Direction::Long,
+    }
+}
+
+fn dummy_public_key() -> PublicKey {
+    PublicKey::from_str("02bd998ebd176715fe92b7467cf6b1df8023950a4dd911db4c94dfc89cc9f5a655")
+        .unwrap()
+}
@@ -0,0 +1,148 @@
+//! A simple timed benchmark of the matching engine's throughput.
+//!
+//! Run it with
+//!
+//! ```bash
+//! cargo test -p coordinator --release matching_throughput -- --ignored --nocapture
+//! ```
+
+use crate::logger::init_tracing_for_test;
+use crate::orderbook::db::orders;
+use crate::orderbook::tests::setup_db;
+use crate::orderbook::tests::start_postgres;
+use crate::orderbook::trading::match_order;
+use bitcoin::secp256k1::PublicKey;
+use bitcoin::secp256k1::XOnlyPublicKey;
+use bitcoin::Network;
+use commons::NewOrder;
+use commons::OrderReason;
+use commons::OrderState;
+use commons::OrderType;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::str::FromStr;
+use std::time::Instant;
+use testcontainers::clients::Cli;
+use time::Duration;
+use time::OffsetDateTime;
+use trade::ContractSymbol;
+use trade::Direction;
+use uuid::Uuid;
+
+/// The number of limit orders in the book.
+const N_LIMIT_ORDERS: usize = 100;
+
+/// The number of market orders matched against the book per path.
+const N_MARKET_ORDERS: usize = 1_000;
+
+/// Compares the matches per second of the current DB-backed path with matching against an order
+/// book which is already in memory.
+///
+/// The DB-backed path mirrors [`crate::orderbook::trading::process_new_market_order`]: it
+/// persists the market order, loads the opposite limit orders, matches and updates the order
+/// state. The limit orders are not taken, so that both paths match against the same book.
+#[tokio::test]
+#[ignore]
+async fn matching_throughput() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    let mut conn = setup_db(conn_spec);
+
+    let limit_orders = (0..N_LIMIT_ORDERS)
+        .map(|i| {
+            let price = dec!(20_000) + Decimal::from(i);
+            orders::insert(
+                &mut conn,
+                new_order(OrderType::Limit, Direction::Short, price),
+                OrderReason::Manual,
+            )
+            .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let oracle_pk = oracle_pk();
+
+    let start = Instant::now();
+    for _ in 0..N_MARKET_ORDERS {
+        let market_order = orders::insert(
+            &mut conn,
+            new_order(OrderType::Market, Direction::Long, dec!(20_000)),
+            OrderReason::Manual,
+        )
+        .unwrap();
+
+        let opposite_direction_limit_orders =
+            orders::all_by_direction_and_type(&mut conn, Direction::Short, OrderType::Limit, true)
+                .unwrap();
+
+        let matched = match_order(
+            &market_order,
+            opposite_direction_limit_orders,
+            Network::Regtest,
+            oracle_pk,
+        )
+        .unwrap();
+        assert!(matched.is_some());
+
+        orders::set_order_state(&mut conn, market_order.id, OrderState::Matched).unwrap();
+    }
+    let db_backed = start.elapsed();
+
+    let market_order = orders::insert(
+        &mut conn,
+        new_order(OrderType::Market, Direction::Long, dec!(20_000)),
+        OrderReason::Manual,
+    )
+    .unwrap();
+
+    let start = Instant::now();
+    for _ in 0..N_MARKET_ORDERS {
+        let matched = match_order(
+            &market_order,
+            limit_orders.clone(),
+            Network::Regtest,
+            oracle_pk,
+        )
+        .unwrap();
+        assert!(matched.is_some());
+    }
+    let in_memory = start.elapsed();
+
+    let matches_per_sec =
+        |elapsed: std::time::Duration| N_MARKET_ORDERS as f64 / elapsed.as_secs_f64();
+
+    println!(
+        "Matched {N_MARKET_ORDERS} market orders against {N_LIMIT_ORDERS} limit orders:\n\
+         DB-backed: {:>12.0} matches/sec ({db_backed:?})\n\
+         in-memory: {:>12.0} matches/sec ({in_memory:?})",
+        matches_per_sec(db_backed),
+        matches_per_sec(in_memory),
+    );
+}
+
+fn new_order(order_type: OrderType, direction: Direction, price: Decimal) -> NewOrder {
+    NewOrder {
+        id: Uuid::new_v4(),
+        price,
+        trader_id: PublicKey::from_str(
+            "027f31ebc5462c1fdce1b737ecff52d37d75dea43ce11c74d25aa297165faa2007",
+        )
+        .unwrap(),
+        direction,
+        quantity: dec!(100.0),
+        order_type,
+        expiry: OffsetDateTime::now_utc() + Duration::minutes(10),
+        contract_symbol: ContractSymbol::BtcUsd,
+        leverage: dec!(1.0),
+        stable: false,
+        auto_rollover: true,
+    }
+}
+
+fn oracle_pk() -> XOnlyPublicKey {
+    XOnlyPublicKey::from_str("16f88cf7d21e6c0f46bcbc983a4e3b19726c6c98858cc31c83551a88fde171c0")
+        .unwrap()
+}
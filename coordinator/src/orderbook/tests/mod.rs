@@ -1,3 +1,5 @@
+mod dlc_protocol_test;
+mod matching_throughput_test;
 mod registration_test;
 mod sample_test;
 
@@ -1,10 +1,16 @@
+use crate::db::positions::Position;
 use crate::db::user;
 use crate::logger::init_tracing_for_test;
 use crate::orderbook::tests::setup_db;
 use crate::orderbook::tests::start_postgres;
+use crate::position::models::NewPosition;
+use crate::position::models::PositionState;
 use bitcoin::secp256k1::PublicKey;
 use std::str::FromStr;
 use testcontainers::clients::Cli;
+use time::OffsetDateTime;
+use trade::ContractSymbol;
+use trade::Direction;
 
 #[tokio::test]
 async fn registered_user_is_stored_in_db() {
@@ -45,6 +51,106 @@ async fn registered_user_is_stored_in_db() {
     assert_eq!(users.first().unwrap().version, version);
 }
 
+#[tokio::test]
+async fn dead_mans_switch_survives_login_and_records_heartbeats() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    let mut conn = setup_db(conn_spec);
+
+    let dummy_pubkey = dummy_public_key();
+    user::upsert_user(&mut conn, dummy_pubkey, None, None, None).unwrap();
+    assert!(user::get_users_with_dead_mans_switch(&mut conn)
+        .unwrap()
+        .is_empty());
+
+    let updated_rows = user::set_dead_mans_switch(&mut conn, dummy_pubkey, Some(3600)).unwrap();
+    assert_eq!(updated_rows, 1);
+
+    // Logging in again must not reset the switch.
+    user::login_user(&mut conn, dummy_pubkey, "token".to_string(), None).unwrap();
+
+    let users = user::get_users_with_dead_mans_switch(&mut conn).unwrap();
+    assert_eq!(users.len(), 1);
+    assert_eq!(users[0].dead_mans_switch_timeout_secs, Some(3600));
+    assert_eq!(users[0].last_heartbeat, None);
+
+    user::record_heartbeat(&mut conn, dummy_pubkey).unwrap();
+
+    let users = user::get_users_with_dead_mans_switch(&mut conn).unwrap();
+    let last_heartbeat = users[0].last_heartbeat.expect("heartbeat to be recorded");
+    assert_eq!(users[0].last_seen(), last_heartbeat);
+
+    user::set_dead_mans_switch(&mut conn, dummy_pubkey, None).unwrap();
+    assert!(user::get_users_with_dead_mans_switch(&mut conn)
+        .unwrap()
+        .is_empty());
+
+    // Unknown traders cannot enable the switch.
+    let unknown_trader =
+        PublicKey::from_str("0218845781f631c48f1c9709e23092067d06837f30aa0cd0544ac887fe91ddd166")
+            .unwrap();
+    let updated_rows = user::set_dead_mans_switch(&mut conn, unknown_trader, Some(3600)).unwrap();
+    assert_eq!(updated_rows, 0);
+}
+
+#[tokio::test]
+async fn disabling_auto_rollover_only_applies_once() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    let mut conn = setup_db(conn_spec);
+
+    let trader = dummy_public_key();
+    user::upsert_user(&mut conn, trader, None, None, None).unwrap();
+
+    // Nothing to halt without an open position.
+    assert_eq!(
+        Position::disable_auto_rollover(&mut conn, trader).unwrap(),
+        None
+    );
+
+    let position = Position::insert(
+        &mut conn,
+        NewPosition {
+            contract_symbol: ContractSymbol::BtcUsd,
+            trader_leverage: 2.0,
+            quantity: 100.0,
+            trader_direction: Direction::Long,
+            trader,
+            average_entry_price: 50_000.0,
+            trader_liquidation_price: 33_333.0,
+            coordinator_margin: 200_000,
+            expiry_timestamp: OffsetDateTime::now_utc(),
+            temporary_contract_id: [0; 32],
+            coordinator_leverage: 1.0,
+            trader_margin: 100_000,
+            stable: false,
+            auto_rollover: true,
+        },
+    )
+    .unwrap();
+    Position::update_proposed_position(&mut conn, trader.to_string(), PositionState::Open).unwrap();
+
+    assert_eq!(
+        Position::disable_auto_rollover(&mut conn, trader).unwrap(),
+        Some(position.id)
+    );
+    assert_eq!(
+        Position::disable_auto_rollover(&mut conn, trader).unwrap(),
+        None
+    );
+
+    let position = Position::get_position_by_trader(&mut conn, trader, vec![PositionState::Open])
+        .unwrap()
+        .unwrap();
+    assert!(!position.auto_rollover);
+}
+
 fn dummy_public_key() -> PublicKey {
     PublicKey::from_str("02bd998ebd176715fe92b7467cf6b1df8023950a4dd911db4c94dfc89cc9f5a655")
         .unwrap()
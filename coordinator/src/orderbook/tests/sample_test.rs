@@ -1,8 +1,14 @@
 use crate::logger::init_tracing_for_test;
+use crate::orderbook::cancel_on_disconnect::CancelOnDisconnect;
+use crate::orderbook::db::matches;
 use crate::orderbook::db::orders;
 use crate::orderbook::tests::setup_db;
 use crate::orderbook::tests::start_postgres;
+use crate::orderbook::trading::TraderMatchParams;
 use bitcoin::secp256k1::PublicKey;
+use bitcoin::secp256k1::XOnlyPublicKey;
+use commons::FilledWith;
+use commons::Match;
 use commons::NewOrder;
 use commons::OrderReason;
 use commons::OrderState;
@@ -85,6 +91,401 @@ async fn test_all_limit_orders() {
     assert_eq!(orders.len(), 1);
 }
 
+#[tokio::test]
+async fn cancel_all_for_trader_leaves_taken_orders_alone() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    let mut conn = setup_db(conn_spec);
+
+    let expiry = OffsetDateTime::now_utc() + Duration::minutes(1);
+    let mut insert_limit_order = |trader_id| {
+        orders::insert(
+            &mut conn,
+            NewOrder {
+                trader_id,
+                ..dummy_order(expiry, OrderType::Limit)
+            },
+            OrderReason::Manual,
+        )
+        .unwrap()
+    };
+
+    let trader = dummy_order(expiry, OrderType::Limit).trader_id;
+    let other_trader =
+        PublicKey::from_str("0218845781f631c48f1c9709e23092067d06837f30aa0cd0544ac887fe91ddd166")
+            .unwrap();
+
+    let open_order = insert_limit_order(trader);
+    let taken_order = insert_limit_order(trader);
+    let other_traders_order = insert_limit_order(other_trader);
+
+    orders::set_is_taken(&mut conn, taken_order.id, true).unwrap();
+
+    let (cancelled, taken) = orders::cancel_all_for_trader(&mut conn, trader).unwrap();
+
+    assert_eq!(
+        cancelled.iter().map(|o| o.id).collect::<Vec<_>>(),
+        vec![open_order.id]
+    );
+    assert_eq!(
+        taken.iter().map(|o| o.id).collect::<Vec<_>>(),
+        vec![taken_order.id]
+    );
+
+    for (order_id, expected_state) in [
+        (open_order.id, OrderState::Deleted),
+        (taken_order.id, OrderState::Taken),
+        (other_traders_order.id, OrderState::Open),
+    ] {
+        let order = orders::get_with_id(&mut conn, order_id).unwrap().unwrap();
+        assert_eq!(order.order_state, expected_state);
+    }
+}
+
+#[tokio::test]
+async fn closing_flagged_sessions_cancels_only_orders_placed_during_them() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    let mut conn = setup_db(conn_spec);
+
+    let expiry = OffsetDateTime::now_utc() + Duration::minutes(1);
+    let maker = dummy_order(expiry, OrderType::Limit).trader_id;
+    let other_trader =
+        PublicKey::from_str("0218845781f631c48f1c9709e23092067d06837f30aa0cd0544ac887fe91ddd166")
+            .unwrap();
+
+    let makers_order = orders::insert(
+        &mut conn,
+        dummy_order(expiry, OrderType::Limit),
+        OrderReason::Manual,
+    )
+    .unwrap();
+    let other_traders_order = orders::insert(
+        &mut conn,
+        NewOrder {
+            trader_id: other_trader,
+            ..dummy_order(expiry, OrderType::Limit)
+        },
+        OrderReason::Manual,
+    )
+    .unwrap();
+
+    // Placed before the maker connected with the flag.
+    let makers_resting_order = orders::insert(
+        &mut conn,
+        dummy_order(expiry, OrderType::Limit),
+        OrderReason::Manual,
+    )
+    .unwrap();
+
+    let cancel_on_disconnect = CancelOnDisconnect::default();
+
+    cancel_on_disconnect.track_order(maker, makers_resting_order.id);
+
+    // The maker is connected twice with the flag, the other trader without it.
+    cancel_on_disconnect.register(maker);
+    cancel_on_disconnect.register(maker);
+
+    cancel_on_disconnect.track_order(maker, makers_order.id);
+    cancel_on_disconnect.track_order(other_trader, other_traders_order.id);
+
+    let cancelled = cancel_on_disconnect
+        .session_closed(&mut conn, other_trader)
+        .unwrap();
+    assert!(cancelled.is_empty());
+
+    let cancelled = cancel_on_disconnect
+        .session_closed(&mut conn, maker)
+        .unwrap();
+    assert!(cancelled.is_empty(), "maker is still connected");
+
+    let cancelled = cancel_on_disconnect
+        .session_closed(&mut conn, maker)
+        .unwrap();
+    assert_eq!(
+        cancelled.iter().map(|o| o.id).collect::<Vec<_>>(),
+        vec![makers_order.id]
+    );
+
+    for (order_id, expected_state) in [
+        (makers_order.id, OrderState::Deleted),
+        (makers_resting_order.id, OrderState::Open),
+        (other_traders_order.id, OrderState::Open),
+    ] {
+        let order = orders::get_with_id(&mut conn, order_id).unwrap().unwrap();
+        assert_eq!(order.order_state, expected_state);
+    }
+}
+
+#[tokio::test]
+async fn only_open_limit_orders_can_be_amended() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    let mut conn = setup_db(conn_spec);
+
+    let expiry = OffsetDateTime::now_utc() + Duration::minutes(1);
+    let order = orders::insert(
+        &mut conn,
+        dummy_order(expiry, OrderType::Limit),
+        OrderReason::Manual,
+    )
+    .unwrap();
+
+    let amended = orders::update_price(&mut conn, order.id, dec!(20100.50))
+        .unwrap()
+        .unwrap();
+    assert_eq!(amended.id, order.id);
+    assert_eq!(amended.price, dec!(20100.50));
+    assert_eq!(amended.order_state, OrderState::Open);
+    assert!(amended.timestamp > order.timestamp);
+
+    orders::set_is_taken(&mut conn, order.id, true).unwrap();
+
+    assert_eq!(
+        orders::update_price(&mut conn, order.id, dec!(20200)).unwrap(),
+        None
+    );
+    let order = orders::get_with_id(&mut conn, order.id).unwrap().unwrap();
+    assert_eq!(order.price, dec!(20100.50));
+
+    let market_order = orders::insert(
+        &mut conn,
+        dummy_order(expiry, OrderType::Market),
+        OrderReason::Manual,
+    )
+    .unwrap();
+    assert_eq!(
+        orders::update_price(&mut conn, market_order.id, dec!(20200)).unwrap(),
+        None
+    );
+
+    assert_eq!(
+        orders::update_price(&mut conn, Uuid::new_v4(), dec!(20200)).unwrap(),
+        None
+    );
+}
+
+#[tokio::test]
+async fn expired_orders_are_not_returned() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    let mut conn = setup_db(conn_spec);
+
+    let now = OffsetDateTime::now_utc();
+    let expired_order = orders::insert(
+        &mut conn,
+        dummy_order(now - Duration::minutes(1), OrderType::Limit),
+        OrderReason::Manual,
+    )
+    .unwrap();
+    let order = orders::insert(
+        &mut conn,
+        dummy_order(now + Duration::minutes(1), OrderType::Limit),
+        OrderReason::Manual,
+    )
+    .unwrap();
+
+    let limit_orders = orders::all_limit_orders(&mut conn).unwrap();
+    assert_eq!(limit_orders, vec![order.clone()]);
+
+    let long_limit_orders =
+        orders::all_by_direction_and_type(&mut conn, Direction::Long, OrderType::Limit, true)
+            .unwrap();
+    assert_eq!(long_limit_orders, vec![order]);
+
+    let long_limit_orders =
+        orders::all_by_direction_and_type(&mut conn, Direction::Long, OrderType::Limit, false)
+            .unwrap();
+    assert!(long_limit_orders.contains(&expired_order));
+}
+
+#[tokio::test]
+async fn delete_expired_prunes_unmatched_expired_orders() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    let mut conn = setup_db(conn_spec);
+
+    let now = OffsetDateTime::now_utc();
+    let mut insert_order = |expiry| {
+        orders::insert(
+            &mut conn,
+            dummy_order(expiry, OrderType::Limit),
+            OrderReason::Manual,
+        )
+        .unwrap()
+    };
+
+    let expired_open_order = insert_order(now - Duration::minutes(2));
+    let expired_order = insert_order(now - Duration::minutes(1));
+    let expired_taken_order = insert_order(now - Duration::minutes(1));
+    let expired_reopened_order = insert_order(now - Duration::minutes(1));
+    let open_order = insert_order(now + Duration::minutes(1));
+
+    orders::set_order_state(&mut conn, expired_order.id, OrderState::Expired).unwrap();
+    orders::set_is_taken(&mut conn, expired_taken_order.id, true).unwrap();
+
+    // The order was matched while it was still valid, but the match failed and the order was
+    // reopened.
+    let market_order = orders::insert(
+        &mut conn,
+        dummy_order(now + Duration::minutes(1), OrderType::Market),
+        OrderReason::Manual,
+    )
+    .unwrap();
+    matches::insert(
+        &mut conn,
+        &TraderMatchParams {
+            trader_id: market_order.trader_id,
+            filled_with: FilledWith {
+                order_id: market_order.id,
+                expiry_timestamp: now + Duration::days(7),
+                oracle_pk: XOnlyPublicKey::from(market_order.trader_id),
+                matches: vec![Match {
+                    id: Uuid::new_v4(),
+                    order_id: expired_reopened_order.id,
+                    quantity: expired_reopened_order.quantity,
+                    pubkey: expired_reopened_order.trader_id,
+                    execution_price: expired_reopened_order.price,
+                }],
+            },
+        },
+    )
+    .unwrap();
+    orders::set_is_taken(&mut conn, expired_reopened_order.id, true).unwrap();
+    orders::set_is_taken(&mut conn, expired_reopened_order.id, false).unwrap();
+
+    let deleted = orders::delete_expired(&mut conn, now).unwrap();
+    assert_eq!(deleted, 2);
+
+    assert_eq!(
+        orders::get_with_id(&mut conn, expired_open_order.id).unwrap(),
+        None
+    );
+    assert_eq!(
+        orders::get_with_id(&mut conn, expired_order.id).unwrap(),
+        None
+    );
+    assert!(orders::get_with_id(&mut conn, expired_taken_order.id)
+        .unwrap()
+        .is_some());
+    assert!(orders::get_with_id(&mut conn, expired_reopened_order.id)
+        .unwrap()
+        .is_some());
+    assert!(orders::get_with_id(&mut conn, open_order.id)
+        .unwrap()
+        .is_some());
+}
+
+#[tokio::test]
+async fn aggregated_depth_sums_open_limit_orders_per_price_level() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    let mut conn = setup_db(conn_spec);
+
+    let expiry = OffsetDateTime::now_utc() + Duration::minutes(1);
+    let mut insert_order = |direction, price, quantity| {
+        orders::insert(
+            &mut conn,
+            NewOrder {
+                direction,
+                price,
+                quantity,
+                ..dummy_order(expiry, OrderType::Limit)
+            },
+            OrderReason::Manual,
+        )
+        .unwrap()
+    };
+
+    insert_order(Direction::Long, dec!(20000), dec!(100));
+    insert_order(Direction::Long, dec!(20000), dec!(50));
+    insert_order(Direction::Long, dec!(20100), dec!(200));
+    insert_order(Direction::Short, dec!(20300), dec!(25));
+    insert_order(Direction::Short, dec!(20200), dec!(75));
+    insert_order(Direction::Short, dec!(20200), dec!(75));
+    let taken_order = insert_order(Direction::Long, dec!(20100), dec!(1000));
+
+    orders::set_is_taken(&mut conn, taken_order.id, true).unwrap();
+
+    let bids = orders::aggregated_depth(&mut conn, Direction::Long).unwrap();
+    assert_eq!(
+        bids,
+        vec![(dec!(20100), dec!(200)), (dec!(20000), dec!(150))]
+    );
+
+    let asks = orders::aggregated_depth(&mut conn, Direction::Short).unwrap();
+    assert_eq!(
+        asks,
+        vec![(dec!(20200), dec!(150)), (dec!(20300), dec!(25))]
+    );
+}
+
+#[tokio::test]
+async fn orders_at_equal_price_are_returned_in_insertion_order() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    let mut conn = setup_db(conn_spec);
+
+    let expiry = OffsetDateTime::now_utc() + Duration::minutes(1);
+    let mut insert_order = |direction, price| {
+        orders::insert(
+            &mut conn,
+            NewOrder {
+                direction,
+                price,
+                ..dummy_order(expiry, OrderType::Limit)
+            },
+            OrderReason::Manual,
+        )
+        .unwrap()
+        .id
+    };
+
+    let first_bid = insert_order(Direction::Long, dec!(20000));
+    let second_bid = insert_order(Direction::Long, dec!(20000));
+    let best_bid = insert_order(Direction::Long, dec!(20100));
+    let first_ask = insert_order(Direction::Short, dec!(20300));
+    let best_ask = insert_order(Direction::Short, dec!(20200));
+    let second_ask = insert_order(Direction::Short, dec!(20300));
+
+    let bids =
+        orders::all_by_direction_and_type(&mut conn, Direction::Long, OrderType::Limit, true)
+            .unwrap();
+    assert_eq!(
+        bids.iter().map(|o| o.id).collect::<Vec<_>>(),
+        vec![best_bid, first_bid, second_bid]
+    );
+
+    let asks =
+        orders::all_by_direction_and_type(&mut conn, Direction::Short, OrderType::Limit, true)
+            .unwrap();
+    assert_eq!(
+        asks.iter().map(|o| o.id).collect::<Vec<_>>(),
+        vec![best_ask, first_ask, second_ask]
+    );
+}
+
 fn dummy_order(expiry: OffsetDateTime, order_type: OrderType) -> NewOrder {
     NewOrder {
         id: Uuid::new_v4(),
@@ -100,5 +501,6 @@ fn dummy_order(expiry: OffsetDateTime, order_type: OrderType) -> NewOrder {
         contract_symbol: trade::ContractSymbol::BtcUsd,
         leverage: dec!(1.0),
         stable: false,
+        auto_rollover: true,
     }
 }
@@ -0,0 +1,221 @@
+use bitcoin::secp256k1::PublicKey;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::time::Duration;
+use std::time::Instant;
+
+/// Enforces a minimum interval between the accepted order submissions of each trader, so that a
+/// single trader cannot stuff the orderbook.
+#[derive(Default)]
+pub struct OrderThrottle {
+    /// When each trader may submit their next order.
+    next_submissions: Mutex<HashMap<PublicKey, Instant>>,
+}
+
+/// A submission slot reserved by [`OrderThrottle::try_reserve`].
+///
+/// The slot is released again when the reservation is dropped without being confirmed, e.g.
+/// because the order was rejected, so that rejected submissions do not count towards the limit.
+#[must_use]
+pub struct Reservation<'a> {
+    throttle: &'a OrderThrottle,
+    trader_id: PublicKey,
+    /// When the trader may submit again, or `None` if nothing was reserved.
+    reserved_until: Option<Instant>,
+}
+
+impl OrderThrottle {
+    /// Reserve a submission for the trader, who then has to wait `min_interval` before
+    /// submitting again.
+    ///
+    /// Checking and reserving happens under a single lock, so that concurrent submissions of the
+    /// same trader cannot all pass before any of them is recorded.
+    ///
+    /// Returns how long the trader has to wait before submitting again if the submission is
+    /// throttled.
+    pub fn try_reserve(
+        &self,
+        trader_id: PublicKey,
+        min_interval: Duration,
+    ) -> Result<Reservation<'_>, Duration> {
+        self.try_reserve_at(trader_id, min_interval, Instant::now())
+    }
+
+    /// Forget about traders who could submit again anyway, so that we do not keep an entry for
+    /// every trader we have ever seen.
+    pub fn prune(&self) {
+        self.prune_at(Instant::now())
+    }
+
+    fn try_reserve_at(
+        &self,
+        trader_id: PublicKey,
+        min_interval: Duration,
+        now: Instant,
+    ) -> Result<Reservation<'_>, Duration> {
+        let mut next_submissions = self.next_submissions.lock();
+
+        if let Some(next_submission) = next_submissions.get(&trader_id) {
+            if *next_submission > now {
+                return Err(*next_submission - now);
+            }
+        }
+
+        let reserved_until = if min_interval.is_zero() {
+            None
+        } else {
+            let reserved_until = now + min_interval;
+            next_submissions.insert(trader_id, reserved_until);
+            Some(reserved_until)
+        };
+
+        Ok(Reservation {
+            throttle: self,
+            trader_id,
+            reserved_until,
+        })
+    }
+
+    fn prune_at(&self, now: Instant) {
+        self.next_submissions
+            .lock()
+            .retain(|_, next_submission| *next_submission > now);
+    }
+}
+
+impl Reservation<'_> {
+    /// Keep the reservation, as the order has been accepted.
+    pub fn confirm(mut self) {
+        self.reserved_until = None;
+    }
+}
+
+impl Drop for Reservation<'_> {
+    fn drop(&mut self) {
+        let reserved_until = match self.reserved_until {
+            Some(reserved_until) => reserved_until,
+            None => return,
+        };
+
+        let mut next_submissions = self.throttle.next_submissions.lock();
+        if next_submissions.get(&self.trader_id) == Some(&reserved_until) {
+            next_submissions.remove(&self.trader_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    const MIN_INTERVAL: Duration = Duration::from_millis(500);
+
+    #[test]
+    fn rapid_submissions_are_throttled() {
+        let throttle = OrderThrottle::default();
+        let trader = trader("0218845781f631c48f1c9709e23092067d06837f30aa0cd0544ac887fe91ddd166");
+        let start = Instant::now();
+
+        throttle
+            .try_reserve_at(trader, MIN_INTERVAL, start)
+            .unwrap()
+            .confirm();
+
+        let retry_after = throttle
+            .try_reserve_at(trader, MIN_INTERVAL, start + Duration::from_millis(100))
+            .err()
+            .unwrap();
+        assert_eq!(retry_after, Duration::from_millis(400));
+
+        // The throttled submission did not reset the interval.
+        assert!(throttle
+            .try_reserve_at(trader, MIN_INTERVAL, start + MIN_INTERVAL)
+            .is_ok());
+    }
+
+    #[test]
+    fn concurrent_submissions_are_throttled_before_either_is_accepted() {
+        let throttle = OrderThrottle::default();
+        let trader = trader("0218845781f631c48f1c9709e23092067d06837f30aa0cd0544ac887fe91ddd166");
+        let now = Instant::now();
+
+        let first = throttle.try_reserve_at(trader, MIN_INTERVAL, now).unwrap();
+
+        // The first submission is still being processed.
+        assert!(throttle.try_reserve_at(trader, MIN_INTERVAL, now).is_err());
+
+        first.confirm();
+    }
+
+    #[test]
+    fn rejected_submissions_are_not_throttled() {
+        let throttle = OrderThrottle::default();
+        let trader = trader("0218845781f631c48f1c9709e23092067d06837f30aa0cd0544ac887fe91ddd166");
+        let now = Instant::now();
+
+        // The order is rejected after the reservation, dropping it without confirmation.
+        drop(throttle.try_reserve_at(trader, MIN_INTERVAL, now).unwrap());
+
+        assert!(throttle.try_reserve_at(trader, MIN_INTERVAL, now).is_ok());
+    }
+
+    #[test]
+    fn traders_are_throttled_independently() {
+        let throttle = OrderThrottle::default();
+        let trader_a = trader("0218845781f631c48f1c9709e23092067d06837f30aa0cd0544ac887fe91ddd166");
+        let trader_b = trader("035eccdd1f05c65b433cf38e3b2597e33715e0392cb14d183e812f1319eb7b6794");
+        let now = Instant::now();
+
+        throttle
+            .try_reserve_at(trader_a, MIN_INTERVAL, now)
+            .unwrap()
+            .confirm();
+
+        assert!(throttle
+            .try_reserve_at(trader_a, MIN_INTERVAL, now)
+            .is_err());
+        assert!(throttle.try_reserve_at(trader_b, MIN_INTERVAL, now).is_ok());
+    }
+
+    #[test]
+    fn zero_interval_disables_throttling() {
+        let throttle = OrderThrottle::default();
+        let trader = trader("0218845781f631c48f1c9709e23092067d06837f30aa0cd0544ac887fe91ddd166");
+        let now = Instant::now();
+
+        throttle
+            .try_reserve_at(trader, Duration::ZERO, now)
+            .unwrap()
+            .confirm();
+
+        assert!(throttle.try_reserve_at(trader, Duration::ZERO, now).is_ok());
+    }
+
+    #[test]
+    fn prune_forgets_traders_who_may_submit_again() {
+        let throttle = OrderThrottle::default();
+        let trader_a = trader("0218845781f631c48f1c9709e23092067d06837f30aa0cd0544ac887fe91ddd166");
+        let trader_b = trader("035eccdd1f05c65b433cf38e3b2597e33715e0392cb14d183e812f1319eb7b6794");
+        let start = Instant::now();
+
+        throttle
+            .try_reserve_at(trader_a, MIN_INTERVAL, start)
+            .unwrap()
+            .confirm();
+        throttle
+            .try_reserve_at(trader_b, MIN_INTERVAL, start + MIN_INTERVAL)
+            .unwrap()
+            .confirm();
+
+        throttle.prune_at(start + MIN_INTERVAL);
+
+        let next_submissions = throttle.next_submissions.lock();
+        assert_eq!(next_submissions.len(), 1);
+        assert!(next_submissions.contains_key(&trader_b));
+    }
+
+    fn trader(pubkey: &str) -> PublicKey {
+        PublicKey::from_str(pubkey).unwrap()
+    }
+}
@@ -34,7 +34,6 @@ use time::OffsetDateTime;
 use tokio::sync::broadcast;
 use tokio::sync::mpsc;
 use tokio::task::spawn_blocking;
-use trade::ContractSymbol;
 use trade::Direction;
 use uuid::Uuid;
 
@@ -142,6 +141,34 @@ pub fn start(
     (remote_handle, sender)
 }
 
+/// Removes the expired orders from the orderbook.
+///
+/// Expired limit orders are first set to `Expired`, so that the traders following the price feed
+/// drop them. Then all expired orders which have never been matched are deleted, so that stale
+/// quotes of makers which went away do not pile up.
+pub async fn prune_expired_orders(
+    node: Node,
+    tx_price_feed: broadcast::Sender<Message>,
+) -> Result<()> {
+    let mut conn = spawn_blocking(move || node.pool.get())
+        .await
+        .expect("task to complete")?;
+
+    let expired_limit_orders = orders::set_expired_limit_orders_to_expired(&mut conn)?;
+    for expired_limit_order in expired_limit_orders {
+        tx_price_feed
+            .send(Message::DeleteOrder(expired_limit_order.id))
+            .context("Could not update price feed")?;
+    }
+
+    let deleted = orders::delete_expired(&mut conn, OffsetDateTime::now_utc())?;
+    if deleted > 0 {
+        tracing::debug!(deleted, "Deleted expired orders");
+    }
+
+    Ok(())
+}
+
 pub async fn process_new_limit_order(
     node: Node,
     tx_price_feed: broadcast::Sender<Message>,
@@ -327,7 +354,7 @@ pub async fn process_new_market_order(
                 .execute(&TradeAndChannelParams {
                     trade_params: TradeParams {
                         pubkey: order.trader_id,
-                        contract_symbol: ContractSymbol::BtcUsd,
+                        contract_symbol: order.contract_symbol,
                         leverage: order.leverage,
                         quantity: order.quantity.to_f32().expect("to fit into f32"),
                         direction: order.direction,
@@ -356,9 +383,10 @@ pub async fn process_new_market_order(
 ///
 /// The caller is expected to provide a list of `opposite_direction_orders` of [`OrderType::Limit`]
 /// and opposite [`Direction`] to the `market_order`. We nevertheless ensure that this is the case
-/// to be on the safe side.
+/// to be on the safe side. Limit orders for a different [`trade::ContractSymbol`] are never
+/// matched.
 
-fn match_order(
+pub(crate) fn match_order(
     market_order: &Order,
     opposite_direction_orders: Vec<Order>,
     network: Network,
@@ -372,6 +400,7 @@ fn match_order(
     let opposite_direction_orders = opposite_direction_orders
         .into_iter()
         .filter(|o| !o.direction.eq(&market_order.direction))
+        .filter(|o| o.contract_symbol == market_order.contract_symbol)
         .collect();
 
     let mut orders = sort_orders(opposite_direction_orders, market_order.direction);
@@ -649,6 +678,7 @@ mod tests {
             order_state: OrderState::Open,
             order_reason: OrderReason::Manual,
             stable: false,
+            auto_rollover: true,
         };
 
         let matched_orders = match_order(
@@ -731,6 +761,7 @@ mod tests {
             order_state: OrderState::Open,
             order_reason: OrderReason::Manual,
             stable: false,
+            auto_rollover: true,
         };
 
         assert!(match_order(
@@ -788,6 +819,7 @@ mod tests {
             order_state: OrderState::Open,
             order_reason: OrderReason::Manual,
             stable: false,
+            auto_rollover: true,
         };
 
         let matched_orders = match_order(
@@ -824,6 +856,7 @@ mod tests {
             order_state: OrderState::Open,
             order_reason: OrderReason::Manual,
             stable: false,
+            auto_rollover: true,
         }
     }
 
@@ -5,6 +5,7 @@ use crate::orderbook::db::orders;
 use crate::routes::AppState;
 use axum::extract::ws::Message as WebsocketMessage;
 use axum::extract::ws::WebSocket;
+use bitcoin::secp256k1::PublicKey;
 use commons::create_sign_message;
 use commons::LspConfig;
 use commons::Message;
@@ -12,10 +13,12 @@ use commons::OrderbookRequest;
 use commons::AUTH_SIGN_MESSAGE;
 use futures::SinkExt;
 use futures::StreamExt;
+use parking_lot::Mutex;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::mpsc;
+use tokio::task::spawn_blocking;
 
 const WEBSOCKET_SEND_TIMEOUT: Duration = Duration::from_secs(5);
 
@@ -78,15 +81,22 @@ pub async fn websocket_connection(stream: WebSocket, state: Arc<AppState>) {
         })
     };
 
+    // The trader who asked for their orders to be cancelled once this connection closes.
+    let cancel_on_disconnect_trader = Arc::new(Mutex::new(None));
+    let disconnected_trader = cancel_on_disconnect_trader.clone();
+    let cleanup_state = state.clone();
+
     // Spawn a task that takes messages from the websocket
     let local_sender = local_sender.clone();
     let mut recv_task = tokio::spawn(async move {
+        let mut authenticated_trader = None;
         while let Some(Ok(WebsocketMessage::Text(text))) = receiver.next().await {
             match serde_json::from_str(text.as_str()) {
                 Ok(OrderbookRequest::Authenticate {
                     fcm_token,
                     version,
                     signature,
+                    cancel_on_disconnect,
                 }) => {
                     let msg = create_sign_message(AUTH_SIGN_MESSAGE.to_vec());
                     let trader_id = signature.pubkey;
@@ -102,6 +112,8 @@ pub async fn websocket_connection(stream: WebSocket, state: Arc<AppState>) {
 
                     match state.secp.verify_ecdsa(&msg, &signature, &trader_id) {
                         Ok(_) => {
+                            authenticated_trader = Some(trader_id);
+
                             let liquidity_options =
                                 db::liquidity_options::get_all(&mut conn).unwrap_or_default();
 
@@ -136,6 +148,15 @@ pub async fn websocket_connection(stream: WebSocket, state: Arc<AppState>) {
                                 sender: local_sender.clone(),
                             };
                             tracing::debug!(%trader_id, "New login");
+
+                            if cancel_on_disconnect {
+                                let mut flagged_trader = cancel_on_disconnect_trader.lock();
+                                if flagged_trader.is_none() {
+                                    state.cancel_on_disconnect.register(trader_id);
+                                    *flagged_trader = Some(trader_id);
+                                }
+                            }
+
                             if let Err(e) = state.tx_user_feed.send(message) {
                                 tracing::error!(%trader_id, "Could not send new user message. Error: {e:#}");
                             }
@@ -155,6 +176,27 @@ pub async fn websocket_connection(stream: WebSocket, state: Arc<AppState>) {
                         }
                     }
                 }
+                Ok(OrderbookRequest::Heartbeat) => {
+                    let trader_id = match authenticated_trader {
+                        Some(trader_id) => trader_id,
+                        None => {
+                            tracing::debug!("Ignoring heartbeat on unauthenticated connection");
+                            continue;
+                        }
+                    };
+
+                    let mut conn = match state.pool.clone().get() {
+                        Ok(conn) => conn,
+                        Err(err) => {
+                            tracing::error!("Could not get connection to db pool {err:#}");
+                            return;
+                        }
+                    };
+
+                    if let Err(e) = user::record_heartbeat(&mut conn, trader_id) {
+                        tracing::error!(%trader_id, "Failed to record heartbeat: {e:#}");
+                    }
+                }
                 Err(err) => {
                     tracing::trace!("Could not deserialize msg: {text} {err:#}");
                 }
@@ -177,4 +219,41 @@ pub async fn websocket_connection(stream: WebSocket, state: Arc<AppState>) {
             send_task.abort();
         },
     };
+
+    let trader_id = disconnected_trader.lock().take();
+    if let Some(trader_id) = trader_id {
+        cancel_orders_on_disconnect(cleanup_state, trader_id).await;
+    }
+}
+
+async fn cancel_orders_on_disconnect(state: Arc<AppState>, trader_id: PublicKey) {
+    let cancelled = spawn_blocking({
+        let state = state.clone();
+        move || {
+            let mut conn = state.pool.get()?;
+            let cancelled = state
+                .cancel_on_disconnect
+                .session_closed(&mut conn, trader_id)?;
+            anyhow::Ok(cancelled)
+        }
+    })
+    .await
+    .expect("task to complete");
+
+    match cancelled {
+        Ok(cancelled) => {
+            if !cancelled.is_empty() {
+                tracing::info!(%trader_id, count = cancelled.len(), "Cancelled orders on disconnect");
+            }
+
+            for order in cancelled {
+                if let Err(e) = state.tx_price_feed.send(Message::DeleteOrder(order.id)) {
+                    tracing::error!("Could not publish delete order message: {e:#}");
+                }
+            }
+        }
+        Err(e) => {
+            tracing::error!(%trader_id, "Failed to cancel orders on disconnect: {e:#}");
+        }
+    }
 }
@@ -1,4 +1,4 @@
-use anyhow::ensure;
+use anyhow::bail;
 use anyhow::Context;
 use anyhow::Result;
 use bitcoin::Amount;
@@ -20,6 +20,9 @@ use trade::cfd::BTCUSD_MAX_PRICE;
 use trade::ContractSymbol;
 use trade::Direction;
 
+/// The number of binary digits the oracle attests to, most significant first.
+pub const ORACLE_NB_DIGITS: usize = 20;
+
 /// Builds the contract descriptor from the point of view of the coordinator.
 ///
 /// It's the direction of the coordinator because the coordinator is always proposing.
@@ -37,11 +40,14 @@ pub fn build_contract_descriptor(
     quantity: f32,
     symbol: ContractSymbol,
 ) -> Result<ContractDescriptor> {
-    ensure!(
-        symbol == ContractSymbol::BtcUsd,
-        "We only support BTCUSD at the moment. \
-         For other symbols we will need a different payout curve"
-    );
+    match symbol {
+        ContractSymbol::BtcUsd => {}
+        // The inverse payout curve only works for contracts settled in the base currency.
+        ContractSymbol::EthUsd => bail!(
+            "We only support BTCUSD at the moment. \
+             For {symbol} we will need a different payout curve"
+        ),
+    }
 
     tracing::info!("Building contract descriptor");
 
@@ -63,7 +69,7 @@ pub fn build_contract_descriptor(
         difference_params: None,
         oracle_numeric_infos: dlc_trie::OracleNumericInfo {
             base: 2,
-            nb_digits: vec![20],
+            nb_digits: vec![ORACLE_NB_DIGITS],
         },
     }))
 }
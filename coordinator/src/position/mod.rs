@@ -1 +1,3 @@
 pub mod models;
+pub mod settlement_price;
+pub mod timeline;
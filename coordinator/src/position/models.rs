@@ -1,12 +1,15 @@
 use crate::compute_relative_contracts;
 use crate::decimal_from_f32;
 use anyhow::bail;
+use anyhow::ensure;
 use anyhow::Context;
 use anyhow::Result;
 use bitcoin::secp256k1::PublicKey;
 use bitcoin::Address;
 use bitcoin::Amount;
+use bitcoin::Network;
 use bitcoin::Txid;
+use commons::average_price;
 use commons::order_matching_fee_taker;
 use commons::TradeParams;
 use dlc_manager::ContractId;
@@ -15,10 +18,13 @@ use lightning::ln::ChannelId;
 use rust_decimal::prelude::Signed;
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
+use rust_decimal::RoundingStrategy;
 use time::OffsetDateTime;
 use trade::bitmex_client::Quote;
+use trade::cfd::calculate_liquidation_price;
 use trade::cfd::calculate_margin;
 use trade::cfd::calculate_pnl;
+use trade::cfd::calculate_pnl_with_fees;
 use trade::ContractSymbol;
 use trade::Direction;
 
@@ -37,6 +43,7 @@ pub struct NewPosition {
     pub coordinator_leverage: f32,
     pub trader_margin: i64,
     pub stable: bool,
+    pub auto_rollover: bool,
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -99,15 +106,78 @@ pub struct Position {
     pub closing_price: Option<f32>,
     pub trader_margin: i64,
     pub stable: bool,
+    /// Whether the position is rolled over automatically. If not, it is closed once it expires.
+    pub auto_rollover: bool,
     pub trader_realized_pnl_sat: Option<i64>,
 }
 
+/// The outcome of closing part of a position.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionReduction {
+    /// The number of contracts which remain open.
+    pub remaining_quantity: f32,
+    pub remaining_coordinator_margin: u64,
+    pub remaining_trader_margin: u64,
+    /// The share of the margin of the closed contracts paid out to the coordinator, including the
+    /// order-matching fee for the reducing trade.
+    pub coordinator_settlement_amount: u64,
+    /// The share of the margin of the closed contracts paid out to the trader.
+    pub trader_settlement_amount: u64,
+    /// The PnL realized by the trader on the closed contracts, net of the order-matching fees for
+    /// opening and closing them.
+    pub trader_realized_pnl_sat: i64,
+}
+
+/// The outcome of adding contracts to a position.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionIncrease {
+    pub quantity: f32,
+    /// The average of the entry price of the position and the price of the added contracts.
+    pub average_entry_price: Decimal,
+    pub trader_liquidation_price: Decimal,
+    pub coordinator_margin: u64,
+    pub trader_margin: u64,
+}
+
+/// The outcome of closing a position and opening one in the opposite direction with the same
+/// order.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionFlip {
+    /// The share of the margin of the closed position paid out to the coordinator, including the
+    /// order-matching fee for closing it.
+    pub coordinator_settlement_amount: u64,
+    /// The share of the margin of the closed position paid out to the trader.
+    pub trader_settlement_amount: u64,
+    /// The PnL realized by the trader on the closed position, net of the order-matching fees for
+    /// opening and closing it.
+    pub trader_realized_pnl_sat: i64,
+    /// The number of contracts of the new position.
+    pub quantity: f32,
+    pub trader_direction: Direction,
+    pub trader_leverage: f32,
+    pub trader_liquidation_price: Decimal,
+    pub coordinator_margin: u64,
+    pub trader_margin: u64,
+}
+
 impl Position {
     // Returns true if the position is expired
     pub fn is_expired(&self) -> bool {
         OffsetDateTime::now_utc() >= self.expiry_timestamp
     }
 
+    /// Whether we should propose to roll the position over into the next expiry at `now`.
+    ///
+    /// Positions which opted out of auto-rollover are never rolled over. They are closed once they
+    /// expire instead.
+    pub fn is_due_for_rollover(&self, now: OffsetDateTime, network: Network) -> bool {
+        self.auto_rollover
+            && commons::is_eligible_for_rollover(now, network)
+            && now < self.expiry_timestamp
+            // Otherwise the position has already been rolled over.
+            && self.expiry_timestamp != commons::calculate_next_expiry(now, network)
+    }
+
     /// Calculates the profit and loss for the coordinator in satoshis
     pub fn calculate_coordinator_pnl(&self, quote: Quote) -> Result<i64> {
         let closing_price = match self.closing_price {
@@ -117,6 +187,12 @@ impl Position {
             }
         };
 
+        self.calculate_coordinator_pnl_at_price(closing_price)
+    }
+
+    /// Calculate the PnL of the coordinator if the whole position settles at `closing_price`,
+    /// excluding fees.
+    pub fn calculate_coordinator_pnl_at_price(&self, closing_price: Decimal) -> Result<i64> {
         let average_entry_price = Decimal::try_from(self.average_entry_price)
             .context("Failed to convert average entry price to Decimal")?;
 
@@ -186,6 +262,192 @@ impl Position {
             trade_params.average_execution_price(),
         )
     }
+
+    /// Calculate the outcome of adding `quantity` contracts at `price` to the position.
+    ///
+    /// The leverages and expiry of the position stay the same.
+    pub fn calculate_increase(&self, quantity: f32, price: Decimal) -> Result<PositionIncrease> {
+        ensure!(
+            quantity > 0.0,
+            "Cannot increase position by {quantity} contracts"
+        );
+
+        let average_entry_price = average_price(&[
+            (
+                decimal_from_f32(self.quantity),
+                Decimal::try_from(self.average_entry_price)?,
+            ),
+            (decimal_from_f32(quantity), price),
+        ])
+        .round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero);
+
+        let quantity = self.quantity + quantity;
+
+        let coordinator_margin =
+            calculate_margin(average_entry_price, quantity, self.coordinator_leverage);
+        let trader_margin = calculate_margin(average_entry_price, quantity, self.trader_leverage);
+
+        let trader_leverage =
+            Decimal::try_from(self.trader_leverage).context("Invalid trader leverage")?;
+        let trader_liquidation_price = calculate_liquidation_price(
+            average_entry_price,
+            trader_leverage,
+            self.trader_direction,
+        );
+
+        Ok(PositionIncrease {
+            quantity,
+            average_entry_price,
+            trader_liquidation_price,
+            coordinator_margin,
+            trader_margin,
+        })
+    }
+
+    /// Calculate the outcome of closing `quantity` contracts of the position at `closing_price`.
+    ///
+    /// The remaining contracts keep the entry price, leverages and expiry of the position. The
+    /// margin freed up by the closed contracts is split between the coordinator and the trader
+    /// based on the PnL of the closed contracts.
+    pub fn calculate_reduction(
+        &self,
+        quantity: f32,
+        closing_price: Decimal,
+    ) -> Result<PositionReduction> {
+        ensure!(
+            quantity > 0.0 && quantity < self.quantity,
+            "Cannot reduce position of {} contracts by {quantity} contracts",
+            self.quantity
+        );
+
+        let opening_price = Decimal::try_from(self.average_entry_price)?;
+        let remaining_quantity = self.quantity - quantity;
+
+        let remaining_coordinator_margin =
+            calculate_margin(opening_price, remaining_quantity, self.coordinator_leverage);
+        let remaining_trader_margin =
+            calculate_margin(opening_price, remaining_quantity, self.trader_leverage);
+
+        // We derive the margin of the closed contracts from the margin of the position, so that no
+        // sats get lost to rounding.
+        let closed_margin = (self.coordinator_margin + self.trader_margin)
+            .to_u64()
+            .and_then(|margin| {
+                margin.checked_sub(remaining_coordinator_margin + remaining_trader_margin)
+            })
+            .context("Remaining margin exceeds the margin of the position")?;
+
+        let (coordinator_settlement_amount, trader_settlement_amount, trader_realized_pnl_sat) =
+            self.settle_contracts(quantity, closing_price, closed_margin)?;
+
+        Ok(PositionReduction {
+            remaining_quantity,
+            remaining_coordinator_margin,
+            remaining_trader_margin,
+            coordinator_settlement_amount,
+            trader_settlement_amount,
+            trader_realized_pnl_sat,
+        })
+    }
+
+    /// Calculate the outcome of closing the position and opening a position in the opposite
+    /// direction for the remainder of `quantity`, both at `price`.
+    ///
+    /// The new position is opened with `trader_leverage` and the coordinator leverage of the
+    /// position. It keeps the expiry of the position.
+    pub fn calculate_flip(
+        &self,
+        quantity: f32,
+        price: Decimal,
+        trader_leverage: f32,
+    ) -> Result<PositionFlip> {
+        ensure!(
+            quantity > self.quantity,
+            "Cannot flip position of {} contracts with {quantity} contracts",
+            self.quantity
+        );
+
+        let closed_margin = (self.coordinator_margin + self.trader_margin)
+            .to_u64()
+            .context("Margin of the position to be positive")?;
+
+        let (coordinator_settlement_amount, trader_settlement_amount, trader_realized_pnl_sat) =
+            self.settle_contracts(self.quantity, price, closed_margin)?;
+
+        let quantity = quantity - self.quantity;
+        let trader_direction = self.trader_direction.opposite();
+
+        let trader_liquidation_price = calculate_liquidation_price(
+            price,
+            Decimal::try_from(trader_leverage).context("Invalid trader leverage")?,
+            trader_direction,
+        );
+
+        Ok(PositionFlip {
+            coordinator_settlement_amount,
+            trader_settlement_amount,
+            trader_realized_pnl_sat,
+            quantity,
+            trader_direction,
+            trader_leverage,
+            trader_liquidation_price,
+            coordinator_margin: calculate_margin(price, quantity, self.coordinator_leverage),
+            trader_margin: calculate_margin(price, quantity, trader_leverage),
+        })
+    }
+
+    /// Split `closed_margin`, the margin of `quantity` closed contracts, between the coordinator
+    /// and the trader based on the PnL at `closing_price`.
+    ///
+    /// Returns the settlement amounts of the coordinator and the trader, and the PnL realized by
+    /// the trader net of the order-matching fees for opening and closing the contracts.
+    fn settle_contracts(
+        &self,
+        quantity: f32,
+        closing_price: Decimal,
+        closed_margin: u64,
+    ) -> Result<(u64, u64, i64)> {
+        let opening_price = Decimal::try_from(self.average_entry_price)?;
+
+        let leverage_long = leverage_long(
+            self.trader_direction,
+            self.trader_leverage,
+            self.coordinator_leverage,
+        );
+        let leverage_short = leverage_short(
+            self.trader_direction,
+            self.trader_leverage,
+            self.coordinator_leverage,
+        );
+
+        let coordinator_settlement_amount = calculate_coordinator_settlement_amount(
+            opening_price,
+            closing_price,
+            quantity,
+            leverage_long,
+            leverage_short,
+            self.trader_direction.opposite(),
+        )?
+        .min(closed_margin);
+        let trader_settlement_amount = closed_margin - coordinator_settlement_amount;
+
+        let trader_realized_pnl_sat = calculate_pnl_with_fees(
+            opening_price,
+            closing_price,
+            quantity,
+            self.trader_direction,
+            calculate_margin(opening_price, quantity, leverage_long),
+            calculate_margin(opening_price, quantity, leverage_short),
+            order_matching_fee_taker(quantity, opening_price).to_sat(),
+            order_matching_fee_taker(quantity, closing_price).to_sat(),
+        )?;
+
+        Ok((
+            coordinator_settlement_amount,
+            trader_settlement_amount,
+            trader_realized_pnl_sat,
+        ))
+    }
 }
 
 /// Calculate the settlement amount for the coordinator, based on the PNL and the order-matching
@@ -417,6 +679,7 @@ impl std::fmt::Debug for NewPosition {
             .field("coordinator_leverage", &self.coordinator_leverage)
             .field("trader_margin", &self.trader_margin)
             .field("stable", &self.stable)
+            .field("auto_rollover", &self.auto_rollover)
             .finish()
     }
 }
@@ -443,6 +706,7 @@ impl std::fmt::Debug for Position {
             .field("closing_price", &self.closing_price)
             .field("trader_margin", &self.trader_margin)
             .field("stable", &self.stable)
+            .field("auto_rollover", &self.auto_rollover)
             .field("trader_realized_pnl_sat", &self.trader_realized_pnl_sat)
             .finish()
     }
@@ -453,6 +717,7 @@ mod tests {
     use super::*;
     use rust_decimal_macros::dec;
     use std::str::FromStr;
+    use time::macros::datetime;
 
     #[test]
     fn position_calculate_coordinator_settlement_amount() {
@@ -478,6 +743,7 @@ mod tests {
             closing_price: None,
             trader_margin: 125_000,
             stable: false,
+            auto_rollover: true,
             trader_realized_pnl_sat: None,
         };
 
@@ -512,6 +778,7 @@ mod tests {
             closing_price: None,
             trader_margin: 125_000,
             stable: false,
+            auto_rollover: true,
             trader_realized_pnl_sat: None,
         };
 
@@ -546,6 +813,7 @@ mod tests {
             closing_price: None,
             trader_margin: 125_000,
             stable: false,
+            auto_rollover: true,
             trader_realized_pnl_sat: None,
         };
 
@@ -952,6 +1220,137 @@ mod tests {
         );
     }
 
+    #[test]
+    fn reducing_position_by_half_realizes_pnl_of_closed_contracts() {
+        let position = Position::dummy()
+            .with_quantity(100.0)
+            .with_average_entry_price(40_000.0)
+            .with_leverage(2.0)
+            .with_direction(Direction::Long);
+        let position = Position {
+            coordinator_margin: 125_000,
+            trader_margin: 125_000,
+            ..position
+        };
+
+        let reduction = position.calculate_reduction(50.0, dec!(42_000)).unwrap();
+
+        assert_eq!(
+            reduction,
+            PositionReduction {
+                remaining_quantity: 50.0,
+                remaining_coordinator_margin: 62_500,
+                remaining_trader_margin: 62_500,
+                // 62_500 margin - 5_952 pnl + 357 closing fee.
+                coordinator_settlement_amount: 56_905,
+                trader_settlement_amount: 68_095,
+                // 5_952 pnl - 375 opening fee - 357 closing fee.
+                trader_realized_pnl_sat: 5_220,
+            }
+        );
+        assert_eq!(
+            reduction.coordinator_settlement_amount
+                + reduction.trader_settlement_amount
+                + reduction.remaining_coordinator_margin
+                + reduction.remaining_trader_margin,
+            250_000
+        );
+    }
+
+    #[test]
+    fn adding_to_position_averages_entry_price() {
+        let position = Position::dummy()
+            .with_quantity(100.0)
+            .with_average_entry_price(40_000.0)
+            .with_leverage(2.0)
+            .with_direction(Direction::Long);
+
+        let increase = position.calculate_increase(100.0, dec!(60_000)).unwrap();
+
+        assert_eq!(increase.quantity, 200.0);
+        assert_eq!(increase.average_entry_price, dec!(48_000));
+        assert_eq!(increase.coordinator_margin, 208_333);
+        assert_eq!(increase.trader_margin, 208_333);
+        assert_eq!(increase.trader_liquidation_price, dec!(32_000));
+    }
+
+    #[test]
+    fn flipping_position_realizes_pnl_and_opens_opposite_position() {
+        let position = Position::dummy()
+            .with_quantity(100.0)
+            .with_average_entry_price(40_000.0)
+            .with_leverage(2.0)
+            .with_direction(Direction::Long);
+        let position = Position {
+            coordinator_margin: 125_000,
+            trader_margin: 125_000,
+            ..position
+        };
+
+        let flip = position.calculate_flip(300.0, dec!(50_000), 2.0).unwrap();
+
+        assert_eq!(
+            flip,
+            PositionFlip {
+                // 125_000 margin - 50_000 pnl + 600 closing fee.
+                coordinator_settlement_amount: 75_600,
+                trader_settlement_amount: 174_400,
+                // 50_000 pnl - 750 opening fee - 600 closing fee.
+                trader_realized_pnl_sat: 48_650,
+                quantity: 200.0,
+                trader_direction: Direction::Short,
+                trader_leverage: 2.0,
+                trader_liquidation_price: dec!(100_000),
+                coordinator_margin: 200_000,
+                trader_margin: 200_000,
+            }
+        );
+    }
+
+    #[test]
+    fn flipping_position_with_smaller_quantity_fails() {
+        let position = Position::dummy().with_quantity(100.0);
+
+        assert!(position.calculate_flip(100.0, dec!(10_000), 2.0).is_err());
+    }
+
+    #[test]
+    fn reducing_position_by_its_whole_quantity_fails() {
+        let position = Position::dummy().with_quantity(100.0);
+
+        assert!(position.calculate_reduction(100.0, dec!(10_000)).is_err());
+    }
+
+    #[test]
+    fn opted_out_position_is_not_rolled_over() {
+        // A Saturday, i.e. within the rollover window.
+        let now = datetime!(2024-03-23 12:00 UTC);
+        let position = Position {
+            expiry_timestamp: datetime!(2024-03-24 15:00 UTC),
+            ..Position::dummy()
+        };
+
+        assert!(position.is_due_for_rollover(now, Network::Bitcoin));
+
+        let opted_out_position = Position {
+            auto_rollover: false,
+            ..position
+        };
+
+        assert!(!opted_out_position.is_due_for_rollover(now, Network::Bitcoin));
+    }
+
+    #[test]
+    fn rolled_over_position_is_not_rolled_over_again() {
+        let now = datetime!(2024-03-23 12:00 UTC);
+        let position = Position {
+            expiry_timestamp: commons::calculate_next_expiry(now, Network::Bitcoin),
+            ..Position::dummy()
+        };
+
+        assert!(!position.is_due_for_rollover(now, Network::Bitcoin));
+    }
+
     fn dummy_quote(bid: u64, ask: u64) -> Quote {
         Quote {
             bid_size: 0,
@@ -987,6 +1386,7 @@ mod tests {
                 coordinator_leverage: 2.0,
                 trader_margin: 1000,
                 stable: false,
+                auto_rollover: true,
                 trader_realized_pnl_sat: None,
             }
         }
@@ -0,0 +1,219 @@
+//! Where the settlement price of a contract comes from.
+//!
+//! The payout of a DLC settled on-chain is determined by the oracle attestation, since that is
+//! what the CETs enforce. Hence, the oracle attestation is the only authoritative settlement
+//! price for a DLC. Positions closed collaboratively, e.g. at expiry, are paid out at the
+//! execution price of the closing trade instead.
+//!
+//! The index price and the last trade price may differ from the attestation. They can be
+//! configured as the reference settlement price for display, but never determine a payout.
+
+use crate::db;
+use crate::node::Node;
+use crate::payout_curve::ORACLE_NB_DIGITS;
+use anyhow::bail;
+use anyhow::ensure;
+use anyhow::Context;
+use anyhow::Result;
+use dlc_messages::oracle_msgs::OracleAttestation;
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
+use tokio::task::spawn_blocking;
+use trade::ContractSymbol;
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SettlementPriceSource {
+    /// The price attested to by the oracle. Authoritative for the payout of a DLC.
+    #[default]
+    Oracle,
+    /// The index price, see [`crate::node::index_price`].
+    Index,
+    /// The execution price of the last trade in the contract symbol.
+    LastTrade,
+}
+
+/// The source of the reference settlement price, per contract symbol.
+///
+/// Contract symbols without a configured source use the oracle.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Default)]
+#[serde(transparent)]
+pub struct SettlementPriceSettings(pub HashMap<ContractSymbol, SettlementPriceSource>);
+
+impl SettlementPriceSettings {
+    pub fn source(&self, contract_symbol: ContractSymbol) -> SettlementPriceSource {
+        self.0.get(&contract_symbol).copied().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SettlementPrice {
+    pub event_id: String,
+    pub contract_symbol: ContractSymbol,
+    pub source: SettlementPriceSource,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub price: Decimal,
+    /// Whether the price determines the payout of the DLCs on the event.
+    pub authoritative: bool,
+}
+
+/// The reference settlement price of the contracts on the oracle event `event_id`, from the
+/// source configured for its contract symbol.
+pub async fn reference_price(node: &Node, event_id: &str) -> Result<SettlementPrice> {
+    let contract_symbol = contract_symbol(event_id)
+        .with_context(|| format!("Unknown contract symbol in event {event_id}"))?;
+    let source = node
+        .settings
+        .read()
+        .await
+        .settlement_price
+        .source(contract_symbol);
+
+    let price = match source {
+        SettlementPriceSource::Oracle => {
+            let attestation = spawn_blocking({
+                let node = node.inner.clone();
+                let event_id = event_id.to_string();
+                move || node.get_oracle_attestation(&event_id)
+            })
+            .await
+            .expect("task to complete")?;
+
+            attested_price(&attestation)?
+        }
+        SettlementPriceSource::Index => {
+            // The index price is the BitMEX XBTUSD quote.
+            ensure!(
+                contract_symbol == ContractSymbol::BtcUsd,
+                "No index price for {contract_symbol}"
+            );
+
+            let quote = node
+                .index_price
+                .quote()
+                .context("No index price available")?;
+
+            (quote.bid_price + quote.ask_price) / Decimal::TWO
+        }
+        SettlementPriceSource::LastTrade => {
+            let mut conn = node.pool.get()?;
+            let trade = db::trades::get_latest_by_contract_symbol(&mut conn, contract_symbol)?
+                .with_context(|| format!("No trade in {contract_symbol}"))?;
+
+            Decimal::from_f32(trade.average_price).expect("to fit into decimal")
+        }
+    };
+
+    Ok(SettlementPrice {
+        event_id: event_id.to_string(),
+        contract_symbol,
+        source,
+        price,
+        authoritative: source == SettlementPriceSource::Oracle,
+    })
+}
+
+/// The price attested to by the oracle, encoded as binary digits, most significant first.
+pub fn attested_price(attestation: &OracleAttestation) -> Result<Decimal> {
+    ensure!(
+        attestation.outcomes.len() == ORACLE_NB_DIGITS,
+        "Expected {ORACLE_NB_DIGITS} outcomes, got {}",
+        attestation.outcomes.len()
+    );
+
+    let mut price = 0u64;
+    for outcome in attestation.outcomes.iter() {
+        let digit = match outcome.as_str() {
+            "0" => 0,
+            "1" => 1,
+            _ => bail!("Invalid outcome {outcome}"),
+        };
+
+        price = price * 2 + digit;
+    }
+
+    Ok(Decimal::from(price))
+}
+
+/// The contract symbol of an oracle event, whose id is the label of the contract symbol followed
+/// by the expiry timestamp.
+fn contract_symbol(event_id: &str) -> Option<ContractSymbol> {
+    ContractSymbol::ALL
+        .into_iter()
+        .find(|symbol| event_id.starts_with(&symbol.label()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::secp256k1::PublicKey;
+    use bitcoin::secp256k1::XOnlyPublicKey;
+    use ln_dlc_node::bitcoin_conversion::to_xonly_pk_29;
+    use std::str::FromStr;
+
+    #[test]
+    fn attested_price_decodes_binary_outcomes() {
+        // 50_000 = 0b0000_1100_0011_0101_0000
+        let outcomes = "00001100001101010000"
+            .chars()
+            .map(|digit| digit.to_string())
+            .collect();
+
+        let price = attested_price(&attestation(outcomes)).unwrap();
+
+        assert_eq!(price, Decimal::from(50_000));
+    }
+
+    #[test]
+    fn attested_price_requires_all_digits() {
+        let outcomes = vec!["1".to_string(); ORACLE_NB_DIGITS - 1];
+
+        assert!(attested_price(&attestation(outcomes)).is_err());
+    }
+
+    #[test]
+    fn unconfigured_contract_symbols_use_the_oracle() {
+        let settings = SettlementPriceSettings(HashMap::from([(
+            ContractSymbol::BtcUsd,
+            SettlementPriceSource::Index,
+        )]));
+
+        assert_eq!(
+            settings.source(ContractSymbol::BtcUsd),
+            SettlementPriceSource::Index
+        );
+        assert_eq!(
+            settings.source(ContractSymbol::EthUsd),
+            SettlementPriceSource::Oracle
+        );
+    }
+
+    #[test]
+    fn contract_symbol_is_parsed_from_event_id() {
+        assert_eq!(
+            contract_symbol("btcusd1711540800"),
+            Some(ContractSymbol::BtcUsd)
+        );
+        assert_eq!(
+            contract_symbol("ethusd1711540800"),
+            Some(ContractSymbol::EthUsd)
+        );
+        assert_eq!(contract_symbol("dogeusd1711540800"), None);
+    }
+
+    fn attestation(outcomes: Vec<String>) -> OracleAttestation {
+        let oracle_pk = PublicKey::from_str(
+            "02bd998ebd176715fe92b7467cf6b1df8023950a4dd911db4c94dfc89cc9f5a655",
+        )
+        .unwrap();
+
+        OracleAttestation {
+            oracle_public_key: to_xonly_pk_29(XOnlyPublicKey::from(oracle_pk)),
+            signatures: vec![],
+            outcomes,
+        }
+    }
+}
@@ -0,0 +1,391 @@
+use crate::db;
+use crate::db::rollover_fees::ConfirmedRolloverFee;
+use crate::dlc_protocol::DlcProtocolState;
+use crate::dlc_protocol::ProtocolId;
+use crate::dlc_protocol::ProtocolKind;
+use crate::position::models::Position;
+use crate::trade::history::TradeHistoryEntry;
+use crate::trade::models::Trade;
+use anyhow::Result;
+use bitcoin::secp256k1::PublicKey;
+use diesel::PgConnection;
+use dlc_manager::ContractId;
+use serde::Serialize;
+use time::OffsetDateTime;
+use trade::ContractSymbol;
+use trade::Direction;
+use uuid::Uuid;
+
+/// A DLC protocol run with a trader, as recorded in the database.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProtocolRecord {
+    pub id: ProtocolId,
+    pub previous_id: Option<ProtocolId>,
+    pub contract_id: ContractId,
+    pub state: DlcProtocolState,
+    pub kind: ProtocolKind,
+    pub timestamp: OffsetDateTime,
+}
+
+/// Everything that happened to a position, in chronological order.
+#[derive(Debug, Serialize)]
+pub struct PositionTimeline {
+    pub position_id: i32,
+    pub trader: PublicKey,
+    pub contract_symbol: ContractSymbol,
+    pub trader_direction: Direction,
+    pub quantity: f32,
+    pub average_entry_price: f32,
+    pub closing_price: Option<f32>,
+    pub trader_realized_pnl_sat: Option<i64>,
+    pub entries: Vec<TimelineEntry>,
+    /// The protocol which settled the position, if it has been settled.
+    pub settlement: Option<SettlementReference>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TimelineEntry {
+    Protocol(ProtocolEntry),
+    Trade(TradeHistoryEntry),
+}
+
+/// A DLC protocol run for the position.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ProtocolEntry {
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+    pub protocol_id: Uuid,
+    pub kind: ProtocolKind,
+    pub state: DlcProtocolState,
+    pub contract_id: String,
+    /// The oracle event the contract is bound to, if the contract is still known.
+    pub event_id: Option<String>,
+    /// The fee charged for a rollover.
+    pub fee_sats: Option<u64>,
+}
+
+/// A reference to the protocol which settled the position and the oracle event whose attestation
+/// the settled contract was bound to.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct SettlementReference {
+    pub protocol_id: Uuid,
+    pub contract_id: String,
+    pub event_id: Option<String>,
+}
+
+impl TimelineEntry {
+    fn timestamp(&self) -> OffsetDateTime {
+        match self {
+            TimelineEntry::Protocol(entry) => entry.timestamp,
+            TimelineEntry::Trade(entry) => entry.timestamp,
+        }
+    }
+}
+
+/// Load the timeline of the position with the given id.
+///
+/// The oracle event ids are looked up with `event_id`, since the contracts are only known to the
+/// DLC storage.
+pub fn load(
+    conn: &mut PgConnection,
+    position_id: i32,
+    event_id: impl Fn(&ContractId) -> Option<String>,
+) -> Result<Option<PositionTimeline>> {
+    let position = match db::positions::Position::get(conn, position_id)? {
+        Some(position) => position,
+        None => return Ok(None),
+    };
+
+    let protocols = db::dlc_protocols::get_dlc_protocols_by_trader(conn, &position.trader)?;
+    let trades = db::trades::get_by_position(conn, position.id)?;
+    let rollover_fees = db::rollover_fees::get_confirmed_by_trader(conn, position.trader)?;
+
+    let protocol_ids = protocols
+        .iter()
+        .filter(|protocol| matches!(protocol.kind, ProtocolKind::Open | ProtocolKind::Renew))
+        .map(|protocol| protocol.id)
+        .collect::<Vec<_>>();
+    let directions = db::trade_params::get_directions(conn, &protocol_ids)?;
+
+    let protocols = position_protocols(&protocols, position.creation_timestamp, |protocol_id| {
+        directions.get(&protocol_id.to_uuid()).copied()
+    });
+
+    Ok(Some(build_timeline(
+        position,
+        protocols,
+        trades,
+        rollover_fees,
+        event_id,
+    )))
+}
+
+/// Select the protocols which belong to the position opened at `opened_at`, oldest first.
+///
+/// The position is opened by the last `Open` or `Renew` protocol started before `opened_at`. From
+/// there we follow the protocol chain, i.e. the protocols referring to their predecessor, along
+/// the successful protocols until the position is settled or closed. Failed attempts are kept in
+/// the timeline, as they are part of what happened to the position.
+///
+/// A `Renew` protocol trading in the direction of the opening trade increases the position, hence
+/// we follow the chain through it. A `Renew` protocol trading in the other direction flips the
+/// position and, like an `Open` protocol, belongs to the next position. The trade direction of a
+/// protocol is looked up with `trade_direction`.
+fn position_protocols(
+    protocols: &[ProtocolRecord],
+    opened_at: OffsetDateTime,
+    trade_direction: impl Fn(ProtocolId) -> Option<Direction>,
+) -> Vec<ProtocolRecord> {
+    let opening = protocols.iter().rev().find(|protocol| {
+        matches!(protocol.kind, ProtocolKind::Open | ProtocolKind::Renew)
+            && protocol.timestamp <= opened_at
+    });
+
+    let mut current = match opening {
+        Some(opening) => opening,
+        None => return vec![],
+    };
+
+    let direction = trade_direction(current.id);
+    let belongs_to_next_position = |protocol: &ProtocolRecord| match protocol.kind {
+        ProtocolKind::Open => true,
+        ProtocolKind::Renew => direction.is_none() || trade_direction(protocol.id) != direction,
+        ProtocolKind::Settle
+        | ProtocolKind::PartialSettle
+        | ProtocolKind::Close
+        | ProtocolKind::ForceClose
+        | ProtocolKind::Rollover => false,
+    };
+
+    let mut position_protocols = vec![current.clone()];
+    loop {
+        let successors = protocols
+            .iter()
+            .filter(|protocol| protocol.previous_id == Some(current.id))
+            .filter(|protocol| !belongs_to_next_position(protocol))
+            .collect::<Vec<_>>();
+
+        position_protocols.extend(successors.iter().map(|protocol| (*protocol).clone()));
+
+        current = match successors
+            .into_iter()
+            .rev()
+            .find(|protocol| protocol.state != DlcProtocolState::Failed)
+        {
+            Some(next) => next,
+            None => break,
+        };
+
+        if matches!(
+            current.kind,
+            ProtocolKind::Settle | ProtocolKind::Close | ProtocolKind::ForceClose
+        ) {
+            break;
+        }
+    }
+
+    position_protocols.sort_by_key(|protocol| protocol.timestamp);
+    position_protocols
+}
+
+fn build_timeline(
+    position: Position,
+    protocols: Vec<ProtocolRecord>,
+    trades: Vec<Trade>,
+    rollover_fees: Vec<ConfirmedRolloverFee>,
+    event_id: impl Fn(&ContractId) -> Option<String>,
+) -> PositionTimeline {
+    let settlement = protocols
+        .iter()
+        .filter(|protocol| protocol.state == DlcProtocolState::Success)
+        .find(|protocol| {
+            matches!(
+                protocol.kind,
+                ProtocolKind::Settle | ProtocolKind::Close | ProtocolKind::ForceClose
+            )
+        })
+        .map(|protocol| SettlementReference {
+            protocol_id: protocol.id.to_uuid(),
+            contract_id: hex::encode(protocol.contract_id),
+            event_id: event_id(&protocol.contract_id),
+        });
+
+    let protocols = protocols.into_iter().map(|protocol| {
+        let fee_sats = rollover_fees
+            .iter()
+            .find(|fee| fee.protocol_id == protocol.id)
+            .map(|fee| fee.fee.to_sat());
+
+        TimelineEntry::Protocol(ProtocolEntry {
+            timestamp: protocol.timestamp,
+            protocol_id: protocol.id.to_uuid(),
+            kind: protocol.kind,
+            state: protocol.state,
+            contract_id: hex::encode(protocol.contract_id),
+            event_id: event_id(&protocol.contract_id),
+            fee_sats,
+        })
+    });
+    let trades = trades
+        .into_iter()
+        .map(|trade| TimelineEntry::Trade(TradeHistoryEntry::from(trade)));
+
+    let mut entries = protocols.chain(trades).collect::<Vec<_>>();
+    entries.sort_by_key(|entry| entry.timestamp());
+
+    PositionTimeline {
+        position_id: position.id,
+        trader: position.trader,
+        contract_symbol: position.contract_symbol,
+        trader_direction: position.trader_direction,
+        quantity: position.quantity,
+        average_entry_price: position.average_entry_price,
+        closing_price: position.closing_price,
+        trader_realized_pnl_sat: position.trader_realized_pnl_sat,
+        entries,
+        settlement,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    #[test]
+    fn position_protocols_follow_the_chain_until_the_settlement() {
+        let previous_settle = protocol(
+            ProtocolKind::Settle,
+            DlcProtocolState::Success,
+            None,
+            datetime!(2024-03-01 09:00:00 UTC),
+        );
+        let renew = protocol(
+            ProtocolKind::Renew,
+            DlcProtocolState::Success,
+            Some(&previous_settle),
+            datetime!(2024-03-02 09:00:00 UTC),
+        );
+        let failed_rollover = protocol(
+            ProtocolKind::Rollover,
+            DlcProtocolState::Failed,
+            Some(&renew),
+            datetime!(2024-03-08 15:00:00 UTC),
+        );
+        let rollover = protocol(
+            ProtocolKind::Rollover,
+            DlcProtocolState::Success,
+            Some(&renew),
+            datetime!(2024-03-08 16:00:00 UTC),
+        );
+        let settle = protocol(
+            ProtocolKind::Settle,
+            DlcProtocolState::Success,
+            Some(&rollover),
+            datetime!(2024-03-10 12:00:00 UTC),
+        );
+        let next_renew = protocol(
+            ProtocolKind::Renew,
+            DlcProtocolState::Success,
+            Some(&settle),
+            datetime!(2024-03-11 12:00:00 UTC),
+        );
+
+        let protocols = vec![
+            previous_settle,
+            renew.clone(),
+            failed_rollover.clone(),
+            rollover.clone(),
+            settle.clone(),
+            next_renew,
+        ];
+
+        let position_protocols =
+            position_protocols(&protocols, datetime!(2024-03-02 09:00:01 UTC), |_| {
+                Some(Direction::Long)
+            });
+
+        assert_eq!(
+            position_protocols,
+            vec![renew, failed_rollover, rollover, settle]
+        );
+    }
+
+    #[test]
+    fn position_protocols_follow_the_chain_through_increases_until_a_flip() {
+        let open = protocol(
+            ProtocolKind::Open,
+            DlcProtocolState::Success,
+            None,
+            datetime!(2024-03-01 09:00:00 UTC),
+        );
+        let increase = protocol(
+            ProtocolKind::Renew,
+            DlcProtocolState::Success,
+            Some(&open),
+            datetime!(2024-03-02 09:00:00 UTC),
+        );
+        let rollover = protocol(
+            ProtocolKind::Rollover,
+            DlcProtocolState::Success,
+            Some(&increase),
+            datetime!(2024-03-08 16:00:00 UTC),
+        );
+        let flip = protocol(
+            ProtocolKind::Renew,
+            DlcProtocolState::Success,
+            Some(&rollover),
+            datetime!(2024-03-10 12:00:00 UTC),
+        );
+
+        let protocols = vec![
+            open.clone(),
+            increase.clone(),
+            rollover.clone(),
+            flip.clone(),
+        ];
+
+        let position_protocols =
+            position_protocols(&protocols, datetime!(2024-03-01 09:00:01 UTC), |id| {
+                if id == flip.id {
+                    Some(Direction::Short)
+                } else {
+                    Some(Direction::Long)
+                }
+            });
+
+        assert_eq!(position_protocols, vec![open, increase, rollover]);
+    }
+
+    #[test]
+    fn position_without_opening_protocol_has_no_protocols() {
+        let rollover = protocol(
+            ProtocolKind::Rollover,
+            DlcProtocolState::Success,
+            None,
+            datetime!(2024-03-08 16:00:00 UTC),
+        );
+
+        assert!(
+            position_protocols(&[rollover], datetime!(2024-03-09 09:00:00 UTC), |_| None)
+                .is_empty()
+        );
+    }
+
+    fn protocol(
+        kind: ProtocolKind,
+        state: DlcProtocolState,
+        previous: Option<&ProtocolRecord>,
+        timestamp: OffsetDateTime,
+    ) -> ProtocolRecord {
+        ProtocolRecord {
+            id: ProtocolId::new(),
+            previous_id: previous.map(|previous| previous.id),
+            contract_id: [0; 32],
+            state,
+            kind,
+            timestamp,
+        }
+    }
+}
@@ -3,12 +3,17 @@ use crate::admin::collaborative_revert;
 use crate::admin::connect_to_peer;
 use crate::admin::delete_dlc_channel;
 use crate::admin::get_balance;
+use crate::admin::get_claimable_balances;
+use crate::admin::get_contract_protocols;
+use crate::admin::get_dlc_channel_protocols;
 use crate::admin::get_fee_rate_estimation;
+use crate::admin::get_node_status;
 use crate::admin::get_utxos;
 use crate::admin::is_connected;
 use crate::admin::list_dlc_channels;
 use crate::admin::list_on_chain_transactions;
 use crate::admin::list_peers;
+use crate::admin::override_oracle_attestation;
 use crate::admin::roll_back_dlc_channel;
 use crate::admin::sign_message;
 use crate::backup::SledBackup;
@@ -23,18 +28,33 @@ use crate::leaderboard::LeaderBoardCategory;
 use crate::leaderboard::LeaderBoardQueryParams;
 use crate::message::NewUserMessage;
 use crate::message::OrderbookMessage;
+use crate::node::dead_mans_switch;
 use crate::node::Node;
 use crate::notifications::Notification;
+use crate::orderbook::cancel_on_disconnect::CancelOnDisconnect;
+use crate::orderbook::routes::cancel_all_orders;
 use crate::orderbook::routes::delete_order;
+use crate::orderbook::routes::get_depth;
 use crate::orderbook::routes::get_order;
 use crate::orderbook::routes::get_orders;
 use crate::orderbook::routes::post_order;
 use crate::orderbook::routes::put_order;
 use crate::orderbook::routes::websocket_handler;
+use crate::orderbook::throttle::OrderThrottle;
 use crate::orderbook::trading::NewOrderMessage;
 use crate::parse_dlc_channel_id;
+use crate::position::settlement_price;
+use crate::position::settlement_price::SettlementPrice;
+use crate::position::timeline;
+use crate::position::timeline::PositionTimeline;
 use crate::settings::Settings;
 use crate::settings::SettingsFile;
+use crate::stats::PlatformStats;
+use crate::stats::StatsCache;
+use crate::trade::history;
+use crate::trade::history::TradeHistoryEntry;
+use crate::trade::history::TradeHistoryFormat;
+use crate::trade::history::TradeHistoryQueryParams;
 use crate::trade::websocket::InternalPositionUpdateMessage;
 use crate::AppError;
 use axum::extract::ConnectInfo;
@@ -43,8 +63,10 @@ use axum::extract::Path;
 use axum::extract::Query;
 use axum::extract::State;
 use axum::extract::WebSocketUpgrade;
+use axum::http::header;
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
+use axum::response::Response;
 use axum::routing::delete;
 use axum::routing::get;
 use axum::routing::post;
@@ -56,8 +78,10 @@ use bitcoin::secp256k1::ecdsa::Signature;
 use bitcoin::secp256k1::PublicKey;
 use bitcoin::secp256k1::Secp256k1;
 use bitcoin::secp256k1::VerifyOnly;
+use commons::is_signed_request_valid;
 use commons::Backup;
 use commons::CollaborativeRevertTraderResponse;
+use commons::DeadMansSwitchRequest;
 use commons::DeleteBackup;
 use commons::Message;
 use commons::Poll;
@@ -109,6 +133,9 @@ pub struct AppState {
     pub notification_sender: mpsc::Sender<Notification>,
     pub user_backup: SledBackup,
     pub secp: Secp256k1<VerifyOnly>,
+    pub platform_stats: StatsCache,
+    pub order_throttle: Arc<OrderThrottle>,
+    pub cancel_on_disconnect: CancelOnDisconnect,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -126,6 +153,7 @@ pub fn router(
     auth_users_notifier: mpsc::Sender<OrderbookMessage>,
     notification_sender: mpsc::Sender<Notification>,
     user_backup: SledBackup,
+    order_throttle: Arc<OrderThrottle>,
 ) -> Router {
     let secp = Secp256k1::verification_only();
 
@@ -144,6 +172,9 @@ pub fn router(
         notification_sender,
         user_backup,
         secp,
+        platform_stats: StatsCache::default(),
+        order_throttle,
+        cancel_on_disconnect: CancelOnDisconnect::default(),
     });
 
     Router::new()
@@ -163,18 +194,27 @@ pub fn router(
             "/api/orderbook/orders/:order_id",
             get(get_order).put(put_order).delete(delete_order),
         )
+        .route("/api/orderbook/orders/cancel-all", post(cancel_all_orders))
+        .route("/api/orderbook/depth", get(get_depth))
         .route("/api/orderbook/websocket", get(websocket_handler))
         .route("/api/rollover/:dlc_channel_id", post(rollover))
+        .route("/api/settlement-price/:event_id", get(get_settlement_price))
         // Deprecated: we just keep it for backwards compatbility as otherwise old apps won't
         // pass registration
         .route("/api/register", post(post_register))
         .route("/api/users", post(post_register))
         .route("/api/users/:trader_pubkey", get(get_user))
         .route("/api/users/nickname", put(update_nickname))
+        .route("/api/users/dead-mans-switch", put(update_dead_mans_switch))
         .route("/api/admin/wallet/balance", get(get_balance))
         .route("/api/admin/wallet/utxos", get(get_utxos))
+        .route(
+            "/api/admin/wallet/claimable_balances",
+            get(get_claimable_balances),
+        )
         .route("/api/admin/channels/:channel_id", delete(close_channel))
         .route("/api/admin/peers", get(list_peers))
+        .route("/api/admin/status", get(get_node_status))
         .route("/api/admin/dlc_channels", get(list_dlc_channels))
         .route(
             "/api/admin/dlc_channels/:channel_id",
@@ -184,6 +224,18 @@ pub fn router(
             "/api/admin/dlc_channels/rollback/:channel_id",
             post(roll_back_dlc_channel),
         )
+        .route(
+            "/api/admin/dlc_channels/:channel_id/protocols",
+            get(get_dlc_channel_protocols),
+        )
+        .route(
+            "/api/admin/contracts/:contract_id/protocols",
+            get(get_contract_protocols),
+        )
+        .route(
+            "/api/admin/oracle/attestation",
+            post(override_oracle_attestation),
+        )
         .route("/api/admin/transactions", get(list_on_chain_transactions))
         .route("/api/admin/sign/:msg", get(sign_message))
         .route("/api/admin/connect", post(connect_to_peer))
@@ -198,6 +250,12 @@ pub fn router(
             get(get_settings).put(update_settings),
         )
         .route("/api/admin/sync", post(post_sync))
+        .route("/api/admin/trades/:trader_pubkey", get(get_trade_history))
+        .route(
+            "/api/admin/positions/:position_id/timeline",
+            get(get_position_timeline),
+        )
+        .route("/api/admin/stats", get(get_platform_stats))
         .route("/api/admin/campaign/push", post(post_push_campaign))
         .route("/metrics", get(get_metrics))
         .route("/health", get(get_health))
@@ -359,6 +417,47 @@ pub async fn update_nickname(
     Ok(())
 }
 
+#[instrument(skip_all, err(Debug))]
+pub async fn update_dead_mans_switch(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<DeadMansSwitchRequest>,
+) -> Result<(), AppError> {
+    request
+        .verify(&state.secp)
+        .map_err(|_| AppError::Unauthorized)?;
+
+    if !is_signed_request_valid(request.timestamp, OffsetDateTime::now_utc()) {
+        return Err(AppError::BadRequest(
+            "Request to update dead man's switch has expired".to_string(),
+        ));
+    }
+
+    let timeout_secs = dead_mans_switch::parse_timeout(request.timeout_secs)
+        .map_err(|e| AppError::BadRequest(format!("{e:#}")))?;
+
+    tracing::info!(
+        trader_id = %request.trader_id,
+        ?timeout_secs,
+        "Updating dead man's switch"
+    );
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|e| AppError::InternalServerError(format!("Could not get connection: {e:#}")))?;
+
+    let updated_rows = user::set_dead_mans_switch(&mut conn, request.trader_id, timeout_secs)
+        .map_err(|e| {
+            AppError::InternalServerError(format!("Could not update dead man's switch: {e:#}"))
+        })?;
+
+    if updated_rows == 0 {
+        return Err(AppError::BadRequest("Unknown user".to_string()));
+    }
+
+    Ok(())
+}
+
 impl TryFrom<User> for commons::User {
     type Error = AppError;
     fn try_from(value: User) -> Result<Self, Self::Error> {
@@ -413,6 +512,8 @@ async fn update_settings(
         .await
         .map_err(|e| AppError::InternalServerError(format!("Could not write settings: {e:#}")))?;
 
+    *state.node.settings.write().await = settings.to_node_settings();
+
     // Forward relevant settings down to the LN-DLC node.
     state
         .node
@@ -615,6 +716,121 @@ fn parse_offset_datetime(date_str: String) -> anyhow::Result<Option<OffsetDateTi
     Ok(Some(date_time))
 }
 
+/// Aggregate statistics about the platform, cached for [`crate::stats::STATS_CACHE_TTL`].
+#[instrument(skip_all, err(Debug))]
+pub async fn get_platform_stats(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<PlatformStats>, AppError> {
+    let stats = state
+        .platform_stats
+        .get(state.pool.clone())
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Could not compute stats: {e:#}")))?;
+
+    Ok(Json(stats))
+}
+
+/// Export a page of a trader's trade history as JSON or CSV.
+///
+/// Optional arguments:
+/// - `[start]` and `[end]` restrict the trades to a date range, formatted as `YYYY-MM-DD`
+/// - `[format]` can be `json` or `csv`, default is `json`
+/// - `[limit]` and `[offset]` page through the trades, oldest first
+#[instrument(skip_all, err(Debug))]
+pub async fn get_trade_history(
+    State(state): State<Arc<AppState>>,
+    Path(trader_pubkey): Path<String>,
+    params: Query<TradeHistoryQueryParams>,
+) -> Result<Response, AppError> {
+    let trader_pubkey = PublicKey::from_str(trader_pubkey.as_str())
+        .map_err(|_| AppError::BadRequest("Invalid trader id provided".to_string()))?;
+
+    let start = params.start.clone().unwrap_or_default();
+    let start = parse_offset_datetime(start.clone())
+        .map_err(|err| {
+            AppError::BadRequest(format!(
+                "Invalid start date provided `{err}`. String provided {start}"
+            ))
+        })?
+        .unwrap_or(OffsetDateTime::UNIX_EPOCH);
+
+    let end = params.end.clone().unwrap_or_default();
+    let end = parse_offset_datetime(end.clone())
+        .map_err(|err| {
+            AppError::BadRequest(format!(
+                "Invalid end date provided `{err}`. String provided {end}"
+            ))
+        })?
+        .unwrap_or(OffsetDateTime::now_utc());
+
+    let limit = params
+        .limit
+        .unwrap_or(history::DEFAULT_PAGE_SIZE)
+        .clamp(1, history::MAX_PAGE_SIZE);
+    let offset = params.offset.unwrap_or_default().max(0);
+
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|e| AppError::InternalServerError(format!("Could not get connection: {e:#}")))?;
+
+    let trades =
+        db::trades::get_trades_by_trader(&mut conn, trader_pubkey, start, end, limit, offset)
+            .map_err(|e| AppError::InternalServerError(format!("Could not load trades: {e:#}")))?;
+
+    let entries = trades
+        .into_iter()
+        .map(TradeHistoryEntry::from)
+        .collect::<Vec<_>>();
+
+    let response = match params.format.unwrap_or_default() {
+        TradeHistoryFormat::Json => Json(entries).into_response(),
+        TradeHistoryFormat::Csv => (
+            [(header::CONTENT_TYPE, "text/csv")],
+            history::to_csv(&entries),
+        )
+            .into_response(),
+    };
+
+    Ok(response)
+}
+
+/// Everything that happened to a position in chronological order: the DLC protocols run for it
+/// and its trades, with prices, fees and the oracle events involved.
+#[instrument(skip_all, err(Debug))]
+pub async fn get_position_timeline(
+    State(state): State<Arc<AppState>>,
+    Path(position_id): Path<i32>,
+) -> Result<Json<PositionTimeline>, AppError> {
+    let mut conn = state
+        .pool
+        .get()
+        .map_err(|e| AppError::InternalServerError(format!("Could not get connection: {e:#}")))?;
+
+    let timeline = timeline::load(&mut conn, position_id, |contract_id| {
+        let contract = state.node.inner.get_contract_by_id(contract_id).ok()??;
+        ln_dlc_node::ContractDetails::from(contract).event_id
+    })
+    .map_err(|e| AppError::InternalServerError(format!("Could not load timeline: {e:#}")))?
+    .ok_or_else(|| AppError::BadRequest("No position found".to_string()))?;
+
+    Ok(Json(timeline))
+}
+
+/// The reference settlement price of the contracts on an oracle event, from the source configured
+/// for its contract symbol. Only the oracle attestation is authoritative for the payout.
+#[instrument(skip_all, err(Debug))]
+pub async fn get_settlement_price(
+    State(state): State<Arc<AppState>>,
+    Path(event_id): Path<String>,
+) -> Result<Json<SettlementPrice>, AppError> {
+    let price = settlement_price::reference_price(&state.node, &event_id)
+        .await
+        .map_err(|e| AppError::BadRequest(format!("Could not get settlement price: {e:#}")))?;
+
+    Ok(Json(price))
+}
+
 pub async fn get_leaderboard(
     State(state): State<Arc<AppState>>,
     params: Query<LeaderBoardQueryParams>,
@@ -157,6 +157,12 @@ fn build_rollover_notification_job(
         match db::positions::Position::get_all_open_positions_with_expiry_before(&mut conn, expiry)
         {
             Ok(positions) => Box::pin({
+                // Positions which opted out of auto-rollover are closed at expiry instead.
+                let positions = positions
+                    .into_iter()
+                    .filter(|position| position.auto_rollover)
+                    .collect::<Vec<_>>();
+
                 tracing::debug!(
                     nr_of_positions = positions.len(),
                     "Found positions to rollover"
@@ -145,6 +145,7 @@ diesel::table! {
         trader_pubkey -> Text,
         timestamp -> Timestamptz,
         protocol_type -> ProtocolTypeType,
+        failure_reason -> Nullable<Text>,
     }
 }
 
@@ -218,6 +219,14 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    node_identity (id) {
+        id -> Int4,
+        node_id -> Text,
+        timestamp -> Timestamptz,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use super::sql_types::DirectionType;
@@ -241,6 +250,7 @@ diesel::table! {
         leverage -> Float4,
         order_reason -> OrderReasonType,
         stable -> Bool,
+        auto_rollover -> Bool,
     }
 }
 
@@ -306,6 +316,19 @@ diesel::table! {
         coordinator_leverage -> Float4,
         trader_margin -> Int8,
         stable -> Bool,
+        auto_rollover -> Bool,
+    }
+}
+
+diesel::table! {
+    rollover_fees (id) {
+        id -> Int4,
+        protocol_id -> Uuid,
+        position_id -> Int4,
+        trader_pubkey -> Text,
+        fee_sats -> Int8,
+        is_confirmed -> Bool,
+        timestamp -> Timestamptz,
     }
 }
 
@@ -384,6 +407,8 @@ diesel::table! {
         last_login -> Timestamptz,
         nickname -> Nullable<Text>,
         version -> Nullable<Text>,
+        dead_mans_switch_timeout_secs -> Nullable<Int4>,
+        last_heartbeat -> Nullable<Timestamptz>,
     }
 }
 
@@ -391,6 +416,7 @@ diesel::joinable!(answers -> choices (choice_id));
 diesel::joinable!(choices -> polls (poll_id));
 diesel::joinable!(last_outbound_dlc_messages -> dlc_messages (message_hash));
 diesel::joinable!(liquidity_request_logs -> liquidity_options (liquidity_option));
+diesel::joinable!(rollover_fees -> positions (position_id));
 diesel::joinable!(trades -> positions (position_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
@@ -406,10 +432,12 @@ diesel::allow_tables_to_appear_in_same_query!(
     liquidity_options,
     liquidity_request_logs,
     matches,
+    node_identity,
     orders,
     payments,
     polls,
     positions,
+    rollover_fees,
     routing_fees,
     spendable_outputs,
     trade_params,
@@ -1,4 +1,10 @@
+use crate::node::index_price::IndexPriceGuardSettings;
+use crate::node::payout_reconciliation::PayoutReconciliationSettings;
+use crate::node::reconcile::ReconciliationPolicy;
+use crate::node::stuck_protocols::DlcProtocolTimeouts;
 use crate::node::NodeSettings;
+use crate::position::settlement_price::SettlementPriceSettings;
+use crate::webhook::PositionWebhookSettings;
 use anyhow::Context;
 use anyhow::Result;
 use bitcoin::secp256k1::PublicKey;
@@ -7,6 +13,7 @@ use serde::Deserialize;
 use serde::Serialize;
 use std::path::Path;
 use std::path::PathBuf;
+use std::time::Duration;
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
 
@@ -56,6 +63,40 @@ pub struct Settings {
 
     /// A list of makers who are allowed to post limit orders. This is to prevent spam.
     pub whitelisted_makers: Vec<PublicKey>,
+
+    /// The minimum leverage a trader is allowed to trade with.
+    pub min_leverage: f32,
+
+    /// The maximum leverage a trader is allowed to trade with.
+    pub max_leverage: f32,
+
+    /// If set, position lifecycle events are delivered to this webhook.
+    pub position_webhook: Option<PositionWebhookSettings>,
+
+    /// How to handle inconsistencies between positions and DLC channels found on startup.
+    pub reconciliation_policy: ReconciliationPolicy,
+
+    /// The minimum time between two order submissions of the same trader. Zero disables the
+    /// throttling.
+    pub min_order_interval: Duration,
+
+    /// The fee in sats charged to the trader for rolling over their position. Zero disables the
+    /// fee.
+    pub rollover_fee_sats: u64,
+
+    /// How long a DLC protocol may stay pending before it is considered stuck, per protocol type.
+    pub dlc_protocol_timeouts: DlcProtocolTimeouts,
+
+    /// When to ignore an index price update as a glitch of the feed.
+    pub index_price_guard: IndexPriceGuardSettings,
+
+    /// Where the reference settlement price of each contract symbol comes from. The oracle
+    /// attestation is authoritative for the payout of a DLC regardless.
+    pub settlement_price: SettlementPriceSettings,
+
+    /// When to report a mismatch between the payout of a contract settled with an oracle
+    /// attestation and the PnL we computed for its position.
+    pub payout_reconciliation: PayoutReconciliationSettings,
 }
 
 impl Settings {
@@ -89,6 +130,14 @@ impl Settings {
     pub fn to_node_settings(&self) -> NodeSettings {
         NodeSettings {
             allow_opening_positions: self.new_positions_enabled,
+            min_leverage: self.min_leverage,
+            max_leverage: self.max_leverage,
+            position_webhook: self.position_webhook.clone(),
+            rollover_fee_sats: self.rollover_fee_sats,
+            dlc_protocol_timeouts: self.dlc_protocol_timeouts,
+            index_price_guard: self.index_price_guard,
+            settlement_price: self.settlement_price.clone(),
+            payout_reconciliation: self.payout_reconciliation,
         }
     }
 
@@ -107,6 +156,16 @@ impl Settings {
             path,
             whitelist_enabled: file.whitelist_enabled,
             whitelisted_makers: file.whitelisted_makers,
+            min_leverage: file.min_leverage,
+            max_leverage: file.max_leverage,
+            position_webhook: file.position_webhook,
+            reconciliation_policy: file.reconciliation_policy,
+            min_order_interval: Duration::from_millis(file.min_order_interval_ms),
+            rollover_fee_sats: file.rollover_fee_sats,
+            dlc_protocol_timeouts: file.dlc_protocol_timeouts,
+            index_price_guard: file.index_price_guard,
+            settlement_price: file.settlement_price,
+            payout_reconciliation: file.payout_reconciliation,
         }
     }
 }
@@ -126,6 +185,46 @@ pub struct SettingsFile {
 
     whitelist_enabled: bool,
     whitelisted_makers: Vec<PublicKey>,
+
+    #[serde(default = "default_min_leverage")]
+    min_leverage: f32,
+    #[serde(default = "default_max_leverage")]
+    max_leverage: f32,
+
+    position_webhook: Option<PositionWebhookSettings>,
+
+    #[serde(default)]
+    reconciliation_policy: ReconciliationPolicy,
+
+    #[serde(default = "default_min_order_interval_ms")]
+    min_order_interval_ms: u64,
+
+    #[serde(default)]
+    rollover_fee_sats: u64,
+
+    #[serde(default)]
+    dlc_protocol_timeouts: DlcProtocolTimeouts,
+
+    #[serde(default)]
+    index_price_guard: IndexPriceGuardSettings,
+
+    #[serde(default)]
+    settlement_price: SettlementPriceSettings,
+
+    #[serde(default)]
+    payout_reconciliation: PayoutReconciliationSettings,
+}
+
+fn default_min_leverage() -> f32 {
+    1.0
+}
+
+fn default_max_leverage() -> f32 {
+    5.0
+}
+
+fn default_min_order_interval_ms() -> u64 {
+    100
 }
 
 impl From<Settings> for SettingsFile {
@@ -139,6 +238,16 @@ impl From<Settings> for SettingsFile {
             close_expired_position_scheduler: value.close_expired_position_scheduler,
             whitelist_enabled: false,
             whitelisted_makers: value.whitelisted_makers,
+            min_leverage: value.min_leverage,
+            max_leverage: value.max_leverage,
+            position_webhook: value.position_webhook,
+            reconciliation_policy: value.reconciliation_policy,
+            min_order_interval_ms: value.min_order_interval.as_millis() as u64,
+            rollover_fee_sats: value.rollover_fee_sats,
+            dlc_protocol_timeouts: value.dlc_protocol_timeouts,
+            index_price_guard: value.index_price_guard,
+            settlement_price: value.settlement_price,
+            payout_reconciliation: value.payout_reconciliation,
         }
     }
 }
@@ -146,7 +255,11 @@ impl From<Settings> for SettingsFile {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::position::settlement_price::SettlementPriceSource;
+    use crate::webhook::PositionEventType;
+    use std::collections::HashMap;
     use std::str::FromStr;
+    use trade::ContractSymbol;
 
     #[test]
     fn toml_serde_roundtrip() {
@@ -159,6 +272,8 @@ mod tests {
                 fee_rate_sync_interval: std::time::Duration::from_secs(1),
                 sub_channel_manager_periodic_check_interval: std::time::Duration::from_secs(1),
                 shadow_sync_interval: std::time::Duration::from_secs(1),
+                oracle_cache_ttl: std::time::Duration::from_secs(1),
+                oracle_negative_cache_ttl: std::time::Duration::from_secs(1),
             },
             rollover_window_open_scheduler: "foo".to_string(),
             rollover_window_close_scheduler: "bar".to_string(),
@@ -168,6 +283,34 @@ mod tests {
                 "0218845781f631c48f1c9709e23092067d06837f30aa0cd0544ac887fe91ddd166",
             )
             .unwrap()],
+            min_leverage: 1.0,
+            max_leverage: 5.0,
+            position_webhook: Some(PositionWebhookSettings {
+                url: "http://localhost:8080/webhook".to_string(),
+                events: vec![PositionEventType::Opened, PositionEventType::Settled],
+            }),
+            reconciliation_policy: ReconciliationPolicy::Report,
+            min_order_interval_ms: 100,
+            rollover_fee_sats: 1_000,
+            dlc_protocol_timeouts: DlcProtocolTimeouts {
+                open_secs: 600,
+                renew_secs: 600,
+                settle_secs: 300,
+                close_secs: 300,
+                rollover_secs: 120,
+            },
+            index_price_guard: IndexPriceGuardSettings {
+                max_deviation_percent: 5.0,
+                window_secs: 120,
+            },
+            settlement_price: SettlementPriceSettings(HashMap::from([
+                (ContractSymbol::BtcUsd, SettlementPriceSource::Index),
+                (ContractSymbol::EthUsd, SettlementPriceSource::Oracle),
+            ])),
+            payout_reconciliation: PayoutReconciliationSettings {
+                tolerance_sats: 1_000,
+                tolerance_percent: 0.5,
+            },
         };
 
         let serialized = toml::to_string_pretty(&original).unwrap();
@@ -176,4 +319,47 @@ mod tests {
 
         assert_eq!(original, deserialized);
     }
+
+    #[test]
+    fn settings_added_after_release_default_if_missing() {
+        let settings = include_str!("../example-settings/test-coordinator-settings.toml")
+            .lines()
+            .filter(|line| {
+                ![
+                    "min_leverage",
+                    "max_leverage",
+                    "reconciliation_policy",
+                    "min_order_interval_ms",
+                    "rollover_fee_sats",
+                ]
+                .iter()
+                .any(|key| line.starts_with(key))
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let settings: SettingsFile = toml::from_str(&settings).unwrap();
+
+        assert_eq!(settings.min_leverage, 1.0);
+        assert_eq!(settings.max_leverage, 5.0);
+        assert_eq!(settings.reconciliation_policy, ReconciliationPolicy::Report);
+        assert_eq!(settings.min_order_interval_ms, 100);
+        assert_eq!(settings.rollover_fee_sats, 0);
+    }
+
+    #[test]
+    fn oracle_cache_ttls_default_if_missing() {
+        let settings = r#"
+            off_chain_sync_interval = 5
+            on_chain_sync_interval = 300
+            fee_rate_sync_interval = 20
+            sub_channel_manager_periodic_check_interval = 30
+            shadow_sync_interval = 600
+        "#;
+
+        let settings: LnDlcNodeSettings = toml::from_str(settings).unwrap();
+
+        assert_eq!(settings.oracle_cache_ttl, Duration::from_secs(60));
+        assert_eq!(settings.oracle_negative_cache_ttl, Duration::from_secs(5));
+    }
 }
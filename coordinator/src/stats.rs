@@ -0,0 +1,120 @@
+use crate::db;
+use crate::position::models::Position;
+use anyhow::Result;
+use diesel::r2d2::ConnectionManager;
+use diesel::r2d2::Pool;
+use diesel::PgConnection;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+use std::time::Instant;
+use time::OffsetDateTime;
+use tokio::sync::Mutex;
+use tokio::task::spawn_blocking;
+use trade::ContractSymbol;
+
+/// How long the statistics are served from the cache before they are computed again.
+///
+/// Computing them requires loading all open positions and the trades of the last 24 hours, so
+/// the returned statistics can be up to this old. See [`PlatformStats::computed_at`].
+pub const STATS_CACHE_TTL: Duration = Duration::from_secs(60);
+
+const VOLUME_WINDOW: time::Duration = time::Duration::hours(24);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlatformStats {
+    pub total_users: i64,
+    pub open_positions: usize,
+    /// The traded quantity per contract symbol over the last 24 hours.
+    pub volume_24h: HashMap<ContractSymbol, f32>,
+    /// The margin locked in open positions by traders and the coordinator.
+    pub total_collateral_sats: u64,
+    #[serde(with = "time::serde::rfc3339")]
+    pub computed_at: OffsetDateTime,
+}
+
+#[derive(Default)]
+pub struct StatsCache {
+    cached: Mutex<Option<(Instant, PlatformStats)>>,
+}
+
+impl StatsCache {
+    /// Return the cached statistics, computing them again if they are older than
+    /// [`STATS_CACHE_TTL`].
+    pub async fn get(&self, pool: Pool<ConnectionManager<PgConnection>>) -> Result<PlatformStats> {
+        let mut cached = self.cached.lock().await;
+
+        if let Some((computed_at, stats)) = cached.as_ref() {
+            if computed_at.elapsed() < STATS_CACHE_TTL {
+                return Ok(stats.clone());
+            }
+        }
+
+        let stats = spawn_blocking(move || {
+            let mut conn = pool.get()?;
+            compute_platform_stats(&mut conn)
+        })
+        .await
+        .expect("task to complete")?;
+
+        *cached = Some((Instant::now(), stats.clone()));
+
+        Ok(stats)
+    }
+}
+
+fn compute_platform_stats(conn: &mut PgConnection) -> Result<PlatformStats> {
+    let now = OffsetDateTime::now_utc();
+
+    let total_users = db::user::count(conn)?;
+    let open_positions = db::positions::Position::get_all_open_positions(conn)?;
+    let trades = db::trades::get_quantities_since(conn, now - VOLUME_WINDOW)?;
+
+    Ok(PlatformStats {
+        total_users,
+        open_positions: open_positions.len(),
+        volume_24h: volume_per_symbol(&trades),
+        total_collateral_sats: total_collateral(&open_positions),
+        computed_at: now,
+    })
+}
+
+/// Sum up the traded quantities per contract symbol, including symbols without any trades.
+fn volume_per_symbol(trades: &[(ContractSymbol, f32)]) -> HashMap<ContractSymbol, f32> {
+    let mut volume = ContractSymbol::ALL
+        .into_iter()
+        .map(|symbol| (symbol, 0.0))
+        .collect::<HashMap<_, _>>();
+
+    for (symbol, quantity) in trades {
+        *volume.entry(*symbol).or_default() += quantity;
+    }
+
+    volume
+}
+
+fn total_collateral(positions: &[Position]) -> u64 {
+    positions
+        .iter()
+        .map(|position| (position.trader_margin + position.coordinator_margin).max(0) as u64)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn volume_is_summed_per_symbol() {
+        let trades = vec![
+            (ContractSymbol::BtcUsd, 100.0),
+            (ContractSymbol::BtcUsd, 250.0),
+            (ContractSymbol::BtcUsd, 50.0),
+        ];
+
+        let volume = volume_per_symbol(&trades);
+
+        assert_eq!(volume[&ContractSymbol::BtcUsd], 400.0);
+        assert_eq!(volume[&ContractSymbol::EthUsd], 0.0);
+    }
+}
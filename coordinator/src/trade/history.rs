@@ -0,0 +1,137 @@
+use crate::decimal_from_f32;
+use crate::trade::models::Trade;
+use commons::order_matching_fee_taker;
+use serde::Deserialize;
+use serde::Serialize;
+use std::fmt::Write;
+use time::OffsetDateTime;
+use trade::ContractSymbol;
+use trade::Direction;
+
+/// The number of trades returned per page if no `limit` is provided.
+pub const DEFAULT_PAGE_SIZE: i64 = 100;
+
+/// The maximum number of trades returned per page, to keep the response size bounded.
+pub const MAX_PAGE_SIZE: i64 = 1_000;
+
+const CSV_HEADER: &str = "timestamp,contract_symbol,direction,quantity,leverage,price,fee_sats";
+
+#[derive(Debug, Deserialize)]
+pub struct TradeHistoryQueryParams {
+    /// Inclusive start date in the format `YYYY-MM-DD`.
+    pub(crate) start: Option<String>,
+    /// Exclusive end date in the format `YYYY-MM-DD`.
+    pub(crate) end: Option<String>,
+    pub(crate) format: Option<TradeHistoryFormat>,
+    pub(crate) limit: Option<i64>,
+    pub(crate) offset: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TradeHistoryFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+/// A single trade of a trader, as exported to the trader.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct TradeHistoryEntry {
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+    pub contract_symbol: ContractSymbol,
+    pub direction: Direction,
+    pub quantity: f32,
+    pub leverage: f32,
+    /// The average execution price of the trade.
+    pub price: f32,
+    /// The order-matching fee paid by the trader.
+    pub fee_sats: u64,
+}
+
+impl From<Trade> for TradeHistoryEntry {
+    fn from(trade: Trade) -> Self {
+        let fee = order_matching_fee_taker(trade.quantity, decimal_from_f32(trade.average_price));
+
+        Self {
+            timestamp: trade.timestamp,
+            contract_symbol: trade.contract_symbol,
+            direction: trade.direction,
+            quantity: trade.quantity,
+            leverage: trade.trader_leverage,
+            price: trade.average_price,
+            fee_sats: fee.to_sat(),
+        }
+    }
+}
+
+/// Render the trade history as CSV, including a header row.
+pub fn to_csv(entries: &[TradeHistoryEntry]) -> String {
+    let mut csv = format!("{CSV_HEADER}\n");
+
+    for entry in entries {
+        let timestamp = entry
+            .timestamp
+            .format(&time::format_description::well_known::Rfc3339)
+            .expect("timestamp to be formattable");
+        let direction = entry.direction.as_str();
+
+        writeln!(
+            csv,
+            "{timestamp},{},{direction},{},{},{},{}",
+            entry.contract_symbol.label(),
+            entry.quantity,
+            entry.leverage,
+            entry.price,
+            entry.fee_sats
+        )
+        .expect("writing to a string to succeed");
+    }
+
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    #[test]
+    fn trade_history_to_csv() {
+        let entries = vec![
+            TradeHistoryEntry {
+                timestamp: datetime!(2024-03-18 09:00:00 UTC),
+                contract_symbol: ContractSymbol::BtcUsd,
+                direction: Direction::Long,
+                quantity: 100.0,
+                leverage: 2.0,
+                price: 50_000.0,
+                fee_sats: 600,
+            },
+            TradeHistoryEntry {
+                timestamp: datetime!(2024-03-19 10:30:00 UTC),
+                contract_symbol: ContractSymbol::BtcUsd,
+                direction: Direction::Short,
+                quantity: 100.0,
+                leverage: 2.0,
+                price: 51_000.5,
+                fee_sats: 588,
+            },
+        ];
+
+        let csv = to_csv(&entries);
+
+        assert_eq!(
+            csv,
+            "timestamp,contract_symbol,direction,quantity,leverage,price,fee_sats\n\
+             2024-03-18T09:00:00Z,btcusd,long,100,2,50000,600\n\
+             2024-03-19T10:30:00Z,btcusd,short,100,2,51000.5,588\n"
+        );
+    }
+
+    #[test]
+    fn empty_trade_history_to_csv_only_has_header() {
+        assert_eq!(to_csv(&[]), format!("{CSV_HEADER}\n"));
+    }
+}
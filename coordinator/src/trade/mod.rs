@@ -1,7 +1,6 @@
 use crate::compute_relative_contracts;
 use crate::db;
 use crate::decimal_from_f32;
-use crate::dlc_protocol;
 use crate::dlc_protocol::DlcProtocolType;
 use crate::dlc_protocol::ProtocolId;
 use crate::message::OrderbookMessage;
@@ -43,12 +42,11 @@ use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use time::OffsetDateTime;
 use tokio::sync::mpsc;
-use trade::cfd::calculate_long_liquidation_price;
+use trade::cfd::calculate_liquidation_price;
 use trade::cfd::calculate_margin;
-use trade::cfd::calculate_short_liquidation_price;
-use trade::Direction;
 use uuid::Uuid;
 
+pub mod history;
 pub mod models;
 pub mod websocket;
 
@@ -63,7 +61,18 @@ pub enum TradeAction {
         channel_id: DlcChannelId,
         position: Box<Position>,
     },
-    ResizePosition,
+    ReducePosition {
+        channel_id: DlcChannelId,
+        position: Box<Position>,
+    },
+    IncreasePosition {
+        channel_id: DlcChannelId,
+        position: Box<Position>,
+    },
+    FlipPosition {
+        channel_id: DlcChannelId,
+        position: Box<Position>,
+    },
 }
 
 pub struct TradeExecutor {
@@ -130,7 +139,13 @@ impl TradeExecutor {
     ///
     /// 2. If no position is found, we open a position.
     ///
-    /// 3. If a position of differing quantity is found, we resize the position.
+    /// 3. If a position of greater quantity and opposite direction is found, we reduce the
+    /// position.
+    ///
+    /// 4. If a position of the same direction is found, we add to the position.
+    ///
+    /// 5. If a position of smaller quantity and opposite direction is found, we close the position
+    /// and open a position in the opposite direction for the remaining quantity.
     async fn execute_internal(&self, params: &TradeAndChannelParams) -> Result<()> {
         let mut connection = self.node.pool.get()?;
 
@@ -150,9 +165,35 @@ impl TradeExecutor {
             order.order_state
         );
 
+        params
+            .trade_params
+            .validate()
+            .context("Invalid trade params")?;
+
+        let trade_action = self.determine_trade_action(&mut connection, params).await?;
+
+        // Closing or reducing a position has to remain possible at the position's leverage, even
+        // if the allowed leverage range has changed since the position was opened.
+        if matches!(
+            trade_action,
+            TradeAction::OpenDlcChannel
+                | TradeAction::OpenPosition { .. }
+                | TradeAction::IncreasePosition { .. }
+                | TradeAction::FlipPosition { .. }
+        ) {
+            let leverage_range = {
+                let settings = self.node.settings.read().await;
+                settings.min_leverage..=settings.max_leverage
+            };
+            params
+                .trade_params
+                .validate_leverage(leverage_range)
+                .context("Invalid trade params")?;
+        }
+
         tracing::info!(%trader_id, %order_id, "Executing match");
 
-        match self.determine_trade_action(&mut connection, params).await? {
+        match trade_action {
             TradeAction::OpenDlcChannel => {
                 let collateral_reserve_coordinator = params
                     .coordinator_reserve
@@ -167,6 +208,7 @@ impl TradeExecutor {
                     collateral_reserve_coordinator,
                     collateral_reserve_trader,
                     is_stable_order,
+                    order.auto_rollover,
                 )
                 .await
                 .context("Failed to open DLC channel")?;
@@ -183,6 +225,7 @@ impl TradeExecutor {
                     own_payout,
                     counter_payout,
                     is_stable_order,
+                    order.auto_rollover,
                 )
                 .await
                 .context("Failed to open new position")?,
@@ -198,7 +241,27 @@ impl TradeExecutor {
                 )
                 .await
                 .with_context(|| format!("Failed at closing position {}", position.id))?,
-            TradeAction::ResizePosition => unimplemented!(),
+            TradeAction::ReducePosition {
+                channel_id,
+                position,
+            } => self
+                .start_reducing_position(&position, &params.trade_params, channel_id)
+                .await
+                .with_context(|| format!("Failed at reducing position {}", position.id))?,
+            TradeAction::IncreasePosition {
+                channel_id,
+                position,
+            } => self
+                .start_increasing_position(&position, &params.trade_params, channel_id)
+                .await
+                .with_context(|| format!("Failed at increasing position {}", position.id))?,
+            TradeAction::FlipPosition {
+                channel_id,
+                position,
+            } => self
+                .start_flipping_position(&position, &params.trade_params, channel_id)
+                .await
+                .with_context(|| format!("Failed at flipping position {}", position.id))?,
         };
 
         Ok(())
@@ -211,6 +274,7 @@ impl TradeExecutor {
         collateral_reserve_coordinator: Amount,
         collateral_reserve_trader: Amount,
         stable: bool,
+        auto_rollover: bool,
     ) -> Result<()> {
         let peer_id = trade_params.pubkey;
 
@@ -314,16 +378,17 @@ impl TradeExecutor {
             .await
             .context("Could not propose DLC channel")?;
 
-        let protocol_executor = dlc_protocol::DlcProtocolExecutor::new(self.node.pool.clone());
-        protocol_executor.start_dlc_protocol(
-            protocol_id,
-            None,
-            &temporary_contract_id,
-            &temporary_channel_id,
-            DlcProtocolType::Open {
-                trade_params: (protocol_id, trade_params).into(),
-            },
-        )?;
+        self.node
+            .start_dlc_protocol(
+                protocol_id,
+                None,
+                temporary_contract_id,
+                temporary_channel_id,
+                DlcProtocolType::Open {
+                    trade_params: (protocol_id, trade_params).into(),
+                },
+            )
+            .await?;
 
         // After the DLC channel has been proposed the position can be created. This fixes
         // https://github.com/get10101/10101/issues/537, where the position was created before the
@@ -344,9 +409,11 @@ impl TradeExecutor {
             temporary_contract_id,
             leverage_coordinator,
             stable,
+            auto_rollover,
         )
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn open_position(
         &self,
         conn: &mut PgConnection,
@@ -355,6 +422,7 @@ impl TradeExecutor {
         coordinator_dlc_channel_collateral: u64,
         trader_dlc_channel_collateral: u64,
         stable: bool,
+        auto_rollover: bool,
     ) -> Result<()> {
         let peer_id = trade_params.pubkey;
 
@@ -438,17 +506,7 @@ impl TradeExecutor {
         let maturity_time = trade_params.filled_with.expiry_timestamp;
         let maturity_time = maturity_time.unix_timestamp();
 
-        let sats_per_vbyte = self
-            .node
-            .inner
-            .fee_rate_estimator
-            .get(ConfirmationTarget::Normal)
-            .as_sat_per_vb()
-            .round();
-        // This fee rate is used to construct the CET transactions.
-        let fee_rate = Decimal::try_from(sats_per_vbyte)?
-            .to_u64()
-            .context("failed to convert to u64")?;
+        let fee_rate = self.cet_fee_rate()?;
 
         // The contract input to be used for setting up the trade between the trader and the
         // coordinator.
@@ -488,16 +546,17 @@ impl TradeExecutor {
             .await
             .context("Could not propose DLC channel update")?;
 
-        let protocol_executor = dlc_protocol::DlcProtocolExecutor::new(self.node.pool.clone());
-        protocol_executor.start_dlc_protocol(
-            protocol_id,
-            previous_id,
-            &temporary_contract_id,
-            &channel.get_id(),
-            DlcProtocolType::Renew {
-                trade_params: (protocol_id, trade_params).into(),
-            },
-        )?;
+        self.node
+            .start_dlc_protocol(
+                protocol_id,
+                previous_id,
+                temporary_contract_id,
+                channel.get_id(),
+                DlcProtocolType::Renew {
+                    trade_params: (protocol_id, trade_params).into(),
+                },
+            )
+            .await?;
 
         // TODO(holzeis): The position should only get created after the dlc protocol has finished
         // successfully.
@@ -507,6 +566,7 @@ impl TradeExecutor {
             temporary_contract_id,
             leverage_coordinator,
             stable,
+            auto_rollover,
         )
     }
 
@@ -518,6 +578,7 @@ impl TradeExecutor {
         temporary_contract_id: ContractId,
         coordinator_leverage: f32,
         stable: bool,
+        auto_rollover: bool,
     ) -> Result<()> {
         let liquidation_price = liquidation_price(trade_params);
         let margin_coordinator = margin_coordinator(trade_params, coordinator_leverage);
@@ -542,6 +603,7 @@ impl TradeExecutor {
             coordinator_leverage,
             trader_margin: margin_trader as i64,
             stable,
+            auto_rollover,
         };
         tracing::debug!(?new_position, "Inserting new position into db");
 
@@ -612,16 +674,17 @@ impl TradeExecutor {
             )
             .await?;
 
-        let protocol_executor = dlc_protocol::DlcProtocolExecutor::new(self.node.pool.clone());
-        protocol_executor.start_dlc_protocol(
-            protocol_id,
-            previous_id,
-            &contract_id,
-            &channel.get_id(),
-            DlcProtocolType::Settle {
-                trade_params: (protocol_id, trade_params).into(),
-            },
-        )?;
+        self.node
+            .start_dlc_protocol(
+                protocol_id,
+                previous_id,
+                contract_id,
+                channel.get_id(),
+                DlcProtocolType::Settle {
+                    trade_params: (protocol_id, trade_params).into(),
+                },
+            )
+            .await?;
 
         db::positions::Position::set_open_position_to_closing(
             conn,
@@ -632,6 +695,408 @@ impl TradeExecutor {
         )
     }
 
+    /// Close part of the position by renewing the DLC channel with a contract for the remaining
+    /// contracts.
+    ///
+    /// The margin of the closed contracts is moved into the collateral reserves, according to the
+    /// PnL of the closed contracts. The position is updated once the trader has accepted the
+    /// `PartialSettle` protocol.
+    pub async fn start_reducing_position(
+        &self,
+        position: &Position,
+        trade_params: &TradeParams,
+        channel_id: DlcChannelId,
+    ) -> Result<()> {
+        if !self.node.inner.is_dlc_channel_confirmed(&channel_id)? {
+            bail!("Underlying DLC channel not yet confirmed");
+        }
+
+        let closing_price = trade_params.average_execution_price();
+        let reduction = position.calculate_reduction(trade_params.quantity, closing_price)?;
+
+        let collateral_reserve_coordinator = self
+            .node
+            .inner
+            .get_dlc_channel_usable_balance(&channel_id)?
+            .to_sat()
+            + reduction.coordinator_settlement_amount;
+        let collateral_reserve_trader = self
+            .node
+            .inner
+            .get_dlc_channel_usable_balance_counterparty(&channel_id)?
+            .to_sat()
+            + reduction.trader_settlement_amount;
+
+        let protocol_id = ProtocolId::new();
+        tracing::info!(
+            %protocol_id,
+            ?position,
+            ?reduction,
+            channel_id = %hex::encode(channel_id),
+            collateral_reserve_coordinator,
+            collateral_reserve_trader,
+            trader_peer_id = %position.trader,
+            "Reducing position by renewing DLC channel",
+        );
+
+        let average_entry_price = Decimal::try_from(position.average_entry_price)?;
+        let contract_descriptor = payout_curve::build_contract_descriptor(
+            average_entry_price,
+            reduction.remaining_coordinator_margin,
+            reduction.remaining_trader_margin,
+            position.coordinator_leverage,
+            position.trader_leverage,
+            position.trader_direction.opposite(),
+            collateral_reserve_coordinator,
+            collateral_reserve_trader,
+            reduction.remaining_quantity,
+            position.contract_symbol,
+        )
+        .context("Could not build contract descriptor")?;
+
+        // The remaining contracts stay bound to the oracle event of the position.
+        let event_id = format!(
+            "{}{}",
+            position.contract_symbol.label(),
+            position.expiry_timestamp.unix_timestamp()
+        );
+
+        let contract_input = ContractInput {
+            offer_collateral: collateral_reserve_coordinator
+                + reduction.remaining_coordinator_margin,
+            accept_collateral: collateral_reserve_trader + reduction.remaining_trader_margin,
+            fee_rate: self.cet_fee_rate()?,
+            contract_infos: vec![ContractInputInfo {
+                contract_descriptor,
+                oracles: OracleInput {
+                    public_keys: vec![to_xonly_pk_29(trade_params.filled_with.oracle_pk)],
+                    event_id,
+                    threshold: 1,
+                },
+            }],
+        };
+
+        let channel = self.node.inner.get_dlc_channel_by_id(&channel_id)?;
+        let previous_id = match channel.get_reference_id() {
+            Some(reference_id) => Some(ProtocolId::try_from(reference_id)?),
+            None => None,
+        };
+
+        let temporary_contract_id = self
+            .node
+            .inner
+            .propose_dlc_channel_update(&channel_id, contract_input, protocol_id.into())
+            .await
+            .context("Could not propose DLC channel update")?;
+
+        self.node
+            .start_dlc_protocol(
+                protocol_id,
+                previous_id,
+                temporary_contract_id,
+                channel.get_id(),
+                DlcProtocolType::PartialSettle {
+                    trade_params: (protocol_id, trade_params).into(),
+                },
+            )
+            .await?;
+
+        let mut conn = self.node.pool.get()?;
+        db::positions::Position::set_open_position_to_resizing(
+            &mut conn,
+            position.trader.to_string(),
+        )
+    }
+
+    /// Add contracts to the position by renewing the DLC channel with a contract for the combined
+    /// position, at the average entry price.
+    ///
+    /// The additional margin and the order-matching fee are taken from the collateral reserves. The
+    /// position is updated once the trader has accepted the `Renew` protocol.
+    pub async fn start_increasing_position(
+        &self,
+        position: &Position,
+        trade_params: &TradeParams,
+        channel_id: DlcChannelId,
+    ) -> Result<()> {
+        if !self.node.inner.is_dlc_channel_confirmed(&channel_id)? {
+            bail!("Underlying DLC channel not yet confirmed");
+        }
+
+        let increase = position.calculate_increase(
+            trade_params.quantity,
+            trade_params.average_execution_price(),
+        )?;
+
+        let additional_margin_coordinator = increase
+            .coordinator_margin
+            .saturating_sub(position.coordinator_margin as u64);
+        let additional_margin_trader = increase
+            .trader_margin
+            .saturating_sub(position.trader_margin as u64);
+
+        let order_matching_fee = order_matching_fee_taker(
+            trade_params.quantity,
+            trade_params.average_execution_price(),
+        )
+        .to_sat();
+
+        let collateral_reserve_coordinator = (self
+            .node
+            .inner
+            .get_dlc_channel_usable_balance(&channel_id)?
+            .to_sat()
+            + order_matching_fee)
+            .checked_sub(additional_margin_coordinator)
+            .with_context(|| {
+                format!(
+                    "Coordinator cannot trade with more than their collateral reserve: \
+                     additional margin ({additional_margin_coordinator}) > reserve + \
+                     order_matching_fee ({order_matching_fee})"
+                )
+            })?;
+        let collateral_reserve_trader = self
+            .node
+            .inner
+            .get_dlc_channel_usable_balance_counterparty(&channel_id)?
+            .to_sat()
+            .checked_sub(order_matching_fee)
+            .and_then(|reserve| reserve.checked_sub(additional_margin_trader))
+            .with_context(|| {
+                format!(
+                    "Trader cannot trade with more than their collateral reserve: \
+                     additional margin ({additional_margin_trader}) + order_matching_fee \
+                     ({order_matching_fee}) > reserve"
+                )
+            })?;
+
+        let protocol_id = ProtocolId::new();
+        tracing::info!(
+            %protocol_id,
+            ?position,
+            ?increase,
+            channel_id = %hex::encode(channel_id),
+            collateral_reserve_coordinator,
+            collateral_reserve_trader,
+            order_matching_fee_sat = %order_matching_fee,
+            trader_peer_id = %position.trader,
+            "Increasing position by renewing DLC channel",
+        );
+
+        let contract_descriptor = payout_curve::build_contract_descriptor(
+            increase.average_entry_price,
+            increase.coordinator_margin,
+            increase.trader_margin,
+            position.coordinator_leverage,
+            position.trader_leverage,
+            position.trader_direction.opposite(),
+            collateral_reserve_coordinator,
+            collateral_reserve_trader,
+            increase.quantity,
+            position.contract_symbol,
+        )
+        .context("Could not build contract descriptor")?;
+
+        // The added contracts are bound to the oracle event of the position.
+        let event_id = format!(
+            "{}{}",
+            position.contract_symbol.label(),
+            position.expiry_timestamp.unix_timestamp()
+        );
+
+        let contract_input = ContractInput {
+            offer_collateral: collateral_reserve_coordinator + increase.coordinator_margin,
+            accept_collateral: collateral_reserve_trader + increase.trader_margin,
+            fee_rate: self.cet_fee_rate()?,
+            contract_infos: vec![ContractInputInfo {
+                contract_descriptor,
+                oracles: OracleInput {
+                    public_keys: vec![to_xonly_pk_29(trade_params.filled_with.oracle_pk)],
+                    event_id,
+                    threshold: 1,
+                },
+            }],
+        };
+
+        let channel = self.node.inner.get_dlc_channel_by_id(&channel_id)?;
+        let previous_id = match channel.get_reference_id() {
+            Some(reference_id) => Some(ProtocolId::try_from(reference_id)?),
+            None => None,
+        };
+
+        let temporary_contract_id = self
+            .node
+            .inner
+            .propose_dlc_channel_update(&channel_id, contract_input, protocol_id.into())
+            .await
+            .context("Could not propose DLC channel update")?;
+
+        self.node
+            .start_dlc_protocol(
+                protocol_id,
+                previous_id,
+                temporary_contract_id,
+                channel.get_id(),
+                DlcProtocolType::Renew {
+                    trade_params: (protocol_id, trade_params).into(),
+                },
+            )
+            .await?;
+
+        let mut conn = self.node.pool.get()?;
+        db::positions::Position::set_open_position_to_resizing(
+            &mut conn,
+            position.trader.to_string(),
+        )
+    }
+
+    /// Close the position and open a position in the opposite direction for the remaining
+    /// contracts of the trade, by renewing the DLC channel with a contract for the new position.
+    ///
+    /// The margin of the closed position is moved into the collateral reserves according to its
+    /// PnL, and the margin of the new position and its order-matching fee are taken from them. The
+    /// positions are updated once the trader has accepted the `Renew` protocol.
+    pub async fn start_flipping_position(
+        &self,
+        position: &Position,
+        trade_params: &TradeParams,
+        channel_id: DlcChannelId,
+    ) -> Result<()> {
+        if !self.node.inner.is_dlc_channel_confirmed(&channel_id)? {
+            bail!("Underlying DLC channel not yet confirmed");
+        }
+
+        let price = trade_params.average_execution_price();
+        let flip = position.calculate_flip(trade_params.quantity, price, trade_params.leverage)?;
+
+        let order_matching_fee = order_matching_fee_taker(flip.quantity, price).to_sat();
+
+        let collateral_reserve_coordinator = (self
+            .node
+            .inner
+            .get_dlc_channel_usable_balance(&channel_id)?
+            .to_sat()
+            + flip.coordinator_settlement_amount
+            + order_matching_fee)
+            .checked_sub(flip.coordinator_margin)
+            .with_context(|| {
+                format!(
+                    "Coordinator cannot trade with more than their collateral reserve: \
+                     margin ({}) > reserve + order_matching_fee ({order_matching_fee})",
+                    flip.coordinator_margin
+                )
+            })?;
+        let collateral_reserve_trader = (self
+            .node
+            .inner
+            .get_dlc_channel_usable_balance_counterparty(&channel_id)?
+            .to_sat()
+            + flip.trader_settlement_amount)
+            .checked_sub(order_matching_fee)
+            .and_then(|reserve| reserve.checked_sub(flip.trader_margin))
+            .with_context(|| {
+                format!(
+                    "Trader cannot trade with more than their collateral reserve: margin ({}) + \
+                     order_matching_fee ({order_matching_fee}) > reserve",
+                    flip.trader_margin
+                )
+            })?;
+
+        let protocol_id = ProtocolId::new();
+        tracing::info!(
+            %protocol_id,
+            ?position,
+            ?flip,
+            channel_id = %hex::encode(channel_id),
+            collateral_reserve_coordinator,
+            collateral_reserve_trader,
+            order_matching_fee_sat = %order_matching_fee,
+            trader_peer_id = %position.trader,
+            "Flipping position by renewing DLC channel",
+        );
+
+        let contract_descriptor = payout_curve::build_contract_descriptor(
+            price,
+            flip.coordinator_margin,
+            flip.trader_margin,
+            position.coordinator_leverage,
+            flip.trader_leverage,
+            flip.trader_direction.opposite(),
+            collateral_reserve_coordinator,
+            collateral_reserve_trader,
+            flip.quantity,
+            position.contract_symbol,
+        )
+        .context("Could not build contract descriptor")?;
+
+        // The new position keeps the oracle event of the closed position.
+        let event_id = format!(
+            "{}{}",
+            position.contract_symbol.label(),
+            position.expiry_timestamp.unix_timestamp()
+        );
+
+        let contract_input = ContractInput {
+            offer_collateral: collateral_reserve_coordinator + flip.coordinator_margin,
+            accept_collateral: collateral_reserve_trader + flip.trader_margin,
+            fee_rate: self.cet_fee_rate()?,
+            contract_infos: vec![ContractInputInfo {
+                contract_descriptor,
+                oracles: OracleInput {
+                    public_keys: vec![to_xonly_pk_29(trade_params.filled_with.oracle_pk)],
+                    event_id,
+                    threshold: 1,
+                },
+            }],
+        };
+
+        let channel = self.node.inner.get_dlc_channel_by_id(&channel_id)?;
+        let previous_id = match channel.get_reference_id() {
+            Some(reference_id) => Some(ProtocolId::try_from(reference_id)?),
+            None => None,
+        };
+
+        let temporary_contract_id = self
+            .node
+            .inner
+            .propose_dlc_channel_update(&channel_id, contract_input, protocol_id.into())
+            .await
+            .context("Could not propose DLC channel update")?;
+
+        self.node
+            .start_dlc_protocol(
+                protocol_id,
+                previous_id,
+                temporary_contract_id,
+                channel.get_id(),
+                DlcProtocolType::Renew {
+                    trade_params: (protocol_id, trade_params).into(),
+                },
+            )
+            .await?;
+
+        let mut conn = self.node.pool.get()?;
+        db::positions::Position::set_open_position_to_resizing(
+            &mut conn,
+            position.trader.to_string(),
+        )
+    }
+
+    /// The fee rate used to construct the CET transactions.
+    fn cet_fee_rate(&self) -> Result<u64> {
+        let sats_per_vbyte = self
+            .node
+            .inner
+            .fee_rate_estimator
+            .get(ConfirmationTarget::Normal)
+            .as_sat_per_vb()
+            .round();
+
+        Decimal::try_from(sats_per_vbyte)?
+            .to_u64()
+            .context("failed to convert to u64")
+    }
+
     fn update_order_and_match(
         &self,
         order_id: Uuid,
@@ -728,18 +1193,46 @@ impl TradeExecutor {
                     compute_relative_contracts(contracts, &trade_params.direction)
                 };
 
-                if position_contracts + trade_contracts == Decimal::ZERO {
+                let contracts_after_trade = position_contracts + trade_contracts;
+                let same_direction = contracts_after_trade.is_sign_negative()
+                    == position_contracts.is_sign_negative();
+                if contracts_after_trade == Decimal::ZERO {
                     TradeAction::ClosePosition {
                         channel_id,
                         position: Box::new(position),
                     }
+                } else if same_direction && contracts_after_trade.abs() < position_contracts.abs() {
+                    TradeAction::ReducePosition {
+                        channel_id,
+                        position: Box::new(position),
+                    }
+                } else if same_direction {
+                    ensure!(
+                        self.node.settings.read().await.allow_opening_positions,
+                        "Increasing positions is disabled"
+                    );
+
+                    ensure!(
+                        trade_params.leverage == position.trader_leverage,
+                        "Cannot add to position with leverage {} using leverage {}",
+                        position.trader_leverage,
+                        trade_params.leverage
+                    );
+
+                    TradeAction::IncreasePosition {
+                        channel_id,
+                        position: Box::new(position),
+                    }
                 } else {
                     ensure!(
                         self.node.settings.read().await.allow_opening_positions,
-                        "Resizing positions is disabled"
+                        "Opening positions is disabled"
                     );
 
-                    bail!("Position resizing not yet possible");
+                    TradeAction::FlipPosition {
+                        channel_id,
+                        position: Box::new(position),
+                    }
                 }
             }
             Some(signed_channel) => {
@@ -772,14 +1265,12 @@ fn margin_coordinator(trade_params: &TradeParams, coordinator_leverage: f32) ->
 
 fn liquidation_price(trade_params: &TradeParams) -> f32 {
     let price = trade_params.average_execution_price();
+
     let leverage = Decimal::try_from(trade_params.leverage).expect("to fit into decimal");
 
-    match trade_params.direction {
-        Direction::Long => calculate_long_liquidation_price(leverage, price),
-        Direction::Short => calculate_short_liquidation_price(leverage, price),
-    }
-    .to_f32()
-    .expect("to fit into f32")
+    calculate_liquidation_price(price, leverage, trade_params.direction)
+        .to_f32()
+        .expect("to fit into f32")
 }
 
 pub fn coordinator_leverage_for_trade(_counterparty_peer_id: &PublicKey) -> Result<f32> {
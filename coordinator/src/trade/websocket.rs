@@ -1,6 +1,7 @@
 use crate::db;
 use crate::position::models::Position;
 use crate::routes::AppState;
+use crate::webhook::PositionEvent;
 use axum::extract::ws::Message as WebsocketMessage;
 use axum::extract::ws::WebSocket;
 use axum::extract::State;
@@ -29,6 +30,8 @@ pub enum InternalPositionUpdateMessage {
         quantity: f32,
         average_entry_price: f32,
     },
+    /// A lifecycle event of a single trader's position.
+    PositionEvent(PositionEvent),
 }
 
 const WEBSOCKET_SEND_TIMEOUT: Duration = Duration::from_secs(5);
@@ -111,6 +114,8 @@ pub async fn websocket_connection(stream: WebSocket, state: Arc<AppState>) {
                                 return;
                             }
                         }
+                        // Only relevant for the position webhook.
+                        InternalPositionUpdateMessage::PositionEvent(_) => {}
                     },
                     Err(RecvError::Closed) => {
                         tracing::error!("position feed sender died! Channel closed.");
@@ -214,13 +219,10 @@ fn calculate_position_stats(
     let average_entry_price = average_entry_price(&positions);
     let total_quantity = positions
         .iter()
-        .map(|pos| {
-            if pos.trader_direction == Direction::Short {
-                pos.quantity
-            } else {
-                // we want to see the quantity as seen from the coordinator
-                pos.quantity * -1.0
-            }
+        // we want to see the quantity as seen from the coordinator
+        .map(|pos| match pos.trader_direction.opposite() {
+            Direction::Long => pos.quantity,
+            Direction::Short => -pos.quantity,
         })
         .sum();
     (average_entry_price, total_quantity)
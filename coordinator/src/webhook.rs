@@ -0,0 +1,233 @@
+use crate::dlc_protocol::ProtocolId;
+use crate::dlc_protocol::TradeParams;
+use crate::node::Node;
+use crate::trade::websocket::InternalPositionUpdateMessage;
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+use bitcoin::secp256k1::PublicKey;
+use futures::future::RemoteHandle;
+use futures::FutureExt;
+use reqwest::header::CONTENT_TYPE;
+use serde::Deserialize;
+use serde::Serialize;
+use std::time::Duration;
+use time::OffsetDateTime;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use trade::Direction;
+use uuid::Uuid;
+
+/// The HTTP header containing the coordinator's signature over the request body.
+///
+/// The signature is created with the coordinator's node key in the same format as Lightning's
+/// `signmessage`, so that the receiver can verify it against the coordinator's node id.
+pub const SIGNATURE_HEADER: &str = "X-10101-Signature";
+
+const MAX_DELIVERY_ATTEMPTS: u32 = 5;
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct PositionWebhookSettings {
+    /// The endpoint to which position events are `POST`ed as JSON.
+    pub url: String,
+    /// The event types which should be delivered. Any other event is dropped.
+    pub events: Vec<PositionEventType>,
+}
+
+impl PositionWebhookSettings {
+    pub fn is_subscribed_to(&self, event_type: PositionEventType) -> bool {
+        self.events.contains(&event_type)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PositionEventType {
+    Opened,
+    /// Contracts were added to the position in the same direction.
+    Increased,
+    Settled,
+    /// Part of the position was closed, the rest remains open.
+    Reduced,
+    RolledOver,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PositionEvent {
+    pub event_type: PositionEventType,
+    pub trader_id: PublicKey,
+    pub protocol_id: Uuid,
+    /// The trade which caused the event. Not set for rollovers.
+    pub trade: Option<PositionEventTrade>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+}
+
+/// The trade as seen from the trader.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PositionEventTrade {
+    pub quantity: f32,
+    pub leverage: f32,
+    pub average_price: f32,
+    pub direction: Direction,
+}
+
+impl PositionEvent {
+    pub fn new(
+        event_type: PositionEventType,
+        trader_id: PublicKey,
+        protocol_id: ProtocolId,
+        trade: Option<PositionEventTrade>,
+    ) -> Self {
+        Self {
+            event_type,
+            trader_id,
+            protocol_id: protocol_id.to_uuid(),
+            trade,
+            timestamp: OffsetDateTime::now_utc(),
+        }
+    }
+}
+
+impl From<&TradeParams> for PositionEventTrade {
+    fn from(trade_params: &TradeParams) -> Self {
+        Self {
+            quantity: trade_params.quantity,
+            leverage: trade_params.leverage,
+            average_price: trade_params.average_price,
+            direction: trade_params.direction,
+        }
+    }
+}
+
+/// Forwards position events from the position feed to the configured webhook.
+///
+/// The webhook settings are read on every event, so that updating the settings at runtime takes
+/// effect immediately.
+pub fn spawn_position_webhook(
+    node: Node,
+    tx_position_feed: broadcast::Sender<InternalPositionUpdateMessage>,
+) -> RemoteHandle<()> {
+    let mut feed = tx_position_feed.subscribe();
+    let (fut, remote_handle) = async move {
+        let client = reqwest::Client::new();
+        loop {
+            match feed.recv().await {
+                Ok(InternalPositionUpdateMessage::PositionEvent(event)) => {
+                    let webhook = match node.settings.read().await.position_webhook.clone() {
+                        Some(webhook) if webhook.is_subscribed_to(event.event_type) => webhook,
+                        _ => continue,
+                    };
+
+                    // Deliver in a separate task so that retries do not hold up the feed.
+                    tokio::spawn({
+                        let client = client.clone();
+                        let node = node.clone();
+                        async move {
+                            if let Err(e) = deliver(&client, &node, &webhook.url, &event).await {
+                                tracing::error!(
+                                    trader_id = %event.trader_id,
+                                    event_type = ?event.event_type,
+                                    "Failed to deliver position event to webhook: {e:#}"
+                                );
+                            }
+                        }
+                    });
+                }
+                Ok(InternalPositionUpdateMessage::NewTrade { .. }) => {}
+                Err(RecvError::Closed) => {
+                    tracing::error!("Position feed sender died! Channel closed.");
+                    break;
+                }
+                Err(RecvError::Lagged(skip)) => tracing::warn!(%skip,
+                    "Lagging behind on position feed."
+                ),
+            }
+        }
+    }
+    .remote_handle();
+
+    tokio::spawn(fut);
+
+    remote_handle
+}
+
+async fn deliver(
+    client: &reqwest::Client,
+    node: &Node,
+    url: &str,
+    event: &PositionEvent,
+) -> Result<()> {
+    let body = serde_json::to_string(event).context("Failed to serialize position event")?;
+    let signature = node
+        .inner
+        .sign_message(body.clone())
+        .context("Failed to sign position event")?;
+
+    let mut retry_delay = INITIAL_RETRY_DELAY;
+    for attempt in 1..=MAX_DELIVERY_ATTEMPTS {
+        let response = client
+            .post(url)
+            .timeout(REQUEST_TIMEOUT)
+            .header(CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, &signature)
+            .body(body.clone())
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+
+        match response {
+            Ok(_) => return Ok(()),
+            Err(e) => {
+                tracing::warn!(%url, attempt, "Position webhook delivery failed: {e:#}");
+            }
+        }
+
+        if attempt < MAX_DELIVERY_ATTEMPTS {
+            tokio::time::sleep(retry_delay).await;
+            retry_delay *= 2;
+        }
+    }
+
+    bail!("Gave up after {MAX_DELIVERY_ATTEMPTS} attempts")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn webhook_only_subscribed_to_configured_events() {
+        let settings = PositionWebhookSettings {
+            url: "http://localhost:8080/webhook".to_string(),
+            events: vec![PositionEventType::Opened, PositionEventType::Settled],
+        };
+
+        assert!(settings.is_subscribed_to(PositionEventType::Opened));
+        assert!(settings.is_subscribed_to(PositionEventType::Settled));
+        assert!(!settings.is_subscribed_to(PositionEventType::RolledOver));
+    }
+
+    #[test]
+    fn rollover_event_serializes_without_trade() {
+        let event = PositionEvent::new(
+            PositionEventType::RolledOver,
+            dummy_trader(),
+            ProtocolId::new(),
+            None,
+        );
+
+        let json = serde_json::to_value(&event).unwrap();
+
+        assert_eq!(json["event_type"], "rolled_over");
+        assert!(json["trade"].is_null());
+    }
+
+    fn dummy_trader() -> PublicKey {
+        PublicKey::from_str("02bd998ebd176715fe92b7467cf6b1df8023950a4dd911db4c94dfc89cc9f5a655")
+            .unwrap()
+    }
+}
@@ -0,0 +1,101 @@
+use anyhow::Result;
+use bitcoin::hashes::sha256;
+use bitcoin::secp256k1::PublicKey;
+use secp256k1::ecdsa::Signature;
+use secp256k1::Message;
+use secp256k1::VerifyOnly;
+use serde::Deserialize;
+use serde::Serialize;
+use std::time::Duration;
+use time::OffsetDateTime;
+
+/// How often the app sends an [`crate::OrderbookRequest::Heartbeat`] while it is connected to the
+/// orderbook.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The shortest timeout a dead man's switch can be set to.
+///
+/// This leaves room for a few missed heartbeats, so that the switch of a trader who is still
+/// around does not fire.
+pub const MIN_DEAD_MANS_SWITCH_TIMEOUT_SECS: u32 = 10 * 60;
+
+/// A request to enable or disable the dead man's switch of a trader.
+///
+/// While the switch is enabled, the coordinator halts the trader once it has not heard from them
+/// within the given timeout: their open orders are cancelled and their position is no longer
+/// rolled over, so that it is closed once it expires. The switch is disabled by default.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DeadMansSwitchRequest {
+    pub trader_id: PublicKey,
+    /// How long the coordinator may go without hearing from the trader before halting them, at
+    /// least [`MIN_DEAD_MANS_SWITCH_TIMEOUT_SECS`]. `None` disables the switch.
+    pub timeout_secs: Option<u32>,
+    /// When the request was created, see [`crate::is_signed_request_valid`].
+    #[serde(with = "time::serde::timestamp")]
+    pub timestamp: OffsetDateTime,
+    /// A signature of [`DeadMansSwitchRequest::message`]
+    pub signature: Signature,
+}
+
+impl DeadMansSwitchRequest {
+    pub fn message(
+        trader_id: PublicKey,
+        timeout_secs: Option<u32>,
+        timestamp: OffsetDateTime,
+    ) -> Message {
+        let timeout = match timeout_secs {
+            Some(timeout_secs) => format!("{timeout_secs}s"),
+            None => "disabled".to_string(),
+        };
+
+        let message = format!(
+            "set dead man's switch of {trader_id} to {timeout} at {}",
+            timestamp.unix_timestamp()
+        );
+
+        Message::from_hashed_data::<sha256::Hash>(message.as_bytes())
+    }
+
+    pub fn verify(&self, secp: &secp256k1::Secp256k1<VerifyOnly>) -> Result<()> {
+        let message = Self::message(self.trader_id, self.timeout_secs, self.timestamp);
+        secp.verify_ecdsa(&message, &self.signature, &self.trader_id)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secp256k1::rand;
+    use secp256k1::Secp256k1;
+    use secp256k1::SecretKey;
+    use secp256k1::SECP256K1;
+
+    #[test]
+    fn signature_covers_timeout() {
+        let secret_key = SecretKey::new(&mut rand::thread_rng());
+        let trader_id = secret_key.public_key(SECP256K1);
+        let timestamp = OffsetDateTime::now_utc();
+
+        let request = DeadMansSwitchRequest {
+            trader_id,
+            timeout_secs: Some(3600),
+            timestamp,
+            signature: secret_key.sign_ecdsa(DeadMansSwitchRequest::message(
+                trader_id,
+                Some(3600),
+                timestamp,
+            )),
+        };
+        request.verify(&Secp256k1::verification_only()).unwrap();
+
+        let tampered_request = DeadMansSwitchRequest {
+            timeout_secs: Some(60),
+            ..request
+        };
+        assert!(tampered_request
+            .verify(&Secp256k1::verification_only())
+            .is_err());
+    }
+}
@@ -5,6 +5,7 @@ use serde::Serialize;
 
 mod backup;
 mod collab_revert;
+mod dead_mans_switch;
 mod liquidity_option;
 mod message;
 mod order;
@@ -18,9 +19,12 @@ mod trade;
 pub use crate::trade::*;
 pub use backup::*;
 pub use collab_revert::*;
+pub use dead_mans_switch::*;
 pub use liquidity_option::*;
 pub use message::*;
 pub use order::*;
+pub use order_matching_fee::maker_fee;
+pub use order_matching_fee::order_matching_fee_maker;
 pub use order_matching_fee::order_matching_fee_taker;
 pub use order_matching_fee::taker_fee;
 pub use polls::*;
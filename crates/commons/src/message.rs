@@ -78,7 +78,16 @@ pub enum OrderbookRequest {
         fcm_token: Option<String>,
         version: Option<String>,
         signature: Signature,
+        /// If set, the limit orders which the trader places while this connection is open are
+        /// cancelled once it closes, unless another connection of theirs also asked for it and is
+        /// still open. This is meant for makers, so that their quotes are not hit while they
+        /// cannot update them.
+        #[serde(default)]
+        cancel_on_disconnect: bool,
     },
+    /// Tells the coordinator that the authenticated trader is still around, e.g. to keep their
+    /// dead man's switch from firing.
+    Heartbeat,
 }
 
 impl TryFrom<OrderbookRequest> for tungstenite::Message {
@@ -22,6 +22,10 @@ pub struct NewOrderRequest {
 }
 
 impl NewOrderRequest {
+    /// Verify the signature of [`NewOrder::message`] by the order's trader.
+    ///
+    /// Signatures by older clients, which did not commit to `stable` and `auto_rollover`, are
+    /// rejected, as those fields could have been altered in transit. Such clients have to upgrade.
     pub fn verify(&self, secp: &secp256k1::Secp256k1<VerifyOnly>) -> Result<()> {
         let message = self.value.message();
         let public_key = self.value.trader_id;
@@ -47,9 +51,19 @@ pub struct NewOrder {
     #[serde(with = "time::serde::timestamp")]
     pub expiry: OffsetDateTime,
     pub stable: bool,
+    /// Whether the resulting position should be rolled over automatically. If not, the position
+    /// is closed once it expires.
+    #[serde(default = "auto_rollover_default")]
+    pub auto_rollover: bool,
+}
+
+fn auto_rollover_default() -> bool {
+    true
 }
 
 impl NewOrder {
+    /// The message the trader signs to authenticate the order. It commits to every field which
+    /// affects the resulting position, so that the order cannot be altered in transit.
     pub fn message(&self) -> Message {
         let mut vec: Vec<u8> = vec![];
         let mut id = self.id.as_bytes().to_vec();
@@ -68,6 +82,8 @@ impl NewOrder {
         let price = price.as_bytes();
         let leverage = format!("{:.2}", self.leverage);
         let leverage = leverage.as_bytes();
+        let stable = [self.stable as u8];
+        let auto_rollover = [self.auto_rollover as u8];
 
         vec.append(&mut id);
         vec.append(&mut seconds);
@@ -77,11 +93,85 @@ impl NewOrder {
         vec.append(&mut quantity.to_vec());
         vec.append(&mut price.to_vec());
         vec.append(&mut leverage.to_vec());
+        vec.append(&mut stable.to_vec());
+        vec.append(&mut auto_rollover.to_vec());
 
         Message::from_hashed_data::<sha256::Hash>(vec.as_slice())
     }
 }
 
+/// A request to cancel all open orders of a trader.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CancelAllOrdersRequest {
+    pub trader_id: PublicKey,
+    /// When the request was created, see [`crate::is_signed_request_valid`].
+    #[serde(with = "time::serde::timestamp")]
+    pub timestamp: OffsetDateTime,
+    /// A signature of [`CancelAllOrdersRequest::message`]
+    pub signature: Signature,
+}
+
+impl CancelAllOrdersRequest {
+    pub fn message(trader_id: PublicKey, timestamp: OffsetDateTime) -> Message {
+        let message = format!(
+            "cancel all orders of {trader_id} at {}",
+            timestamp.unix_timestamp()
+        );
+
+        Message::from_hashed_data::<sha256::Hash>(message.as_bytes())
+    }
+
+    pub fn verify(&self, secp: &secp256k1::Secp256k1<VerifyOnly>) -> Result<()> {
+        let message = Self::message(self.trader_id, self.timestamp);
+        secp.verify_ecdsa(&message, &self.signature, &self.trader_id)?;
+
+        Ok(())
+    }
+}
+
+/// A request to amend the price of an open limit order.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AmendOrderPriceRequest {
+    #[serde(with = "rust_decimal::serde::float")]
+    pub price: Decimal,
+    /// A signature of [`AmendOrderPriceRequest::message`] by the trader who placed the order.
+    pub signature: Signature,
+}
+
+impl AmendOrderPriceRequest {
+    pub fn message(order_id: Uuid, price: Decimal) -> Message {
+        let mut vec: Vec<u8> = vec![];
+        let mut id = order_id.as_bytes().to_vec();
+        let price = format!("{:.2}", price);
+        let price = price.as_bytes();
+
+        vec.append(&mut id);
+        vec.append(&mut price.to_vec());
+
+        Message::from_hashed_data::<sha256::Hash>(vec.as_slice())
+    }
+
+    pub fn verify(
+        &self,
+        secp: &secp256k1::Secp256k1<VerifyOnly>,
+        order_id: Uuid,
+        trader_id: &PublicKey,
+    ) -> Result<()> {
+        let message = Self::message(order_id, self.price);
+        secp.verify_ecdsa(&message, &self.signature, trader_id)?;
+
+        Ok(())
+    }
+}
+
+/// The outcome of cancelling all open orders of a trader.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CancelledOrders {
+    pub cancelled: Vec<Uuid>,
+    /// Orders which were taken before they could be cancelled.
+    pub taken: Vec<Uuid>,
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 pub enum OrderType {
     #[allow(dead_code)]
@@ -134,6 +224,8 @@ pub struct Order {
     pub order_state: OrderState,
     pub order_reason: OrderReason,
     pub stable: bool,
+    #[serde(default = "auto_rollover_default")]
+    pub auto_rollover: bool,
 }
 
 /// Extra information required to open a DLC channel, independent of the [`TradeParams`] associated
@@ -150,10 +242,14 @@ pub struct ChannelOpeningParams {
 
 #[cfg(test)]
 pub mod tests {
+    use crate::AmendOrderPriceRequest;
+    use crate::CancelAllOrdersRequest;
     use crate::NewOrder;
     use crate::NewOrderRequest;
     use crate::OrderType;
+    use bitcoin::hashes::sha256;
     use secp256k1::rand;
+    use secp256k1::Message;
     use secp256k1::Secp256k1;
     use secp256k1::SecretKey;
     use secp256k1::SECP256K1;
@@ -180,6 +276,7 @@ pub mod tests {
             order_type: OrderType::Market,
             expiry: OffsetDateTime::now_utc(),
             stable: false,
+            auto_rollover: true,
         };
 
         let message = order.message();
@@ -208,6 +305,7 @@ pub mod tests {
             // Note: the last 5 is too much as it does not get serialized
             expiry: OffsetDateTime::UNIX_EPOCH + 1.1010101015.seconds(),
             stable: false,
+            auto_rollover: true,
         };
 
         let message = original_order.clone().message();
@@ -223,7 +321,7 @@ pub mod tests {
 
         let original_serialized_request = serde_json::to_string(&original_request).unwrap();
 
-        let serialized_msg = "{\"value\":{\"id\":\"67e55044-10b1-426f-9247-bb680e5fe0c8\",\"contract_symbol\":\"BtcUsd\",\"price\":53000.0,\"quantity\":2000.0,\"trader_id\":\"0218845781f631c48f1c9709e23092067d06837f30aa0cd0544ac887fe91ddd166\",\"direction\":\"Long\",\"leverage\":2.0,\"order_type\":\"Market\",\"expiry\":1,\"stable\":false},\"signature\":\"SIGNATURE_PLACEHOLDER\",\"channel_opening_params\":null}";
+        let serialized_msg = "{\"value\":{\"id\":\"67e55044-10b1-426f-9247-bb680e5fe0c8\",\"contract_symbol\":\"BtcUsd\",\"price\":53000.0,\"quantity\":2000.0,\"trader_id\":\"0218845781f631c48f1c9709e23092067d06837f30aa0cd0544ac887fe91ddd166\",\"direction\":\"Long\",\"leverage\":2.0,\"order_type\":\"Market\",\"expiry\":1,\"stable\":false,\"auto_rollover\":true},\"signature\":\"SIGNATURE_PLACEHOLDER\",\"channel_opening_params\":null}";
 
         // replace the signature with the one from above to have the same string
         let serialized_msg =
@@ -249,4 +347,144 @@ pub mod tests {
         let secp = Secp256k1::verification_only();
         parsed_request.verify(&secp).unwrap();
     }
+
+    #[test]
+    pub fn tampered_new_order_request_is_rejected() {
+        let secp = Secp256k1::verification_only();
+        let secret_key = SecretKey::new(&mut rand::thread_rng());
+        let trader_id = secret_key.public_key(SECP256K1);
+
+        let order = NewOrder {
+            id: Uuid::new_v4(),
+            contract_symbol: ContractSymbol::BtcUsd,
+            price: rust_decimal_macros::dec!(53_000),
+            quantity: rust_decimal_macros::dec!(2000),
+            trader_id,
+            direction: Direction::Long,
+            leverage: rust_decimal_macros::dec!(2.0),
+            order_type: OrderType::Market,
+            expiry: OffsetDateTime::now_utc(),
+            stable: false,
+            auto_rollover: true,
+        };
+        let request = NewOrderRequest {
+            signature: secret_key.sign_ecdsa(order.message()),
+            value: order,
+            channel_opening_params: None,
+        };
+        request.verify(&secp).unwrap();
+
+        let mut tampered_request = request.clone();
+        tampered_request.value.quantity = rust_decimal_macros::dec!(20_000);
+        assert!(tampered_request.verify(&secp).is_err());
+
+        let mut tampered_request = request.clone();
+        tampered_request.value.stable = true;
+        assert!(tampered_request.verify(&secp).is_err());
+
+        let other_secret_key = SecretKey::new(&mut rand::thread_rng());
+        let mut impersonating_request = request;
+        impersonating_request.value.trader_id = other_secret_key.public_key(SECP256K1);
+        assert!(impersonating_request.verify(&secp).is_err());
+
+        let mut tampered_signature_request = impersonating_request;
+        tampered_signature_request.signature =
+            other_secret_key.sign_ecdsa(tampered_signature_request.value.message());
+        tampered_signature_request.value.trader_id = trader_id;
+        assert!(tampered_signature_request.verify(&secp).is_err());
+    }
+
+    #[test]
+    pub fn legacy_signed_new_order_request_is_rejected() {
+        let secp = Secp256k1::verification_only();
+        let secret_key = SecretKey::new(&mut rand::thread_rng());
+        let trader_id = secret_key.public_key(SECP256K1);
+
+        let order = NewOrder {
+            id: Uuid::new_v4(),
+            contract_symbol: ContractSymbol::BtcUsd,
+            price: rust_decimal_macros::dec!(53_000),
+            quantity: rust_decimal_macros::dec!(2000),
+            trader_id,
+            direction: Direction::Long,
+            leverage: rust_decimal_macros::dec!(2.0),
+            order_type: OrderType::Market,
+            expiry: OffsetDateTime::now_utc(),
+            stable: false,
+            auto_rollover: true,
+        };
+
+        // The message signed by older clients, which does not commit to `stable` and
+        // `auto_rollover`.
+        let mut legacy_preimage = vec![];
+        legacy_preimage.extend_from_slice(order.id.as_bytes());
+        legacy_preimage.extend_from_slice(&order.expiry.unix_timestamp().to_le_bytes());
+        legacy_preimage.extend_from_slice(order.contract_symbol.label().as_bytes());
+        legacy_preimage.extend_from_slice(order.order_type.label().as_bytes());
+        legacy_preimage.extend_from_slice(order.direction.to_string().as_bytes());
+        legacy_preimage.extend_from_slice(format!("{:.2}", order.quantity).as_bytes());
+        legacy_preimage.extend_from_slice(format!("{:.2}", order.price).as_bytes());
+        legacy_preimage.extend_from_slice(format!("{:.2}", order.leverage).as_bytes());
+        let legacy_message = Message::from_hashed_data::<sha256::Hash>(&legacy_preimage);
+
+        let mut request = NewOrderRequest {
+            signature: secret_key.sign_ecdsa(legacy_message),
+            value: order,
+            channel_opening_params: None,
+        };
+        request.value.stable = true;
+
+        assert!(request.verify(&secp).is_err());
+    }
+
+    #[test]
+    pub fn cancel_all_orders_request_must_be_signed_by_trader() {
+        let secret_key = SecretKey::new(&mut rand::thread_rng());
+        let trader_id = secret_key.public_key(SECP256K1);
+        let timestamp = OffsetDateTime::now_utc();
+
+        let request = CancelAllOrdersRequest {
+            trader_id,
+            timestamp,
+            signature: secret_key.sign_ecdsa(CancelAllOrdersRequest::message(trader_id, timestamp)),
+        };
+        request.verify(&Secp256k1::verification_only()).unwrap();
+
+        let other_trader_id = SecretKey::new(&mut rand::thread_rng()).public_key(SECP256K1);
+        let forged_request = CancelAllOrdersRequest {
+            trader_id: other_trader_id,
+            ..request
+        };
+        assert!(forged_request
+            .verify(&Secp256k1::verification_only())
+            .is_err());
+    }
+
+    #[test]
+    pub fn amend_order_price_request_must_be_signed_by_trader() {
+        let secret_key = SecretKey::new(&mut rand::thread_rng());
+        let trader_id = secret_key.public_key(SECP256K1);
+        let order_id = Uuid::new_v4();
+        let price = rust_decimal_macros::dec!(53_000);
+
+        let request = AmendOrderPriceRequest {
+            price,
+            signature: secret_key.sign_ecdsa(AmendOrderPriceRequest::message(order_id, price)),
+        };
+        let secp = Secp256k1::verification_only();
+        request.verify(&secp, order_id, &trader_id).unwrap();
+
+        let other_trader_id = SecretKey::new(&mut rand::thread_rng()).public_key(SECP256K1);
+        assert!(request.verify(&secp, order_id, &other_trader_id).is_err());
+
+        assert!(request.verify(&secp, Uuid::new_v4(), &trader_id).is_err());
+
+        let tampered_request = AmendOrderPriceRequest {
+            price: rust_decimal_macros::dec!(52_000),
+            ..request
+        };
+        assert!(tampered_request
+            .verify(&secp, order_id, &trader_id)
+            .is_err());
+    }
 }
@@ -3,17 +3,31 @@ use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use rust_decimal::RoundingStrategy;
 
-/// The order-matching fee per cent for the taker.
+// The order-matching fees are given in basis points, i.e. as `(bps, 4)` mantissa and scale of a
+// `Decimal`: 30 basis points are `0.0030`, i.e. 0.30% of the notional value of the trade.
+
+/// The order-matching fee for the taker: 30 basis points, i.e. 0.30%.
 const TAKER_FEE: (i64, u32) = (30, 4);
 
+/// The order-matching fee for the maker: 10 basis points, i.e. 0.10%.
+const MAKER_FEE: (i64, u32) = (10, 4);
+
 pub fn order_matching_fee_taker(quantity: f32, price: Decimal) -> bitcoin::Amount {
-    order_matching_fee(quantity, price, Decimal::new(TAKER_FEE.0, TAKER_FEE.1))
+    order_matching_fee(quantity, price, taker_fee())
+}
+
+pub fn order_matching_fee_maker(quantity: f32, price: Decimal) -> bitcoin::Amount {
+    order_matching_fee(quantity, price, maker_fee())
 }
 
 pub fn taker_fee() -> Decimal {
     Decimal::new(TAKER_FEE.0, TAKER_FEE.1)
 }
 
+pub fn maker_fee() -> Decimal {
+    Decimal::new(MAKER_FEE.0, MAKER_FEE.1)
+}
+
 fn order_matching_fee(quantity: f32, price: Decimal, fee_per_cent: Decimal) -> bitcoin::Amount {
     let quantity = Decimal::from_f32(quantity).expect("quantity to fit in Decimal");
 
@@ -51,4 +65,30 @@ mod tests {
 
         assert_eq!(fee.to_sat(), 0);
     }
+
+    #[test]
+    fn calculate_maker_and_taker_fee_for_the_same_trade() {
+        let price = Decimal::new(30209, 0);
+
+        let maker_fee = order_matching_fee_maker(50.0, price);
+        let taker_fee = order_matching_fee_taker(50.0, price);
+
+        assert_eq!(maker_fee.to_sat(), 166);
+        assert_eq!(taker_fee.to_sat(), 497);
+    }
+
+    #[test]
+    fn maker_fee_is_lower_than_taker_fee_for_identical_trades() {
+        let price = Decimal::new(65_000, 0);
+
+        for quantity in [1.0, 100.0, 2_500.0, 100_000.0] {
+            let maker_fee = order_matching_fee_maker(quantity, price);
+            let taker_fee = order_matching_fee_taker(quantity, price);
+
+            assert!(
+                maker_fee < taker_fee,
+                "maker fee {maker_fee} not lower than taker fee {taker_fee} for {quantity} contracts"
+            );
+        }
+    }
 }
@@ -29,7 +29,9 @@ pub fn best_current_price(current_orders: &[Order]) -> Prices {
             },
         );
     };
-    add_price_for_symbol(ContractSymbol::BtcUsd);
+    for symbol in ContractSymbol::ALL {
+        add_price_for_symbol(symbol);
+    }
     prices
 }
 
@@ -48,15 +50,14 @@ fn best_price_for(
     direction: Direction,
     symbol: ContractSymbol,
 ) -> Option<Decimal> {
-    assert_eq!(
-        symbol,
-        ContractSymbol::BtcUsd,
-        "only btcusd supported for now"
-    );
     let use_max = direction == Direction::Long;
     current_orders
         .iter()
-        .filter(|order| order.order_state == OrderState::Open && order.direction == direction)
+        .filter(|order| {
+            order.order_state == OrderState::Open
+                && order.direction == direction
+                && order.contract_symbol == symbol
+        })
         .map(|order| order.price.to_f64().expect("to represent decimal as f64"))
         // get the best price
         .fold(None, |acc, x| match acc {
@@ -84,6 +85,7 @@ mod test {
     use trade::Direction;
     use uuid::Uuid;
     use ContractSymbol::BtcUsd;
+    use ContractSymbol::EthUsd;
 
     fn dummy_public_key() -> PublicKey {
         PublicKey::from_str("02bd998ebd176715fe92b7467cf6b1df8023950a4dd911db4c94dfc89cc9f5a655")
@@ -91,13 +93,22 @@ mod test {
     }
 
     fn dummy_order(price: Decimal, direction: Direction, order_state: OrderState) -> Order {
+        dummy_order_for_symbol(BtcUsd, price, direction, order_state)
+    }
+
+    fn dummy_order_for_symbol(
+        contract_symbol: ContractSymbol,
+        price: Decimal,
+        direction: Direction,
+        order_state: OrderState,
+    ) -> Order {
         Order {
             id: Uuid::new_v4(),
             price,
             trader_id: dummy_public_key(),
             direction,
             leverage: 1.0,
-            contract_symbol: BtcUsd,
+            contract_symbol,
             quantity: 100.into(),
             order_type: OrderType::Market,
             timestamp: OffsetDateTime::now_utc(),
@@ -105,6 +116,7 @@ mod test {
             order_state,
             order_reason: OrderReason::Manual,
             stable: false,
+            auto_rollover: true,
         }
     }
 
@@ -142,4 +154,18 @@ mod test {
         assert_eq!(best_ask_price(&all_orders_taken, BtcUsd), None);
         assert_eq!(best_bid_price(&all_orders_taken, BtcUsd), None);
     }
+
+    #[test]
+    fn test_best_price_per_symbol() {
+        let current_orders = vec![
+            dummy_order_for_symbol(BtcUsd, dec!(30_000), Direction::Long, OrderState::Open),
+            dummy_order_for_symbol(EthUsd, dec!(2_000), Direction::Long, OrderState::Open),
+            dummy_order_for_symbol(EthUsd, dec!(2_100), Direction::Short, OrderState::Open),
+        ];
+
+        assert_eq!(best_bid_price(&current_orders, BtcUsd), Some(dec!(30_000)));
+        assert_eq!(best_ask_price(&current_orders, BtcUsd), None);
+        assert_eq!(best_bid_price(&current_orders, EthUsd), Some(dec!(2_000)));
+        assert_eq!(best_ask_price(&current_orders, EthUsd), Some(dec!(2_100)));
+    }
 }
@@ -5,6 +5,14 @@ use serde::Serialize;
 use sha2::digest::FixedOutput;
 use sha2::Digest;
 use sha2::Sha256;
+use time::Duration;
+use time::OffsetDateTime;
+
+/// How long a signed request carrying its creation time is valid for.
+///
+/// Such requests act on behalf of a trader, e.g. to cancel all of their orders. The coordinator
+/// rejects them once they are older than this, so that they cannot be replayed later on.
+pub const SIGNED_REQUEST_VALIDITY: Duration = Duration::minutes(1);
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Signature {
@@ -20,12 +28,24 @@ pub fn create_sign_message(message: Vec<u8>) -> SecpMessage {
     msg
 }
 
+/// Whether a signed request created at `timestamp` is still valid at `now`.
+///
+/// Clocks can be off in either direction, hence a request from the future is accepted within the
+/// same window.
+pub fn is_signed_request_valid(timestamp: OffsetDateTime, now: OffsetDateTime) -> bool {
+    (now - timestamp).abs() <= SIGNED_REQUEST_VALIDITY
+}
+
 #[cfg(test)]
 mod test {
+    use crate::signature::is_signed_request_valid;
     use crate::signature::Signature;
+    use crate::signature::SIGNED_REQUEST_VALIDITY;
     use bitcoin::secp256k1::PublicKey;
     use bitcoin::secp256k1::SecretKey;
     use std::str::FromStr;
+    use time::Duration;
+    use time::OffsetDateTime;
 
     fn dummy_public_key() -> PublicKey {
         PublicKey::from_str("02bd998ebd176715fe92b7467cf6b1df8023950a4dd911db4c94dfc89cc9f5a655")
@@ -64,4 +84,17 @@ mod test {
 
         assert_eq!(serialized, signature);
     }
+
+    #[test]
+    fn signed_request_expires_in_either_direction() {
+        let now = OffsetDateTime::now_utc();
+
+        assert!(is_signed_request_valid(now, now));
+        assert!(is_signed_request_valid(now - SIGNED_REQUEST_VALIDITY, now));
+        assert!(is_signed_request_valid(now + SIGNED_REQUEST_VALIDITY, now));
+
+        let just_too_long = SIGNED_REQUEST_VALIDITY + Duration::seconds(1);
+        assert!(!is_signed_request_valid(now - just_too_long, now));
+        assert!(!is_signed_request_valid(now + just_too_long, now));
+    }
 }
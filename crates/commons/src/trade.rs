@@ -4,6 +4,8 @@ use bitcoin::Amount;
 use rust_decimal::Decimal;
 use serde::Deserialize;
 use serde::Serialize;
+use std::ops::RangeInclusive;
+use thiserror::Error;
 use time::OffsetDateTime;
 use trade::ContractSymbol;
 use trade::Direction;
@@ -60,6 +62,93 @@ impl TradeParams {
     pub fn average_execution_price(&self) -> Decimal {
         self.filled_with.average_execution_price()
     }
+
+    /// Sanity check the trade params before acting on them.
+    ///
+    /// The trade params are deserialized from messages of the orderbook, so we should not blindly
+    /// trust them. Most notably, a malformed match would otherwise only surface later on as a
+    /// panic when calculating the average execution price or the margin.
+    ///
+    /// The leverage is only checked against the allowed range by
+    /// [`TradeParams::validate_leverage`], since trades reducing an existing position have to be
+    /// accepted at the position's leverage.
+    pub fn validate(&self) -> Result<(), TradeParamsError> {
+        if self.quantity.is_nan() || self.quantity <= 0.0 {
+            return Err(TradeParamsError::NonPositiveQuantity(self.quantity));
+        }
+
+        if self.leverage.is_nan() || self.leverage <= 0.0 {
+            return Err(TradeParamsError::NonPositiveLeverage(self.leverage));
+        }
+
+        if self.filled_with.matches.is_empty() {
+            return Err(TradeParamsError::NoMatches);
+        }
+
+        if let Some(execution_price) = self
+            .filled_with
+            .matches
+            .iter()
+            .map(|m| m.execution_price)
+            .find(|execution_price| *execution_price <= Decimal::ZERO)
+        {
+            return Err(TradeParamsError::NonPositiveExecutionPrice(execution_price));
+        }
+
+        if self.filled_with.expiry_timestamp <= OffsetDateTime::UNIX_EPOCH {
+            return Err(TradeParamsError::InvalidExpiry(
+                self.filled_with.expiry_timestamp,
+            ));
+        }
+
+        if self
+            .filled_with
+            .matches
+            .iter()
+            .any(|m| m.pubkey == self.pubkey)
+        {
+            return Err(TradeParamsError::MatchedWithSelf(self.pubkey));
+        }
+
+        Ok(())
+    }
+
+    /// Check that the leverage is within the allowed range, for trades which open or add to a
+    /// position.
+    pub fn validate_leverage(
+        &self,
+        leverage_range: RangeInclusive<f32>,
+    ) -> Result<(), TradeParamsError> {
+        if !leverage_range.contains(&self.leverage) {
+            return Err(TradeParamsError::LeverageOutOfRange {
+                leverage: self.leverage,
+                min: *leverage_range.start(),
+                max: *leverage_range.end(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// The reasons why [`TradeParams`] can be rejected by [`TradeParams::validate`] or
+/// [`TradeParams::validate_leverage`].
+#[derive(Debug, Clone, Error, PartialEq)]
+pub enum TradeParamsError {
+    #[error("Quantity must be positive, got {0}")]
+    NonPositiveQuantity(f32),
+    #[error("Leverage must be positive, got {0}")]
+    NonPositiveLeverage(f32),
+    #[error("Leverage {leverage} is outside of the allowed range [{min}, {max}]")]
+    LeverageOutOfRange { leverage: f32, min: f32, max: f32 },
+    #[error("Trade params do not contain any matches")]
+    NoMatches,
+    #[error("Execution price must be positive, got {0}")]
+    NonPositiveExecutionPrice(Decimal),
+    #[error("Invalid contract expiry {0}")]
+    InvalidExpiry(OffsetDateTime),
+    #[error("Trader {0} cannot be matched with themselves")]
+    MatchedWithSelf(PublicKey),
 }
 
 /// A match for an order
@@ -156,15 +245,26 @@ impl FilledWith {
 /// `total_order_quantity / (quantity_trade_0 / execution_price_trade_0 + quantity_trade_1 /
 /// execution_price_trade_1 )`
 pub fn average_execution_price(matches: Vec<Match>) -> Decimal {
-    if matches.len() == 1 {
-        return matches.first().expect("to be exactly one").execution_price;
+    let fills = matches
+        .iter()
+        .map(|m| (m.quantity, m.execution_price))
+        .collect::<Vec<_>>();
+
+    average_price(&fills)
+}
+
+/// Calculates the average price of inverse contracts traded at different prices, given as
+/// `(quantity, price)` pairs, following the same formula as [`average_execution_price`].
+pub fn average_price(fills: &[(Decimal, Decimal)]) -> Decimal {
+    if let [(_, price)] = fills {
+        return *price;
     }
-    let sum_quantity = matches
+    let sum_quantity = fills
         .iter()
-        .fold(Decimal::ZERO, |acc, m| acc + m.quantity);
+        .fold(Decimal::ZERO, |acc, (quantity, _)| acc + quantity);
 
-    let nominal_prices: Decimal = matches.iter().fold(Decimal::ZERO, |acc, m| {
-        acc + (m.quantity / m.execution_price)
+    let nominal_prices: Decimal = fills.iter().fold(Decimal::ZERO, |acc, (quantity, price)| {
+        acc + (quantity / price)
     });
 
     sum_quantity / nominal_prices
@@ -195,14 +295,20 @@ mod test {
         PublicKey::from_str("02bd998ebd176715fe92b7467cf6b1df8023950a4dd911db4c94dfc89cc9f5a655")
             .unwrap()
     }
+    use crate::trade::average_price;
 
     use crate::trade::FilledWith;
     use crate::trade::Match;
+    use crate::trade::TradeParams;
+    use crate::trade::TradeParamsError;
     use bitcoin::secp256k1::PublicKey;
     use bitcoin::secp256k1::XOnlyPublicKey;
     use rust_decimal_macros::dec;
     use std::str::FromStr;
+    use time::ext::NumericalDuration;
     use time::OffsetDateTime;
+    use trade::ContractSymbol;
+    use trade::Direction;
     use uuid::Uuid;
 
     #[test]
@@ -240,4 +346,125 @@ mod test {
 
         assert_eq!(average_execution_price.round_dp(2), dec!(11250.00));
     }
+
+    #[test]
+    fn average_price_of_two_fills() {
+        let price = average_price(&[(dec!(100), dec!(40_000)), (dec!(100), dec!(60_000))]);
+
+        assert_eq!(price.round_dp(2), dec!(48_000.00));
+    }
+
+    #[test]
+    fn valid_trade_params() {
+        let trade_params = dummy_trade_params();
+
+        assert_eq!(trade_params.validate(), Ok(()));
+    }
+
+    #[test]
+    fn reject_non_positive_quantity() {
+        let trade_params = TradeParams {
+            quantity: 0.0,
+            ..dummy_trade_params()
+        };
+
+        assert_eq!(
+            trade_params.validate(),
+            Err(TradeParamsError::NonPositiveQuantity(0.0))
+        );
+    }
+
+    #[test]
+    fn reject_leverage_out_of_range() {
+        let trade_params = TradeParams {
+            leverage: 10.0,
+            ..dummy_trade_params()
+        };
+
+        assert_eq!(trade_params.validate(), Ok(()));
+        assert_eq!(
+            trade_params.validate_leverage(1.0..=5.0),
+            Err(TradeParamsError::LeverageOutOfRange {
+                leverage: 10.0,
+                min: 1.0,
+                max: 5.0
+            })
+        );
+    }
+
+    #[test]
+    fn reject_non_positive_leverage() {
+        let trade_params = TradeParams {
+            leverage: 0.0,
+            ..dummy_trade_params()
+        };
+
+        assert_eq!(
+            trade_params.validate(),
+            Err(TradeParamsError::NonPositiveLeverage(0.0))
+        );
+    }
+
+    #[test]
+    fn reject_non_positive_execution_price() {
+        let mut trade_params = dummy_trade_params();
+        trade_params.filled_with.matches[0].execution_price = dec!(0);
+
+        assert_eq!(
+            trade_params.validate(),
+            Err(TradeParamsError::NonPositiveExecutionPrice(dec!(0)))
+        );
+    }
+
+    #[test]
+    fn reject_zero_expiry() {
+        let mut trade_params = dummy_trade_params();
+        trade_params.filled_with.expiry_timestamp = OffsetDateTime::UNIX_EPOCH;
+
+        assert_eq!(
+            trade_params.validate(),
+            Err(TradeParamsError::InvalidExpiry(OffsetDateTime::UNIX_EPOCH))
+        );
+    }
+
+    #[test]
+    fn reject_match_with_self() {
+        let mut trade_params = dummy_trade_params();
+        trade_params.filled_with.matches[0].pubkey = trade_params.pubkey;
+
+        assert_eq!(
+            trade_params.validate(),
+            Err(TradeParamsError::MatchedWithSelf(trade_params.pubkey))
+        );
+    }
+
+    fn dummy_trade_params() -> TradeParams {
+        let counterparty = PublicKey::from_str(
+            "0218845781f631c48f1c9709e23092067d06837f30aa0cd0544ac887fe91ddd166",
+        )
+        .unwrap();
+
+        TradeParams {
+            pubkey: dummy_public_key(),
+            contract_symbol: ContractSymbol::BtcUsd,
+            leverage: 2.0,
+            quantity: 100.0,
+            direction: Direction::Long,
+            filled_with: FilledWith {
+                order_id: Uuid::new_v4(),
+                expiry_timestamp: OffsetDateTime::now_utc() + 7.days(),
+                oracle_pk: XOnlyPublicKey::from_str(
+                    "16f88cf7d21e6c0f46bcbc983a4e3b19726c6c98858cc31c83551a88fde171c0",
+                )
+                .unwrap(),
+                matches: vec![Match {
+                    id: Uuid::new_v4(),
+                    order_id: Uuid::new_v4(),
+                    quantity: dec!(100),
+                    pubkey: counterparty,
+                    execution_price: dec!(50_000),
+                }],
+            },
+        }
+    }
 }
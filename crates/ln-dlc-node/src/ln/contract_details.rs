@@ -1,7 +1,9 @@
+use dlc_manager::contract::offered_contract::OfferedContract;
 use dlc_manager::contract::Contract;
 use dlc_manager::ContractId;
 use serde::Serialize;
 use serde::Serializer;
+use time::OffsetDateTime;
 
 #[derive(Serialize, Debug)]
 pub struct ContractDetails {
@@ -14,6 +16,13 @@ pub struct ContractDetails {
     pub accepted_funding_sats: Option<u64>,
     pub fee_rate_per_vb: Option<u64>,
     pub event_id: Option<String>,
+    /// The time at which the oracle attests to the outcome of the contract.
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub maturity: Option<OffsetDateTime>,
+    /// The collateral we put into the contract.
+    pub local_collateral_sats: Option<u64>,
+    /// The collateral our counterparty put into the contract.
+    pub counter_collateral_sats: Option<u64>,
 }
 
 #[derive(Serialize, Debug)]
@@ -170,6 +179,26 @@ impl From<Contract> for ContractDetails {
             ),
         };
 
+        let offered_contract = offered_contract(&contract);
+
+        let maturity = offered_contract
+            .and_then(|offered_contract| offered_contract.contract_info.first())
+            .and_then(|contract_info| contract_info.oracle_announcements.first())
+            .and_then(|oracle_announcement| {
+                OffsetDateTime::from_unix_timestamp(
+                    oracle_announcement.oracle_event.event_maturity_epoch as i64,
+                )
+                .ok()
+            });
+
+        let (local_collateral_sats, counter_collateral_sats) = match offered_contract {
+            Some(offered_contract) if offered_contract.is_offer_party => {
+                (offered_funding_sats, accepted_funding_sats)
+            }
+            Some(_) => (accepted_funding_sats, offered_funding_sats),
+            None => (None, None),
+        };
+
         ContractDetails {
             contract_id: contract.get_id(),
             temporary_contract_id: contract.get_temporary_id(),
@@ -178,7 +207,38 @@ impl From<Contract> for ContractDetails {
             accepted_funding_sats,
             fee_rate_per_vb,
             event_id: event_id.flatten(),
+            maturity,
+            local_collateral_sats,
+            counter_collateral_sats,
+        }
+    }
+}
+
+/// The original offer of a contract, if it is still known in the contract's current state.
+fn offered_contract(contract: &Contract) -> Option<&OfferedContract> {
+    match contract {
+        Contract::Offered(offered_contract) | Contract::Rejected(offered_contract) => {
+            Some(offered_contract)
+        }
+        Contract::Accepted(accepted_contract) => Some(&accepted_contract.offered_contract),
+        Contract::Signed(signed_contract)
+        | Contract::Confirmed(signed_contract)
+        | Contract::Refunded(signed_contract) => {
+            Some(&signed_contract.accepted_contract.offered_contract)
         }
+        Contract::PreClosed(pre_closed_contract) => Some(
+            &pre_closed_contract
+                .signed_contract
+                .accepted_contract
+                .offered_contract,
+        ),
+        Contract::FailedAccept(failed_accept_contract) => {
+            Some(&failed_accept_contract.offered_contract)
+        }
+        Contract::FailedSign(failed_sign_contract) => {
+            Some(&failed_sign_contract.accepted_contract.offered_contract)
+        }
+        Contract::Closed(_) => None,
     }
 }
 
@@ -188,3 +248,148 @@ where
 {
     s.serialize_str(&hex::encode(contract_id))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin_old::secp256k1::ecdsa::Signature;
+    use bitcoin_old::secp256k1::schnorr;
+    use bitcoin_old::secp256k1::PublicKey;
+    use bitcoin_old::PackedLockTime;
+    use bitcoin_old::Script;
+    use bitcoin_old::Transaction;
+    use dlc::DlcTransactions;
+    use dlc::PartyParams;
+    use dlc_manager::contract::accepted_contract::AcceptedContract;
+    use dlc_manager::contract::contract_info::ContractInfo;
+    use dlc_manager::contract::enum_descriptor::EnumDescriptor;
+    use dlc_manager::contract::ContractDescriptor;
+    use dlc_messages::oracle_msgs::EnumEventDescriptor;
+    use dlc_messages::oracle_msgs::EventDescriptor;
+    use dlc_messages::oracle_msgs::OracleAnnouncement;
+    use dlc_messages::oracle_msgs::OracleEvent;
+    use std::str::FromStr;
+
+    const MATURITY: u32 = 1_700_000_000;
+
+    #[test]
+    fn collateral_is_split_from_our_point_of_view() {
+        let details = ContractDetails::from(Contract::Accepted(dummy_accepted_contract(true)));
+
+        assert_eq!(
+            details.maturity,
+            Some(OffsetDateTime::from_unix_timestamp(MATURITY as i64).unwrap())
+        );
+        assert_eq!(details.local_collateral_sats, Some(200));
+        assert_eq!(details.counter_collateral_sats, Some(100));
+
+        let details = ContractDetails::from(Contract::Accepted(dummy_accepted_contract(false)));
+
+        assert_eq!(details.local_collateral_sats, Some(100));
+        assert_eq!(details.counter_collateral_sats, Some(200));
+    }
+
+    #[test]
+    fn offered_contract_has_no_counter_collateral_yet() {
+        let details = ContractDetails::from(Contract::Offered(dummy_offered_contract(true)));
+
+        assert_eq!(
+            details.maturity,
+            Some(OffsetDateTime::from_unix_timestamp(MATURITY as i64).unwrap())
+        );
+        assert_eq!(details.local_collateral_sats, Some(200));
+        assert_eq!(details.counter_collateral_sats, None);
+    }
+
+    fn dummy_accepted_contract(is_offer_party: bool) -> AcceptedContract {
+        AcceptedContract {
+            offered_contract: dummy_offered_contract(is_offer_party),
+            accept_params: dummy_params(100),
+            funding_inputs: vec![],
+            adaptor_infos: vec![],
+            adaptor_signatures: None,
+            dlc_transactions: DlcTransactions {
+                fund: dummy_tx(),
+                cets: vec![],
+                refund: dummy_tx(),
+                funding_script_pubkey: Script::new(),
+            },
+            accept_refund_signature: dummy_signature(),
+        }
+    }
+
+    /// An offer with 200 sats of collateral, of which the counterparty is expected to match 100.
+    fn dummy_offered_contract(is_offer_party: bool) -> OfferedContract {
+        OfferedContract {
+            id: [1; 32],
+            is_offer_party,
+            contract_info: vec![ContractInfo {
+                contract_descriptor: ContractDescriptor::Enum(EnumDescriptor {
+                    outcome_payouts: vec![],
+                }),
+                oracle_announcements: vec![OracleAnnouncement {
+                    announcement_signature: dummy_schnorr_signature(),
+                    oracle_public_key: dummy_pubkey().x_only_public_key().0,
+                    oracle_event: OracleEvent {
+                        oracle_nonces: vec![],
+                        event_maturity_epoch: MATURITY,
+                        event_descriptor: EventDescriptor::EnumEvent(EnumEventDescriptor {
+                            outcomes: vec![],
+                        }),
+                        event_id: format!("btcusd{MATURITY}"),
+                    },
+                }],
+                threshold: 0,
+            }],
+            counter_party: dummy_pubkey(),
+            offer_params: dummy_params(200),
+            total_collateral: 300,
+            funding_inputs_info: vec![],
+            fund_output_serial_id: 0,
+            fee_rate_per_vb: 0,
+            cet_locktime: 0,
+            refund_locktime: 0,
+        }
+    }
+
+    fn dummy_params(collateral: u64) -> PartyParams {
+        PartyParams {
+            collateral,
+            change_script_pubkey: Script::new(),
+            change_serial_id: 0,
+            fund_pubkey: dummy_pubkey(),
+            input_amount: 0,
+            inputs: vec![],
+            payout_script_pubkey: Script::new(),
+            payout_serial_id: 0,
+        }
+    }
+
+    fn dummy_pubkey() -> PublicKey {
+        PublicKey::from_str("02bd998ebd176715fe92b7467cf6b1df8023950a4dd911db4c94dfc89cc9f5a655")
+            .unwrap()
+    }
+
+    fn dummy_tx() -> Transaction {
+        Transaction {
+            version: 1,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![],
+            output: vec![],
+        }
+    }
+
+    fn dummy_signature() -> Signature {
+        Signature::from_str(
+            "304402202f2545f818a5dac9311157d75065156b141e5a6437e817d1d75f9fab084e46940220757bb6f0916f83b2be28877a0d6b05c45463794e3c8c99f799b774443575910d",
+        )
+        .unwrap()
+    }
+
+    fn dummy_schnorr_signature() -> schnorr::Signature {
+        schnorr::Signature::from_str(
+            "84526253c27c7aef56c7b71a5cd25bebb66dddda437826defc5b2568bde81f0784526253c27c7aef56c7b71a5cd25bebb66dddda437826defc5b2568bde81f07",
+        )
+        .unwrap()
+    }
+}
@@ -0,0 +1,138 @@
+use crate::node::Node;
+use crate::node::Storage;
+use crate::on_chain_wallet::BdkStorage;
+use crate::storage::TenTenOneStorage;
+use lightning::chain::channelmonitor::Balance;
+use serde::Serialize;
+
+/// A balance which we can claim on-chain from a channel, as reported by LDK.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ClaimableBalanceDetails {
+    pub kind: ClaimableBalanceKind,
+    pub amount_sats: u64,
+    /// The height at which the balance can be spent, once the claiming transaction has been
+    /// confirmed.
+    pub confirmation_height: Option<u32>,
+    /// The height at which the counterparty can claim the balance instead of us, or at which we
+    /// can claim the balance if the counterparty does not.
+    pub timeout_height: Option<u32>,
+}
+
+/// Mirrors the variants of LDK's [`Balance`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ClaimableBalanceKind {
+    /// The channel is not closed yet, but the balance would be claimable if it was.
+    ClaimableOnChannelClose,
+    /// The claiming transaction has been broadcast and is waiting for enough confirmations.
+    ClaimableAwaitingConfirmations,
+    /// An HTLC which we know the preimage for, racing against its timeout.
+    ContentiousClaimable,
+    /// An outbound HTLC which we can claim back after its timeout.
+    MaybeTimeoutClaimableHtlc,
+    /// An inbound HTLC which we can claim if we learn the preimage before its expiry.
+    MaybePreimageClaimableHtlc,
+    /// An output of a revoked commitment transaction broadcast by the counterparty.
+    CounterpartyRevokedOutputClaimable,
+}
+
+impl From<&Balance> for ClaimableBalanceDetails {
+    fn from(balance: &Balance) -> Self {
+        let (kind, confirmation_height, timeout_height) = match balance {
+            Balance::ClaimableOnChannelClose { .. } => {
+                (ClaimableBalanceKind::ClaimableOnChannelClose, None, None)
+            }
+            Balance::ClaimableAwaitingConfirmations {
+                confirmation_height,
+                ..
+            } => (
+                ClaimableBalanceKind::ClaimableAwaitingConfirmations,
+                Some(*confirmation_height),
+                None,
+            ),
+            Balance::ContentiousClaimable { timeout_height, .. } => (
+                ClaimableBalanceKind::ContentiousClaimable,
+                None,
+                Some(*timeout_height),
+            ),
+            Balance::MaybeTimeoutClaimableHTLC {
+                claimable_height, ..
+            } => (
+                ClaimableBalanceKind::MaybeTimeoutClaimableHtlc,
+                None,
+                Some(*claimable_height),
+            ),
+            Balance::MaybePreimageClaimableHTLC { expiry_height, .. } => (
+                ClaimableBalanceKind::MaybePreimageClaimableHtlc,
+                None,
+                Some(*expiry_height),
+            ),
+            Balance::CounterpartyRevokedOutputClaimable { .. } => (
+                ClaimableBalanceKind::CounterpartyRevokedOutputClaimable,
+                None,
+                None,
+            ),
+        };
+
+        Self {
+            kind,
+            amount_sats: balance.claimable_amount_satoshis(),
+            confirmation_height,
+            timeout_height,
+        }
+    }
+}
+
+impl<D: BdkStorage, S: TenTenOneStorage, N: Storage + Send + Sync + 'static> Node<D, S, N> {
+    /// List the balances we can claim on-chain from closing and closed channels, one entry per
+    /// LDK [`Balance`].
+    ///
+    /// Balances of channels which are still open are not included.
+    pub fn claimable_balances(&self) -> Vec<ClaimableBalanceDetails> {
+        let open_channels = self.channel_manager.list_channels();
+        let open_channels = open_channels.iter().collect::<Vec<_>>();
+
+        self.chain_monitor
+            .get_claimable_balances(&open_channels)
+            .iter()
+            .map(ClaimableBalanceDetails::from)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lightning::ln::PaymentHash;
+
+    #[test]
+    fn balance_heights_are_kept() {
+        let awaiting_confirmations = Balance::ClaimableAwaitingConfirmations {
+            amount_satoshis: 10_000,
+            confirmation_height: 800_006,
+        };
+        let timeout = Balance::MaybeTimeoutClaimableHTLC {
+            amount_satoshis: 2_000,
+            claimable_height: 800_144,
+            payment_hash: PaymentHash([0; 32]),
+        };
+
+        assert_eq!(
+            ClaimableBalanceDetails::from(&awaiting_confirmations),
+            ClaimableBalanceDetails {
+                kind: ClaimableBalanceKind::ClaimableAwaitingConfirmations,
+                amount_sats: 10_000,
+                confirmation_height: Some(800_006),
+                timeout_height: None,
+            }
+        );
+        assert_eq!(
+            ClaimableBalanceDetails::from(&timeout),
+            ClaimableBalanceDetails {
+                kind: ClaimableBalanceKind::MaybeTimeoutClaimableHtlc,
+                amount_sats: 2_000,
+                confirmation_height: None,
+                timeout_height: Some(800_144),
+            }
+        );
+    }
+}
@@ -19,17 +19,50 @@ use lightning::ln::features::NodeFeatures;
 use lightning::ln::msgs;
 use lightning::ln::msgs::OnionMessage;
 use lightning::ln::msgs::OnionMessageHandler;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::time::Duration;
 
+/// The optional init feature bit with which we advertise support for the DLC custom messages.
+const DLC_MESSAGES_FEATURE_BIT: usize = 257;
+
+/// What we learned about a peer when they connected.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ConnectedPeer {
+    pub inbound: bool,
+    pub supports_dlc: bool,
+}
+
+/// A peer we are currently connected to.
+#[derive(Debug, Clone, Serialize)]
+pub struct PeerDetails {
+    pub pubkey: PublicKey,
+    /// The address of the peer, if known.
+    pub address: Option<String>,
+    /// Whether the peer connected to us, as opposed to us connecting to them.
+    pub inbound: bool,
+    /// Whether the peer advertised support for the DLC custom messages. Peers running a version
+    /// which does not advertise it yet are reported as not supporting them.
+    pub supports_dlc: bool,
+}
+
 pub struct TenTenOneOnionMessageHandler {
     handler: Arc<NodeEventHandler>,
+    connected_peers: Arc<Mutex<HashMap<PublicKey, ConnectedPeer>>>,
 }
 
 impl TenTenOneOnionMessageHandler {
-    pub fn new(handler: Arc<NodeEventHandler>) -> Self {
-        TenTenOneOnionMessageHandler { handler }
+    pub(crate) fn new(
+        handler: Arc<NodeEventHandler>,
+        connected_peers: Arc<Mutex<HashMap<PublicKey, ConnectedPeer>>>,
+    ) -> Self {
+        TenTenOneOnionMessageHandler {
+            handler,
+            connected_peers,
+        }
     }
 }
 
@@ -56,10 +89,19 @@ impl OnionMessageHandler for TenTenOneOnionMessageHandler {
     fn peer_connected(
         &self,
         their_node_id: &bitcoin_old::secp256k1::PublicKey,
-        _init: &msgs::Init,
+        init: &msgs::Init,
         inbound: bool,
     ) -> Result<(), ()> {
-        tracing::info!(%their_node_id, inbound, "Peer connected!");
+        let supports_dlc = supports_dlc_messages(&init.features);
+        tracing::info!(%their_node_id, inbound, supports_dlc, "Peer connected!");
+
+        self.connected_peers.lock().insert(
+            to_secp_pk_30(*their_node_id),
+            ConnectedPeer {
+                inbound,
+                supports_dlc,
+            },
+        );
 
         if let Err(e) = self.handler.publish(NodeEvent::Connected {
             peer: to_secp_pk_30(*their_node_id),
@@ -69,7 +111,11 @@ impl OnionMessageHandler for TenTenOneOnionMessageHandler {
 
         Ok(())
     }
-    fn peer_disconnected(&self, _their_node_id: &bitcoin_old::secp256k1::PublicKey) {}
+    fn peer_disconnected(&self, their_node_id: &bitcoin_old::secp256k1::PublicKey) {
+        self.connected_peers
+            .lock()
+            .remove(&to_secp_pk_30(*their_node_id));
+    }
     fn provided_node_features(&self) -> NodeFeatures {
         NodeFeatures::empty()
     }
@@ -77,10 +123,27 @@ impl OnionMessageHandler for TenTenOneOnionMessageHandler {
         &self,
         _their_node_id: &bitcoin_old::secp256k1::PublicKey,
     ) -> InitFeatures {
-        InitFeatures::empty()
+        let mut features = InitFeatures::empty();
+        features
+            .set_optional_custom_bit(DLC_MESSAGES_FEATURE_BIT)
+            .expect("DLC messages feature bit to be a custom bit");
+
+        features
     }
 }
 
+/// Whether the peer set the optional or the required DLC messages feature bit.
+fn supports_dlc_messages(features: &InitFeatures) -> bool {
+    let flags = features.le_flags();
+    [DLC_MESSAGES_FEATURE_BIT, DLC_MESSAGES_FEATURE_BIT - 1]
+        .iter()
+        .any(|bit| {
+            flags
+                .get(bit / 8)
+                .is_some_and(|byte| byte & (1 << (bit % 8)) != 0)
+        })
+}
+
 impl<D: BdkStorage, S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 'static>
     Node<D, S, N>
 {
@@ -148,6 +211,27 @@ impl<D: BdkStorage, S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 's
         Ok(())
     }
 
+    /// Connect to a peer, unless we are already connected to them.
+    ///
+    /// Returns right away if the connection already exists, so callers can use this to make sure
+    /// they are connected before talking to the peer.
+    pub async fn connect_peer(&self, peer: NodeInfo) -> Result<()> {
+        if self.is_connected(peer.pubkey) {
+            tracing::trace!(%peer, "Already connected");
+            return Ok(());
+        }
+
+        self.connect_once(peer).await
+    }
+
+    /// Disconnect from a peer. Does nothing if we are not connected to them.
+    pub fn disconnect_peer(&self, node_id: PublicKey) {
+        tracing::debug!(%node_id, "Disconnecting from peer");
+
+        self.peer_manager
+            .disconnect_by_node_id(to_secp_pk_29(node_id));
+    }
+
     pub fn is_connected(&self, pubkey: PublicKey) -> bool {
         self.peer_manager
             .get_peer_node_ids()
@@ -155,3 +239,22 @@ impl<D: BdkStorage, S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 's
             .any(|(id, _)| *id == to_secp_pk_29(pubkey))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_advertised_dlc_messages_feature() {
+        let handler = TenTenOneOnionMessageHandler::new(
+            Arc::new(NodeEventHandler::new()),
+            Arc::new(Mutex::new(HashMap::new())),
+        );
+        let their_node_id = bitcoin_old::secp256k1::PublicKey::from_slice(&[2; 33]).unwrap();
+
+        assert!(supports_dlc_messages(
+            &handler.provided_init_features(&their_node_id)
+        ));
+        assert!(!supports_dlc_messages(&InitFeatures::empty()));
+    }
+}
@@ -1,5 +1,6 @@
 use crate::bitcoin_conversion::to_secp_pk_29;
 use crate::bitcoin_conversion::to_secp_pk_30;
+use crate::node::dlc_channel_state_name;
 use crate::node::event::NodeEvent;
 use crate::node::Node;
 use crate::node::Storage as LnDlcStorage;
@@ -30,6 +31,16 @@ use dlc_messages::Message;
 use time::OffsetDateTime;
 use tokio::task::spawn_blocking;
 
+#[derive(Debug, thiserror::Error)]
+pub enum ForceCloseDlcChannelError {
+    #[error("Unknown DLC channel")]
+    UnknownChannel,
+    #[error("DLC channel cannot be force-closed in state {0}")]
+    WrongState(String),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
 impl<D: BdkStorage, S: TenTenOneStorage + 'static, N: LnDlcStorage + Sync + Send + 'static>
     Node<D, S, N>
 {
@@ -69,7 +80,7 @@ impl<D: BdkStorage, S: TenTenOneStorage + 'static, N: LnDlcStorage + Sync + Send
             move || {
                 let announcements: Vec<_> = p2pd_oracles
                     .into_iter()
-                    .filter(|o| oracles.public_keys.contains(&o.public_key))
+                    .filter(|o| oracles.public_keys.contains(&o.get_public_key()))
                     .filter_map(|oracle| oracle.get_announcement(&event_id).ok())
                     .collect();
 
@@ -134,7 +145,7 @@ impl<D: BdkStorage, S: TenTenOneStorage + 'static, N: LnDlcStorage + Sync + Send
             .context("DLC channel to close not found")?;
 
         if is_force_close {
-            self.force_close_dlc_channel(&channel_id)?;
+            self.force_close_dlc_channel(channel_id)?;
         } else {
             self.propose_dlc_channel_collaborative_close(channel)
                 .await?
@@ -143,15 +154,46 @@ impl<D: BdkStorage, S: TenTenOneStorage + 'static, N: LnDlcStorage + Sync + Send
         Ok(())
     }
 
-    fn force_close_dlc_channel(&self, channel_id: &DlcChannelId) -> Result<()> {
-        let channel_id_hex = hex::encode(channel_id);
+    /// Force-close a DLC channel by broadcasting our latest buffer transaction.
+    ///
+    /// Only signed channels which are not already being closed can be force-closed.
+    pub fn force_close_dlc_channel(
+        &self,
+        dlc_channel_id: DlcChannelId,
+    ) -> Result<(), ForceCloseDlcChannelError> {
+        let channel_id_hex = hex::encode(dlc_channel_id);
+
+        let channel = self
+            .dlc_manager
+            .get_store()
+            .get_channel(&dlc_channel_id)
+            .map_err(anyhow::Error::from)?
+            .ok_or(ForceCloseDlcChannelError::UnknownChannel)?;
+
+        let can_force_close = match &channel {
+            Channel::Signed(signed_channel) => !matches!(
+                signed_channel.state,
+                SignedChannelState::Closing { .. } | SignedChannelState::SettledClosing { .. }
+            ),
+            _ => false,
+        };
+
+        if !can_force_close {
+            return Err(ForceCloseDlcChannelError::WrongState(
+                dlc_channel_state_name(&channel),
+            ));
+        }
 
         tracing::info!(
             channel_id = %channel_id_hex,
+            state = dlc_channel_state_name(&channel),
             "Force closing DLC channel"
         );
 
-        self.dlc_manager.force_close_channel(channel_id, None)?;
+        self.dlc_manager
+            .force_close_channel(&dlc_channel_id, None)
+            .map_err(anyhow::Error::from)?;
+
         Ok(())
     }
 
@@ -1,6 +1,7 @@
 use crate::bitcoin_conversion::to_secp_pk_29;
 use crate::dlc_wallet::DlcWallet;
 use crate::fee_rate_estimator::FeeRateEstimator;
+use crate::node::oracle::CachedOracle;
 use crate::node::Node;
 use crate::node::Storage;
 use crate::on_chain_wallet::BdkStorage;
@@ -10,6 +11,8 @@ use anyhow::Result;
 use bitcoin::secp256k1::PublicKey;
 use dlc_manager::channel::signed_channel::SignedChannel;
 use dlc_manager::channel::signed_channel::SignedChannelState;
+use dlc_manager::channel::Channel;
+use dlc_manager::Oracle;
 use dlc_manager::Storage as DlcStorage;
 use dlc_manager::SystemTimeProvider;
 use ln_dlc_storage::DlcStorageProvider;
@@ -23,7 +26,7 @@ pub type DlcManager<D, S, N> = dlc_manager::manager::Manager<
     Arc<DlcWallet<D, S, N>>,
     Arc<DlcWallet<D, S, N>>,
     Arc<DlcStorageProvider<S>>,
-    Arc<P2PDOracleClient>,
+    Arc<CachedOracle<P2PDOracleClient>>,
     Arc<SystemTimeProvider>,
     Arc<FeeRateEstimator>,
 >;
@@ -32,7 +35,7 @@ pub fn build<D: BdkStorage, S: TenTenOneStorage, N: Storage>(
     data_dir: &Path,
     wallet: Arc<DlcWallet<D, S, N>>,
     dlc_storage: Arc<DlcStorageProvider<S>>,
-    p2pdoracles: Vec<Arc<P2PDOracleClient>>,
+    p2pdoracles: Vec<Arc<CachedOracle<P2PDOracleClient>>>,
     fee_rate_estimator: Arc<FeeRateEstimator>,
 ) -> Result<DlcManager<D, S, N>> {
     let offers_path = data_dir.join("offers");
@@ -40,7 +43,7 @@ pub fn build<D: BdkStorage, S: TenTenOneStorage, N: Storage>(
 
     let mut oracles = HashMap::new();
     for oracle in p2pdoracles.into_iter() {
-        oracles.insert(oracle.public_key, oracle);
+        oracles.insert(oracle.get_public_key(), oracle);
     }
 
     // FIXME: We need to do this to ensure that we can upgrade `Node`s from LDK 0.0.114 to 0.0.116.
@@ -63,6 +66,25 @@ pub fn build<D: BdkStorage, S: TenTenOneStorage, N: Storage>(
     .context("Failed to initialise DlcManager")
 }
 
+pub fn dlc_channel_state_name(channel: &Channel) -> String {
+    let name = match channel {
+        Channel::Offered(_) => "Offered",
+        Channel::Accepted(_) => "Accepted",
+        Channel::Signed(signed_channel) => return signed_channel_state_name(signed_channel),
+        Channel::FailedAccept(_) => "FailedAccept",
+        Channel::FailedSign(_) => "FailedSign",
+        Channel::Cancelled(_) => "Cancelled",
+        Channel::Closing(_) => "Closing",
+        Channel::SettledClosing(_) => "SettledClosing",
+        Channel::Closed(_) => "Closed",
+        Channel::CounterClosed(_) => "CounterClosed",
+        Channel::ClosedPunished(_) => "ClosedPunished",
+        Channel::CollaborativelyClosed(_) => "CollaborativelyClosed",
+    };
+
+    name.to_string()
+}
+
 pub fn signed_channel_state_name(signed_channel: &SignedChannel) -> String {
     let name = match signed_channel.state {
         SignedChannelState::Established { .. } => "Established",
@@ -1,4 +1,6 @@
 use crate::bitcoin_conversion::to_network_29;
+use crate::bitcoin_conversion::to_script_29;
+use crate::bitcoin_conversion::to_secp_pk_29;
 use crate::bitcoin_conversion::to_secp_pk_30;
 use crate::blockchain::Blockchain;
 use crate::channel::UserChannelId;
@@ -7,7 +9,12 @@ use crate::dlc_wallet::DlcWallet;
 use crate::fee_rate_estimator::FeeRateEstimator;
 use crate::ln::manage_spendable_outputs;
 use crate::ln::TracingLogger;
+use crate::node::connection::ConnectedPeer;
 use crate::node::event::NodeEventHandler;
+use crate::node::oracle::CachedOracle;
+use crate::node::status::Heartbeat;
+use crate::node::status::LastSyncs;
+use crate::node::status::TaskHeartbeats;
 use crate::node::sub_channel::sub_channel_manager_periodic_check;
 use crate::on_chain_wallet::BdkStorage;
 use crate::on_chain_wallet::OnChainWallet;
@@ -19,6 +26,8 @@ use crate::EventHandlerTrait;
 use crate::NetworkGraph;
 use crate::P2pGossipSync;
 use crate::PeerManager;
+use anyhow::anyhow;
+use anyhow::bail;
 use anyhow::Context;
 use anyhow::Result;
 use bdk::FeeRate;
@@ -35,7 +44,10 @@ use futures::FutureExt;
 use lightning::chain::chaininterface::ConfirmationTarget;
 use lightning::chain::chainmonitor;
 use lightning::chain::Confirm;
+use lightning::ln::channelmanager::ChannelDetails;
 use lightning::ln::peer_handler::MessageHandler;
+use lightning::ln::script::ShutdownScript;
+use lightning::ln::ChannelId;
 use lightning::routing::gossip::P2PGossipSync;
 use lightning::routing::router::DefaultRouter;
 use lightning::routing::scoring::ProbabilisticScorer;
@@ -54,6 +66,7 @@ use serde::Deserialize;
 use serde::Serialize;
 use serde_with::serde_as;
 use serde_with::DurationSeconds;
+use std::collections::HashMap;
 use std::fmt;
 use std::fmt::Display;
 use std::fmt::Formatter;
@@ -63,13 +76,17 @@ use std::sync::Arc;
 use std::time::Duration;
 use std::time::Instant;
 use std::time::SystemTime;
+use time::OffsetDateTime;
+use tokio::sync::watch;
 use tokio::sync::RwLock;
 use tokio::task::spawn_blocking;
 
+mod balance;
 mod channel_manager;
 mod connection;
 mod dlc_manager;
 mod oracle;
+mod status;
 mod storage;
 mod sub_channel_manager;
 mod wallet;
@@ -81,11 +98,17 @@ pub mod event;
 pub mod peer_manager;
 
 pub use ::dlc_manager as rust_dlc_manager;
+pub use balance::ClaimableBalanceDetails;
+pub use balance::ClaimableBalanceKind;
 pub use channel_manager::ChannelManager;
+pub use connection::PeerDetails;
 pub use connection::TenTenOneOnionMessageHandler;
+pub use dlc_manager::dlc_channel_state_name;
 pub use dlc_manager::signed_channel_state_name;
 pub use dlc_manager::DlcManager;
+pub use oracle::CachedOracle;
 pub use oracle::OracleInfo;
+pub use status::NodeStatus;
 pub use storage::InMemoryStore;
 pub use storage::Storage;
 pub use sub_channel::dlc_message_name;
@@ -115,6 +138,12 @@ pub struct LiquidityRequest {
 /// An LN-DLC node.
 pub struct Node<D: BdkStorage, S: TenTenOneStorage, N: Storage> {
     pub settings: Arc<RwLock<LnDlcNodeSettings>>,
+    /// Notifies the periodic tasks that the settings have been updated.
+    settings_updated: watch::Sender<()>,
+    last_syncs: Arc<parking_lot::Mutex<LastSyncs>>,
+    heartbeats: TaskHeartbeats,
+    /// Details on the connected peers, which the [`PeerManager`] does not keep track of.
+    connected_peers: Arc<parking_lot::Mutex<HashMap<PublicKey, ConnectedPeer>>>,
     pub network: Network,
 
     pub(crate) wallet: Arc<OnChainWallet<D>>,
@@ -138,7 +167,7 @@ pub struct Node<D: BdkStorage, S: TenTenOneStorage, N: Storage> {
     pub sub_channel_manager: Arc<SubChannelManager<D, S, N>>,
 
     /// All oracles clients the node is aware of.
-    oracles: Vec<Arc<P2PDOracleClient>>,
+    oracles: Vec<Arc<CachedOracle<P2PDOracleClient>>>,
     pub dlc_message_handler: Arc<DlcMessageHandler>,
     pub ldk_config: Arc<parking_lot::RwLock<UserConfig>>,
 
@@ -178,9 +207,33 @@ pub struct NodeInfo {
     pub is_ws: bool,
 }
 
-/// Node is running until this struct is dropped
+/// Node is running until this struct is dropped or [`RunningNode::shutdown`] is called.
 pub struct RunningNode {
     _handles: Vec<RemoteHandle<()>>,
+    background_processor: RemoteHandle<()>,
+    stop_background_processor: watch::Sender<bool>,
+    disconnect_all_peers: Box<dyn Fn() + Send + Sync>,
+}
+
+impl RunningNode {
+    /// Disconnect all peers and stop the background tasks.
+    ///
+    /// Unlike dropping the [`RunningNode`], this waits for the background processor to exit, so
+    /// that the channel manager, the network graph and the scorer are persisted one last time.
+    pub async fn shutdown(self) -> Result<()> {
+        tracing::info!("Shutting down node");
+
+        (self.disconnect_all_peers)();
+
+        if self.stop_background_processor.send(true).is_err() {
+            tracing::warn!("Background processor already stopped");
+        }
+        self.background_processor.await;
+
+        tracing::info!("Node shut down");
+
+        Ok(())
+    }
 }
 
 #[serde_as]
@@ -201,6 +254,23 @@ pub struct LnDlcNodeSettings {
     /// How often we sync the shadow states
     #[serde_as(as = "DurationSeconds")]
     pub shadow_sync_interval: Duration,
+    /// How long oracle announcements and attestations are cached. Only applied on startup.
+    #[serde_as(as = "DurationSeconds")]
+    #[serde(default = "default_oracle_cache_ttl")]
+    pub oracle_cache_ttl: Duration,
+    /// How long a failed oracle request, e.g. for an event which has not been attested yet, is
+    /// cached. Only applied on startup.
+    #[serde_as(as = "DurationSeconds")]
+    #[serde(default = "default_oracle_negative_cache_ttl")]
+    pub oracle_negative_cache_ttl: Duration,
+}
+
+fn default_oracle_cache_ttl() -> Duration {
+    Duration::from_secs(60)
+}
+
+fn default_oracle_negative_cache_ttl() -> Duration {
+    Duration::from_secs(5)
 }
 
 impl<D: BdkStorage, S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 'static>
@@ -209,6 +279,20 @@ impl<D: BdkStorage, S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 's
     pub async fn update_settings(&self, new_settings: LnDlcNodeSettings) {
         tracing::info!(?new_settings, "Updating LnDlcNode settings");
         *self.settings.write().await = new_settings;
+        self.settings_updated.send_replace(());
+    }
+
+    /// Sleep for the interval selected from the settings.
+    ///
+    /// If the settings are updated in the meantime, the new interval applies right away, measured
+    /// from when we started sleeping.
+    pub async fn sleep_for_interval(&self, interval: impl Fn(&LnDlcNodeSettings) -> Duration) {
+        sleep_for_interval(
+            &self.settings,
+            &mut self.settings_updated.subscribe(),
+            interval,
+        )
+        .await
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -319,8 +403,16 @@ impl<D: BdkStorage, S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 's
             logger.clone(),
         ));
 
-        let oracle_clients: Vec<Arc<P2PDOracleClient>> =
-            oracle_clients.into_iter().map(Arc::new).collect();
+        let oracle_clients: Vec<Arc<CachedOracle<P2PDOracleClient>>> = oracle_clients
+            .into_iter()
+            .map(|oracle| {
+                Arc::new(CachedOracle::new(
+                    oracle,
+                    settings.oracle_cache_ttl,
+                    settings.oracle_negative_cache_ttl,
+                ))
+            })
+            .collect();
 
         let dlc_wallet = DlcWallet::new(
             on_chain_wallet.clone(),
@@ -347,8 +439,10 @@ impl<D: BdkStorage, S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 's
 
         let dlc_message_handler = Arc::new(DlcMessageHandler::new());
 
+        let connected_peers = Arc::new(parking_lot::Mutex::new(HashMap::new()));
         let onion_message_handler = Arc::new(TenTenOneOnionMessageHandler::new(
             node_event_handler.clone(),
+            connected_peers.clone(),
         ));
 
         let lightning_msg_handler = MessageHandler {
@@ -373,6 +467,7 @@ impl<D: BdkStorage, S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 's
         };
 
         let settings = Arc::new(RwLock::new(settings));
+        let (settings_updated, _) = watch::channel(());
 
         Ok(Self {
             network,
@@ -396,6 +491,10 @@ impl<D: BdkStorage, S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 's
             ldk_config,
             network_graph,
             settings,
+            settings_updated,
+            last_syncs: Default::default(),
+            heartbeats: Default::default(),
+            connected_peers,
             listen_address,
             scorer,
             electrs_server_url,
@@ -423,10 +522,12 @@ impl<D: BdkStorage, S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 's
         #[cfg(not(feature = "ln_net_tcp"))]
         let mut handles = Vec::new();
 
-        std::thread::spawn(shadow_sync_periodically(
+        tokio::spawn(shadow_sync_periodically(
             self.settings.clone(),
+            self.settings_updated.subscribe(),
             self.node_storage.clone(),
             self.wallet.clone(),
+            self.heartbeats.shadow_sync.clone(),
         ));
 
         // TODO: Remove once all pending production subchannels are gone.
@@ -434,16 +535,22 @@ impl<D: BdkStorage, S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 's
             self.channel_manager.clone(),
             self.chain_monitor.clone(),
             self.settings.clone(),
+            self.settings_updated.subscribe(),
             self.esplora_client.clone(),
+            self.last_syncs.clone(),
+            self.heartbeats.lightning_wallet_sync.clone(),
         ));
 
         tokio::spawn(update_fee_rate_estimates(
             self.settings.clone(),
+            self.settings_updated.subscribe(),
             self.fee_rate_estimator.clone(),
+            self.heartbeats.fee_rate_update.clone(),
         ));
 
         // TODO: Remove once all pending production subchannels are gone.
-        handles.push(spawn_background_processor(
+        let (stop_background_processor, stop_background_processor_rx) = watch::channel(false);
+        let background_processor = spawn_background_processor(
             self.peer_manager.clone(),
             self.channel_manager.clone(),
             self.chain_monitor.clone(),
@@ -453,10 +560,14 @@ impl<D: BdkStorage, S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 's
             self.gossip_sync.clone(),
             self.scorer.clone(),
             mobile_interruptable_platform,
-        ));
+            stop_background_processor_rx,
+        );
 
         // TODO: Remove once all pending production subchannels are gone.
-        handles.push(manage_sub_channels(self.sub_channel_manager.clone()));
+        handles.push(manage_sub_channels(
+            self.sub_channel_manager.clone(),
+            self.heartbeats.sub_channel_manager_periodic_check.clone(),
+        ));
 
         tokio::spawn(manage_spendable_outputs_task::<D, N>(
             self.electrs_server_url.clone(),
@@ -465,11 +576,22 @@ impl<D: BdkStorage, S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 's
             self.blockchain.clone(),
             self.fee_rate_estimator.clone(),
             self.keys_manager.clone(),
+            self.heartbeats.manage_spendable_outputs.clone(),
         ));
 
         tracing::info!("Lightning node started with node ID {}", self.info);
 
-        Ok(RunningNode { _handles: handles })
+        let disconnect_all_peers = {
+            let peer_manager = self.peer_manager.clone();
+            Box::new(move || peer_manager.disconnect_all_peers())
+        };
+
+        Ok(RunningNode {
+            _handles: handles,
+            background_processor,
+            stop_background_processor,
+            disconnect_all_peers,
+        })
     }
 
     pub async fn sub_channel_manager_periodic_check(&self) -> Result<()> {
@@ -481,7 +603,11 @@ impl<D: BdkStorage, S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 's
             &self.channel_manager,
             &self.chain_monitor,
             &self.esplora_client,
-        )
+        )?;
+
+        self.last_syncs.lock().lightning = Some(OffsetDateTime::now_utc());
+
+        Ok(())
     }
 
     /// Send the given `amount_sats` sats to the given unchecked, on-chain `address`.
@@ -509,11 +635,178 @@ impl<D: BdkStorage, S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 's
         Ok(txid)
     }
 
-    pub fn list_peers(&self) -> Vec<PublicKey> {
+    /// Replace the unconfirmed on-chain transaction `txid` with one paying `fee_rate`.
+    ///
+    /// Fails if the transaction is unknown to the wallet or already confirmed.
+    pub async fn bump_fee(&self, txid: Txid, fee_rate: FeeRate) -> Result<Txid> {
+        let tx = spawn_blocking({
+            let wallet = self.wallet.clone();
+            move || wallet.build_fee_bump_tx(txid, fee_rate)
+        })
+        .await
+        .expect("task to complete")?;
+
+        let txid = self.blockchain.broadcast_transaction_blocking(&tx)?;
+
+        Ok(txid)
+    }
+
+    /// Send all spendable on-chain funds to the given unchecked, on-chain `address`.
+    ///
+    /// Fails if the wallet has nothing to spend or if the remaining amount after fees would be
+    /// dust.
+    pub async fn sweep_on_chain(
+        &self,
+        address: Address<NetworkUnchecked>,
+        fee: Fee,
+    ) -> Result<Txid> {
+        let balance = self.get_on_chain_balance();
+        if balance.trusted_spendable() == 0 {
+            bail!("No spendable on-chain funds to sweep");
+        }
+
+        // An amount of 0 drains the wallet. BDK refuses to create the drain output if it would
+        // be dust after fees.
+        self.send_to_address(address, 0, fee)
+            .await
+            .context("Failed to sweep on-chain funds")
+    }
+
+    /// Cooperatively close the Lightning channel with the given [`ChannelId`].
+    ///
+    /// Our output is paid to the on-chain wallet at the fee rate LDK proposes by default.
+    pub fn close_channel(&self, channel_id: ChannelId, counterparty: PublicKey) -> Result<()> {
+        self.close_channel_with_fee_rate_and_destination(channel_id, counterparty, None, None)
+    }
+
+    /// Cooperatively close the Lightning channel with the given [`ChannelId`], optionally
+    /// targeting the given `fee_rate` and paying our output to `destination` instead of the
+    /// on-chain wallet, e.g. to move the funds to cold storage.
+    ///
+    /// The closing transaction is only negotiated with the counterparty after this returns, so
+    /// its txid is not known yet.
+    pub fn close_channel_with_fee_rate_and_destination(
+        &self,
+        channel_id: ChannelId,
+        counterparty: PublicKey,
+        fee_rate: Option<FeeRate>,
+        destination: Option<Address<NetworkUnchecked>>,
+    ) -> Result<()> {
+        self.find_channel(channel_id, counterparty)?;
+
+        let shutdown_script = destination
+            .map(|address| {
+                let address = address.require_network(self.network)?;
+                let script = to_script_29(address.script_pubkey());
+
+                ShutdownScript::try_from(script)
+                    .map_err(|_| anyhow!("Unsupported destination {address} for channel close"))
+            })
+            .transpose()?;
+
+        // LDK expects the fee rate in sats per 1000 weight units. A vbyte is 4 weight units.
+        let fee_rate_sats_per_kw =
+            fee_rate.map(|fee_rate| (fee_rate.as_sat_per_vb() * 250.0) as u32);
+
+        tracing::info!(
+            %channel_id,
+            %counterparty,
+            ?fee_rate_sats_per_kw,
+            ?shutdown_script,
+            "Closing channel"
+        );
+
+        self.channel_manager
+            .close_channel_with_feerate_and_script(
+                &channel_id,
+                &to_secp_pk_29(counterparty),
+                fee_rate_sats_per_kw,
+                shutdown_script,
+            )
+            .map_err(|e| anyhow!("Failed to close channel {channel_id}: {e:?}"))?;
+
+        Ok(())
+    }
+
+    /// Force-close the Lightning channel with the given [`ChannelId`], broadcasting our latest
+    /// commitment transaction.
+    ///
+    /// This is meant for channels which are stuck. Note that channels with a DLC in them should
+    /// be force-closed through the [`SubChannelManager`] instead.
+    pub fn force_close_channel(
+        &self,
+        channel_id: ChannelId,
+        counterparty: PublicKey,
+    ) -> Result<()> {
+        let channel = self.find_channel(channel_id, counterparty)?;
+
+        tracing::warn!(%channel_id, %counterparty, "Force-closing channel");
+
+        self.channel_manager
+            .force_close_broadcasting_latest_txn(&channel_id, &to_secp_pk_29(counterparty))
+            .map_err(|e| anyhow!("Failed to force-close channel {channel_id}: {e:?}"))?;
+
+        let commitment_txid = channel
+            .funding_txo
+            .and_then(|funding_txo| self.chain_monitor.get_monitor(funding_txo).ok())
+            .and_then(|monitor| {
+                monitor
+                    .get_latest_holder_commitment_txn(&self.logger)
+                    .first()
+                    .map(|tx| tx.txid())
+            });
+
+        match commitment_txid {
+            Some(txid) => tracing::warn!(%channel_id, %txid, "Broadcast commitment transaction"),
+            None => tracing::warn!(%channel_id, "Could not determine commitment transaction"),
+        }
+
+        Ok(())
+    }
+
+    fn find_channel(
+        &self,
+        channel_id: ChannelId,
+        counterparty: PublicKey,
+    ) -> Result<ChannelDetails> {
+        self.channel_manager
+            .list_channels()
+            .into_iter()
+            .find(|channel| {
+                channel.channel_id == channel_id
+                    && channel.counterparty.node_id == to_secp_pk_29(counterparty)
+            })
+            .with_context(|| format!("Unknown channel {channel_id} with {counterparty}"))
+    }
+
+    /// Force-close all Lightning channels without broadcasting our commitment transactions.
+    ///
+    /// This is only useful if our channel state is known to be stale, e.g. after restoring from
+    /// an outdated backup, because broadcasting would then give the counterparties our funds.
+    pub fn force_close_all_channels_without_broadcasting(&self) {
+        tracing::warn!("Force-closing all channels without broadcasting");
+
+        self.channel_manager
+            .force_close_all_channels_without_broadcasting_txn();
+    }
+
+    pub fn list_peers(&self) -> Vec<PeerDetails> {
+        let connected_peers = self.connected_peers.lock();
+
         self.peer_manager
             .get_peer_node_ids()
             .into_iter()
-            .map(|(peer, _)| to_secp_pk_30(peer))
+            .map(|(peer, address)| {
+                let pubkey = to_secp_pk_30(peer);
+                let connected_peer = connected_peers.get(&pubkey);
+
+                PeerDetails {
+                    pubkey,
+                    address: address.map(|address| address.to_string()),
+                    inbound: connected_peer.is_some_and(|peer| peer.inbound),
+                    supports_dlc: connected_peer.is_some_and(|peer| peer.supports_dlc),
+                }
+            })
             .collect()
     }
 
@@ -526,18 +819,45 @@ impl<D: BdkStorage, S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 's
 
 async fn update_fee_rate_estimates(
     settings: Arc<RwLock<LnDlcNodeSettings>>,
+    mut settings_updated: watch::Receiver<()>,
     fee_rate_estimator: Arc<FeeRateEstimator>,
+    heartbeat: Arc<Heartbeat>,
 ) {
     loop {
-        if let Err(err) = fee_rate_estimator.update().await {
+        if let Err(err) = heartbeat.track(fee_rate_estimator.update().await) {
             tracing::error!("Failed to update fee rate estimates: {err:#}");
         }
 
-        let interval = {
-            let guard = settings.read().await;
-            guard.fee_rate_sync_interval
-        };
-        tokio::time::sleep(interval).await;
+        sleep_for_interval(&settings, &mut settings_updated, |settings| {
+            settings.fee_rate_sync_interval
+        })
+        .await;
+    }
+}
+
+/// Sleep for the interval selected from the settings, starting over whenever the settings are
+/// updated, so that a changed interval applies without waiting for the previous one to elapse.
+async fn sleep_for_interval(
+    settings: &RwLock<LnDlcNodeSettings>,
+    settings_updated: &mut watch::Receiver<()>,
+    interval: impl Fn(&LnDlcNodeSettings) -> Duration,
+) {
+    let start = tokio::time::Instant::now();
+
+    loop {
+        settings_updated.borrow_and_update();
+        let deadline = start + interval(&*settings.read().await);
+
+        tokio::select! {
+            _ = tokio::time::sleep_until(deadline) => return,
+            changed = settings_updated.changed() => {
+                if changed.is_err() {
+                    // The node is gone, there will be no further updates.
+                    tokio::time::sleep_until(deadline).await;
+                    return;
+                }
+            }
+        }
     }
 }
 
@@ -556,6 +876,7 @@ fn spawn_background_processor<
     gossip_sync: Arc<NodeGossipSync>,
     scorer: Arc<std::sync::RwLock<Scorer>>,
     mobile_interruptable_platform: bool,
+    stop: watch::Receiver<bool>,
 ) -> RemoteHandle<()> {
     tracing::info!("Starting background processor");
     let (fut, remote_handle) = async move {
@@ -569,9 +890,14 @@ fn spawn_background_processor<
             logger,
             Some(scorer),
             |d| {
+                let mut stop = stop.clone();
                 Box::pin(async move {
-                    tokio::time::sleep(d).await;
-                    false
+                    // Returning `true` makes the background processor persist everything and
+                    // exit.
+                    tokio::select! {
+                        _ = tokio::time::sleep(d) => false,
+                        _ = stop.changed() => true,
+                    }
                 })
             },
             mobile_interruptable_platform,
@@ -594,18 +920,25 @@ async fn periodic_lightning_wallet_sync<
     channel_manager: Arc<ChannelManager<D, S, N>>,
     chain_monitor: Arc<ChainMonitor<S, N>>,
     settings: Arc<RwLock<LnDlcNodeSettings>>,
+    mut settings_updated: watch::Receiver<()>,
     esplora_client: Arc<EsploraSyncClient<Arc<TracingLogger>>>,
+    last_syncs: Arc<parking_lot::Mutex<LastSyncs>>,
+    heartbeat: Arc<Heartbeat>,
 ) {
     loop {
-        if let Err(e) = lightning_wallet_sync(&channel_manager, &chain_monitor, &esplora_client) {
-            tracing::error!("Background sync of Lightning wallet failed: {e:#}")
+        match heartbeat.track(lightning_wallet_sync(
+            &channel_manager,
+            &chain_monitor,
+            &esplora_client,
+        )) {
+            Ok(()) => last_syncs.lock().lightning = Some(OffsetDateTime::now_utc()),
+            Err(e) => tracing::error!("Background sync of Lightning wallet failed: {e:#}"),
         }
 
-        let interval = {
-            let guard = settings.read().await;
-            guard.off_chain_sync_interval
-        };
-        tokio::time::sleep(interval).await;
+        sleep_for_interval(&settings, &mut settings_updated, |settings| {
+            settings.off_chain_sync_interval
+        })
+        .await;
     }
 }
 
@@ -631,24 +964,27 @@ fn lightning_wallet_sync<D: BdkStorage, S: TenTenOneStorage, N: Storage + Sync +
     Ok(())
 }
 
-fn shadow_sync_periodically<D: BdkStorage, N: Storage>(
+async fn shadow_sync_periodically<D: BdkStorage, N: Storage + Send + Sync + 'static>(
     settings: Arc<RwLock<LnDlcNodeSettings>>,
+    mut settings_updated: watch::Receiver<()>,
     node_storage: Arc<N>,
     wallet: Arc<OnChainWallet<D>>,
-) -> impl Fn() {
-    let handle = tokio::runtime::Handle::current();
-    let shadow = Shadow::new(node_storage, wallet);
-    move || loop {
-        if let Err(e) = shadow.sync_transactions() {
+    heartbeat: Arc<Heartbeat>,
+) {
+    let shadow = Arc::new(Shadow::new(node_storage, wallet));
+    loop {
+        let sync = spawn_blocking({
+            let shadow = shadow.clone();
+            move || shadow.sync_transactions()
+        });
+        if let Err(e) = heartbeat.track(sync.await.expect("task to complete")) {
             tracing::error!("Failed to sync transaction shadows. Error: {e:#}");
         }
 
-        let interval = handle.block_on(async {
-            let guard = settings.read().await;
-            guard.shadow_sync_interval
-        });
-
-        std::thread::sleep(interval);
+        sleep_for_interval(&settings, &mut settings_updated, |settings| {
+            settings.shadow_sync_interval
+        })
+        .await;
     }
 }
 
@@ -709,13 +1045,14 @@ async fn manage_spendable_outputs_task<D: BdkStorage, N: Storage + Sync + Send +
     blockchain: Arc<Blockchain<N>>,
     fee_rate_estimator: Arc<FeeRateEstimator>,
     keys_manager: Arc<CustomKeysManager<D>>,
+    heartbeat: Arc<Heartbeat>,
 ) {
     let client = Arc::new(esplora_client::BlockingClient::from_agent(
         electrs_server_url,
         ureq::agent(),
     ));
     loop {
-        if let Err(e) = spawn_blocking({
+        let result = spawn_blocking({
             let client = client.clone();
             let node_storage = node_storage.clone();
             let ln_dlc_wallet = wallet.clone();
@@ -734,8 +1071,9 @@ async fn manage_spendable_outputs_task<D: BdkStorage, N: Storage + Sync + Send +
             }
         })
         .await
-        .expect("task to complete")
-        {
+        .expect("task to complete");
+
+        if let Err(e) = heartbeat.track(result) {
             tracing::error!("Failed to deal with spendable outputs: {e:#}");
         };
 
@@ -750,14 +1088,15 @@ fn manage_sub_channels<
     N: Storage + Send + Sync + 'static,
 >(
     sub_channel_manager: Arc<SubChannelManager<D, S, N>>,
+    heartbeat: Arc<Heartbeat>,
 ) -> RemoteHandle<()> {
     let (fut, remote_handle) = {
         async move {
             loop {
                 tracing::trace!("Started periodic check");
                 let now = Instant::now();
-                if let Err(e) =
-                    sub_channel_manager_periodic_check(sub_channel_manager.clone()).await
+                if let Err(e) = heartbeat
+                    .track(sub_channel_manager_periodic_check(sub_channel_manager.clone()).await)
                 {
                     tracing::error!("Failed to process pending DLC actions: {e:#}");
                 };
@@ -785,3 +1124,42 @@ impl Display for NodeInfo {
         format!("{scheme}://{}@{}", self.pubkey, self.address).fmt(f)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn shortened_interval_applies_to_ongoing_sleep() {
+        let settings = Arc::new(RwLock::new(LnDlcNodeSettings {
+            off_chain_sync_interval: Duration::from_secs(5),
+            on_chain_sync_interval: Duration::from_secs(300),
+            fee_rate_sync_interval: Duration::from_secs(300),
+            sub_channel_manager_periodic_check_interval: Duration::from_secs(30),
+            shadow_sync_interval: Duration::from_secs(600),
+            oracle_cache_ttl: Duration::from_secs(60),
+            oracle_negative_cache_ttl: Duration::from_secs(5),
+        }));
+        let (settings_updated, mut settings_updated_rx) = watch::channel(());
+
+        let sleep = tokio::spawn({
+            let settings = settings.clone();
+            async move {
+                sleep_for_interval(&settings, &mut settings_updated_rx, |settings| {
+                    settings.fee_rate_sync_interval
+                })
+                .await
+            }
+        });
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        settings.write().await.fee_rate_sync_interval = Duration::from_millis(200);
+        settings_updated.send_replace(());
+
+        tokio::time::timeout(Duration::from_secs(5), sleep)
+            .await
+            .expect("sleep to end with the shortened interval")
+            .unwrap();
+    }
+}
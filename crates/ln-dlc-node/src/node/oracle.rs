@@ -4,11 +4,25 @@ use crate::node::Node;
 use crate::node::Storage;
 use crate::on_chain_wallet::BdkStorage;
 use crate::storage::TenTenOneStorage;
+use anyhow::ensure;
+use anyhow::Context;
 use bitcoin::secp256k1::XOnlyPublicKey;
+use bitcoin_old::hashes::sha256;
+use bitcoin_old::secp256k1::Message;
+use bitcoin_old::secp256k1::Secp256k1;
+use dlc_manager::error::Error;
 use dlc_manager::Oracle;
+use dlc_manager::Storage as _;
+use dlc_messages::oracle_msgs::OracleAnnouncement;
+use dlc_messages::oracle_msgs::OracleAttestation;
 use p2pd_oracle_client::P2PDOracleClient;
+use parking_lot::Mutex;
 use serde::Deserialize;
 use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct OracleInfo {
@@ -33,4 +47,465 @@ impl<D: BdkStorage, S: TenTenOneStorage, N: Storage> Node<D, S, N> {
             .map(|oracle| to_xonly_pk_30(oracle.get_public_key()))
             .collect()
     }
+
+    /// Settle the contracts on `event_id` with an attestation supplied by the operator, instead of
+    /// the one published by the oracle.
+    ///
+    /// This is meant for recovering from an oracle which is permanently unavailable. The
+    /// attestation has to be signed by one of our oracles which the contracts on the event also
+    /// allow, e.g. a backup oracle key. The signatures are verified against the nonces of the
+    /// corresponding announcement.
+    pub fn override_oracle_attestation(
+        &self,
+        event_id: &str,
+        attestation: OracleAttestation,
+    ) -> anyhow::Result<()> {
+        let oracle_pk = attestation.oracle_public_key;
+
+        let oracle = self
+            .oracles
+            .iter()
+            .find(|oracle| oracle.get_public_key() == oracle_pk)
+            .with_context(|| format!("Unknown oracle {oracle_pk}"))?;
+
+        let contracts = self.dlc_manager.get_store().get_confirmed_contracts()?;
+        let announcement = contracts
+            .iter()
+            .flat_map(|contract| &contract.accepted_contract.offered_contract.contract_info)
+            .flat_map(|contract_info| &contract_info.oracle_announcements)
+            .find(|announcement| {
+                announcement.oracle_public_key == oracle_pk
+                    && announcement.oracle_event.event_id == event_id
+            })
+            .with_context(|| {
+                format!("No confirmed contract on event {event_id} allows oracle {oracle_pk}")
+            })?;
+
+        verify_attestation(announcement, &attestation)?;
+
+        tracing::warn!(
+            event_id,
+            %oracle_pk,
+            outcomes = ?attestation.outcomes,
+            "Overriding oracle attestation"
+        );
+
+        oracle.override_attestation(event_id, attestation);
+
+        Ok(())
+    }
+
+    /// Get the attestation of `event_id` from the first of our oracles which has attested to it.
+    ///
+    /// The attestation is verified against the nonces of the oracle's announcement of the event.
+    ///
+    /// Blocks on the oracles, unless the attestation is cached.
+    pub fn get_oracle_attestation(&self, event_id: &str) -> anyhow::Result<OracleAttestation> {
+        let mut errors = vec![];
+        for oracle in self.oracles.iter() {
+            match oracle.get_verified_attestation(event_id) {
+                Ok(attestation) => return Ok(attestation),
+                Err(e) => errors.push(format!("{}: {e:?}", oracle.get_public_key())),
+            }
+        }
+
+        anyhow::bail!("No attestation for event {event_id}: {}", errors.join(", "))
+    }
+}
+
+/// Check that every outcome of the attestation is signed by the oracle, using the nonce which it
+/// committed to in the announcement.
+fn verify_attestation(
+    announcement: &OracleAnnouncement,
+    attestation: &OracleAttestation,
+) -> anyhow::Result<()> {
+    ensure!(
+        attestation.oracle_public_key == announcement.oracle_public_key,
+        "Attestation by oracle {} for announcement by oracle {}",
+        attestation.oracle_public_key,
+        announcement.oracle_public_key
+    );
+
+    let nonces = &announcement.oracle_event.oracle_nonces;
+
+    ensure!(
+        attestation.signatures.len() == nonces.len() && attestation.outcomes.len() == nonces.len(),
+        "Expected {} signed outcomes, got {} signatures for {} outcomes",
+        nonces.len(),
+        attestation.signatures.len(),
+        attestation.outcomes.len()
+    );
+
+    let secp = Secp256k1::verification_only();
+    for ((signature, outcome), nonce) in attestation
+        .signatures
+        .iter()
+        .zip(attestation.outcomes.iter())
+        .zip(nonces.iter())
+    {
+        ensure!(
+            signature[..32] == nonce.serialize()[..],
+            "Signature for outcome {outcome} does not use the announced nonce"
+        );
+
+        let message = Message::from_hashed_data::<sha256::Hash>(outcome.as_bytes());
+        secp.verify_schnorr(signature, &message, &attestation.oracle_public_key)
+            .with_context(|| format!("Invalid signature for outcome {outcome}"))?;
+    }
+
+    Ok(())
+}
+
+/// An [`Oracle`] which caches announcements and attestations per event.
+///
+/// Concurrent requests for the same event wait for a single fetch from the oracle. Failed fetches
+/// are cached as well, but for a shorter time, so that we do not hammer the oracle while e.g. an
+/// attestation is still pending.
+pub struct CachedOracle<O> {
+    oracle: O,
+    ttl: Duration,
+    negative_ttl: Duration,
+    announcements: Cache<OracleAnnouncement>,
+    attestations: Cache<OracleAttestation>,
+    /// Attestations supplied by the operator, which take precedence over the oracle's.
+    attestation_overrides: Mutex<HashMap<String, OracleAttestation>>,
+}
+
+impl<O> CachedOracle<O> {
+    pub fn new(oracle: O, ttl: Duration, negative_ttl: Duration) -> Self {
+        Self {
+            oracle,
+            ttl,
+            negative_ttl,
+            announcements: Cache::default(),
+            attestations: Cache::default(),
+            attestation_overrides: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn override_attestation(&self, event_id: &str, attestation: OracleAttestation) {
+        self.attestation_overrides
+            .lock()
+            .insert(event_id.to_string(), attestation);
+    }
+}
+
+impl<O: Oracle> CachedOracle<O> {
+    /// Get the attestation of `event_id`, verified against the nonces of the announcement.
+    ///
+    /// Overridden attestations have already been verified when they were supplied.
+    fn get_verified_attestation(&self, event_id: &str) -> anyhow::Result<OracleAttestation> {
+        if let Some(attestation) = self.attestation_overrides.lock().get(event_id) {
+            return Ok(attestation.clone());
+        }
+
+        let attestation = self.get_attestation(event_id)?;
+        let announcement = self.get_announcement(event_id)?;
+        verify_attestation(&announcement, &attestation)?;
+
+        Ok(attestation)
+    }
+}
+
+impl<O: Oracle> Oracle for CachedOracle<O> {
+    fn get_public_key(&self) -> bitcoin_old::XOnlyPublicKey {
+        self.oracle.get_public_key()
+    }
+
+    fn get_announcement(&self, event_id: &str) -> Result<OracleAnnouncement, Error> {
+        self.announcements
+            .get_or_fetch(event_id, self.ttl, self.negative_ttl, || {
+                self.oracle.get_announcement(event_id)
+            })
+    }
+
+    fn get_attestation(&self, event_id: &str) -> Result<OracleAttestation, Error> {
+        if let Some(attestation) = self.attestation_overrides.lock().get(event_id) {
+            return Ok(attestation.clone());
+        }
+
+        self.attestations
+            .get_or_fetch(event_id, self.ttl, self.negative_ttl, || {
+                self.oracle.get_attestation(event_id)
+            })
+    }
+}
+
+struct Cache<T> {
+    entries: Mutex<HashMap<String, Arc<Mutex<Option<CacheEntry<T>>>>>>,
+}
+
+struct CacheEntry<T> {
+    fetched_at: Instant,
+    /// The fetched value, or the error message if fetching failed.
+    result: Result<T, String>,
+}
+
+impl<T> CacheEntry<T> {
+    fn is_expired(&self, ttl: Duration, negative_ttl: Duration) -> bool {
+        let ttl = match self.result {
+            Ok(_) => ttl,
+            Err(_) => negative_ttl,
+        };
+
+        self.fetched_at.elapsed() >= ttl
+    }
+}
+
+/// Whether a cached entry can be dropped. Entries which are still being fetched are kept.
+fn entry_expired<T>(
+    entry: &Mutex<Option<CacheEntry<T>>>,
+    ttl: Duration,
+    negative_ttl: Duration,
+) -> bool {
+    match entry.try_lock() {
+        Some(entry) => match entry.as_ref() {
+            Some(entry) => entry.is_expired(ttl, negative_ttl),
+            // Nothing was fetched, e.g. because the fetching request was dropped.
+            None => true,
+        },
+        None => false,
+    }
+}
+
+impl<T> Default for Cache<T> {
+    fn default() -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<T: Clone> Cache<T> {
+    fn get_or_fetch(
+        &self,
+        event_id: &str,
+        ttl: Duration,
+        negative_ttl: Duration,
+        fetch: impl FnOnce() -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let entry = {
+            let mut entries = self.entries.lock();
+
+            if !entries.contains_key(event_id) {
+                // Drop expired entries whenever a new event is cached, so that the cache does not
+                // grow with every event we have ever asked the oracle about.
+                entries.retain(|_, entry| !entry_expired(entry, ttl, negative_ttl));
+            }
+
+            entries.entry(event_id.to_string()).or_default().clone()
+        };
+
+        // Concurrent requests for the same event wait here until the first one has fetched it.
+        let mut entry = entry.lock();
+
+        if let Some(entry) = entry.as_ref() {
+            if !entry.is_expired(ttl, negative_ttl) {
+                return entry.result.clone().map_err(Error::OracleError);
+            }
+        }
+
+        let result = fetch().map_err(|e| e.to_string());
+
+        *entry = Some(CacheEntry {
+            fetched_at: Instant::now(),
+            result: result.clone(),
+        });
+
+        result.map_err(Error::OracleError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin_old::secp256k1::KeyPair;
+    use bitcoin_old::secp256k1::SecretKey;
+    use dlc_messages::oracle_msgs::EnumEventDescriptor;
+    use dlc_messages::oracle_msgs::EventDescriptor;
+    use dlc_messages::oracle_msgs::OracleEvent;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+
+    const TTL: Duration = Duration::from_secs(60);
+    const NEGATIVE_TTL: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn concurrent_requests_share_one_fetch() {
+        let cache = Cache::default();
+        let n_fetches = AtomicUsize::new(0);
+
+        let fetch = || {
+            n_fetches.fetch_add(1, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(100));
+            Ok(42)
+        };
+
+        std::thread::scope(|s| {
+            let requests = (0..10)
+                .map(|_| s.spawn(|| cache.get_or_fetch("btcusd1", TTL, NEGATIVE_TTL, fetch)))
+                .collect::<Vec<_>>();
+
+            for request in requests {
+                assert_eq!(request.join().unwrap().unwrap(), 42);
+            }
+        });
+
+        assert_eq!(n_fetches.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn failed_fetch_is_cached_for_negative_ttl() {
+        let cache = Cache::<u32>::default();
+        let n_fetches = AtomicUsize::new(0);
+
+        let fetch = || {
+            n_fetches.fetch_add(1, Ordering::SeqCst);
+            Err(Error::OracleError("Not yet attested".to_string()))
+        };
+
+        assert!(cache
+            .get_or_fetch("btcusd1", TTL, NEGATIVE_TTL, fetch)
+            .is_err());
+        assert!(cache
+            .get_or_fetch("btcusd1", TTL, NEGATIVE_TTL, fetch)
+            .is_err());
+        assert_eq!(n_fetches.load(Ordering::SeqCst), 1);
+
+        // Once the negative TTL has passed, we ask the oracle again.
+        assert!(cache
+            .get_or_fetch("btcusd1", TTL, Duration::ZERO, fetch)
+            .is_err());
+        assert_eq!(n_fetches.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn expired_entries_are_pruned_when_caching_a_new_event() {
+        let cache = Cache::default();
+
+        cache
+            .get_or_fetch("btcusd1", TTL, NEGATIVE_TTL, || Ok(1))
+            .unwrap();
+        cache
+            .get_or_fetch("btcusd2", TTL, NEGATIVE_TTL, || Ok(2))
+            .unwrap();
+        assert_eq!(cache.entries.lock().len(), 2);
+
+        // Both entries have expired by the time we cache the next event.
+        cache
+            .get_or_fetch("btcusd3", Duration::ZERO, Duration::ZERO, || Ok(3))
+            .unwrap();
+
+        let entries = cache.entries.lock();
+        assert_eq!(entries.len(), 1);
+        assert!(entries.contains_key("btcusd3"));
+    }
+
+    #[test]
+    fn attestation_must_be_signed_with_announced_nonce() {
+        let announcement = announcement(ORACLE_KEY);
+
+        assert!(verify_attestation(&announcement, &attestation(ORACLE_KEY, "up")).is_ok());
+
+        let mut forged = attestation(ORACLE_KEY, "up");
+        forged.outcomes = vec!["down".to_string()];
+        assert!(verify_attestation(&announcement, &forged).is_err());
+    }
+
+    #[test]
+    fn attestation_must_be_signed_by_announcing_oracle() {
+        let announcement = announcement(ORACLE_KEY);
+
+        assert!(verify_attestation(&announcement, &attestation(OTHER_ORACLE_KEY, "up")).is_err());
+    }
+
+    #[test]
+    fn unverified_attestation_is_rejected() {
+        let oracle = CachedOracle::new(
+            StaticOracle {
+                announcement: announcement(ORACLE_KEY),
+                attestation: attestation(OTHER_ORACLE_KEY, "up"),
+            },
+            TTL,
+            NEGATIVE_TTL,
+        );
+
+        assert!(oracle.get_verified_attestation("btcusd1").is_err());
+
+        let oracle = CachedOracle::new(
+            StaticOracle {
+                announcement: announcement(ORACLE_KEY),
+                attestation: attestation(ORACLE_KEY, "up"),
+            },
+            TTL,
+            NEGATIVE_TTL,
+        );
+
+        assert_eq!(
+            oracle.get_verified_attestation("btcusd1").unwrap().outcomes,
+            vec!["up".to_string()]
+        );
+    }
+
+    const ORACLE_KEY: [u8; 32] = [1; 32];
+    const OTHER_ORACLE_KEY: [u8; 32] = [3; 32];
+    const NONCE: [u8; 32] = [2; 32];
+
+    struct StaticOracle {
+        announcement: OracleAnnouncement,
+        attestation: OracleAttestation,
+    }
+
+    impl Oracle for StaticOracle {
+        fn get_public_key(&self) -> bitcoin_old::XOnlyPublicKey {
+            self.announcement.oracle_public_key
+        }
+
+        fn get_announcement(&self, _: &str) -> Result<OracleAnnouncement, Error> {
+            Ok(self.announcement.clone())
+        }
+
+        fn get_attestation(&self, _: &str) -> Result<OracleAttestation, Error> {
+            Ok(self.attestation.clone())
+        }
+    }
+
+    fn announcement(oracle_key: [u8; 32]) -> OracleAnnouncement {
+        let secp = Secp256k1::new();
+        let oracle = KeyPair::from_seckey_slice(&secp, &oracle_key).unwrap();
+        let nonce = SecretKey::from_slice(&NONCE).unwrap();
+        let (nonce_pk, _) = nonce.x_only_public_key(&secp);
+
+        OracleAnnouncement {
+            announcement_signature: secp.sign_schnorr_no_aux_rand(
+                &Message::from_hashed_data::<sha256::Hash>(b"announcement"),
+                &oracle,
+            ),
+            oracle_public_key: oracle.x_only_public_key().0,
+            oracle_event: OracleEvent {
+                oracle_nonces: vec![nonce_pk],
+                event_maturity_epoch: 0,
+                event_descriptor: EventDescriptor::EnumEvent(EnumEventDescriptor {
+                    outcomes: vec!["up".to_string(), "down".to_string()],
+                }),
+                event_id: "btcusd1".to_string(),
+            },
+        }
+    }
+
+    fn attestation(oracle_key: [u8; 32], outcome: &str) -> OracleAttestation {
+        let secp = Secp256k1::new();
+        let oracle = KeyPair::from_seckey_slice(&secp, &oracle_key).unwrap();
+
+        OracleAttestation {
+            oracle_public_key: oracle.x_only_public_key().0,
+            signatures: vec![dlc::secp_utils::schnorrsig_sign_with_nonce(
+                &secp,
+                &Message::from_hashed_data::<sha256::Hash>(outcome.as_bytes()),
+                &oracle,
+                &NONCE,
+            )],
+            outcomes: vec![outcome.to_string()],
+        }
+    }
 }
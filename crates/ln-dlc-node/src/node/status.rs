@@ -0,0 +1,164 @@
+use crate::node::Node;
+use crate::node::Storage;
+use crate::on_chain_wallet::BdkStorage;
+use crate::storage::TenTenOneStorage;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use time::OffsetDateTime;
+
+/// When the wallets were last synced successfully.
+#[derive(Debug, Default)]
+pub(crate) struct LastSyncs {
+    pub on_chain: Option<OffsetDateTime>,
+    pub lightning: Option<OffsetDateTime>,
+}
+
+/// When a periodic task last ran successfully.
+#[derive(Debug, Default)]
+pub(crate) struct Heartbeat {
+    /// Unix timestamp in seconds, 0 if the task never succeeded.
+    last_success: AtomicU64,
+}
+
+impl Heartbeat {
+    /// Advance the heartbeat if the task succeeded and pass on the result.
+    pub fn track<T, E>(&self, result: Result<T, E>) -> Result<T, E> {
+        if result.is_ok() {
+            let now = OffsetDateTime::now_utc().unix_timestamp() as u64;
+            self.last_success.store(now, Ordering::Relaxed);
+        }
+
+        result
+    }
+
+    fn last_success(&self) -> Option<OffsetDateTime> {
+        match self.last_success.load(Ordering::Relaxed) {
+            0 => None,
+            timestamp => OffsetDateTime::from_unix_timestamp(timestamp as i64).ok(),
+        }
+    }
+}
+
+/// The heartbeats of the node's periodic tasks.
+#[derive(Debug, Default)]
+pub(crate) struct TaskHeartbeats {
+    pub lightning_wallet_sync: Arc<Heartbeat>,
+    pub shadow_sync: Arc<Heartbeat>,
+    pub fee_rate_update: Arc<Heartbeat>,
+    pub sub_channel_manager_periodic_check: Arc<Heartbeat>,
+    pub manage_spendable_outputs: Arc<Heartbeat>,
+}
+
+/// A snapshot of the node's health.
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeStatus {
+    /// Whether the on-chain wallet has caught up with the chain tip.
+    pub synced: bool,
+    pub n_peers: usize,
+    pub n_usable_channels: usize,
+    /// The height of the latest block known to the on-chain wallet.
+    pub wallet_height: u32,
+    /// The height of the chain tip according to Esplora. Not set if Esplora cannot be reached.
+    pub chain_tip: Option<u32>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_on_chain_sync: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_lightning_sync: Option<OffsetDateTime>,
+}
+
+impl<D: BdkStorage, S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 'static>
+    Node<D, S, N>
+{
+    pub async fn status(&self) -> NodeStatus {
+        let chain_tip = match self.blockchain.esplora_client_async.get_height().await {
+            Ok(height) => Some(height),
+            Err(e) => {
+                tracing::warn!("Failed to get chain tip: {e:#}");
+                None
+            }
+        };
+        let wallet_height = self.wallet.get_tip();
+
+        let (last_on_chain_sync, last_lightning_sync) = {
+            let last_syncs = self.last_syncs.lock();
+            (last_syncs.on_chain, last_syncs.lightning)
+        };
+
+        NodeStatus {
+            synced: is_synced(wallet_height, chain_tip),
+            n_peers: self.list_peers().len(),
+            n_usable_channels: self.channel_manager.list_usable_channels().len(),
+            wallet_height,
+            chain_tip,
+            last_on_chain_sync,
+            last_lightning_sync,
+        }
+    }
+}
+
+impl<D: BdkStorage, S: TenTenOneStorage, N: Storage> Node<D, S, N> {
+    /// When each periodic task last succeeded. Tasks which have not succeeded yet are omitted.
+    ///
+    /// A task which keeps failing stops advancing its heartbeat, which can be used to alert on
+    /// e.g. a stalled sync.
+    pub fn task_heartbeats(&self) -> HashMap<&'static str, OffsetDateTime> {
+        let TaskHeartbeats {
+            lightning_wallet_sync,
+            shadow_sync,
+            fee_rate_update,
+            sub_channel_manager_periodic_check,
+            manage_spendable_outputs,
+        } = &self.heartbeats;
+
+        [
+            ("lightning_wallet_sync", lightning_wallet_sync),
+            ("shadow_sync", shadow_sync),
+            ("fee_rate_update", fee_rate_update),
+            (
+                "sub_channel_manager_periodic_check",
+                sub_channel_manager_periodic_check,
+            ),
+            ("manage_spendable_outputs", manage_spendable_outputs),
+        ]
+        .into_iter()
+        .filter_map(|(task, heartbeat)| Some((task, heartbeat.last_success()?)))
+        .collect()
+    }
+}
+
+fn is_synced(wallet_height: u32, chain_tip: Option<u32>) -> bool {
+    chain_tip.is_some_and(|chain_tip| wallet_height >= chain_tip)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn synced_only_if_wallet_caught_up_with_chain_tip() {
+        assert!(is_synced(100, Some(100)));
+        assert!(!is_synced(99, Some(100)));
+        assert!(!is_synced(100, None));
+    }
+
+    #[test]
+    fn failing_task_does_not_advance_heartbeat() {
+        let heartbeat = Heartbeat::default();
+        let failed_run: Result<(), &str> = Err("esplora unreachable");
+
+        assert!(heartbeat.track(failed_run).is_err());
+        assert_eq!(heartbeat.last_success(), None);
+
+        let earlier = OffsetDateTime::from_unix_timestamp(1).unwrap();
+        heartbeat.last_success.store(1, Ordering::Relaxed);
+
+        assert!(heartbeat.track(failed_run).is_err());
+        assert_eq!(heartbeat.last_success(), Some(earlier));
+
+        assert!(heartbeat.track(Ok::<_, ()>(())).is_ok());
+        assert!(heartbeat.last_success().unwrap() > earlier);
+    }
+}
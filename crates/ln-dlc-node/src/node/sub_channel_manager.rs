@@ -3,6 +3,7 @@ use crate::dlc_wallet::DlcWallet;
 use crate::fee_rate_estimator::FeeRateEstimator;
 use crate::node::channel_manager::ChannelManager;
 use crate::node::dlc_manager::DlcManager;
+use crate::node::oracle::CachedOracle;
 use crate::node::Storage;
 use crate::on_chain_wallet::BdkStorage;
 use crate::storage::TenTenOneStorage;
@@ -21,7 +22,7 @@ pub type SubChannelManager<D, S, N> = sub_channel_manager::SubChannelManager<
     Arc<ChainMonitor<S, N>>,
     Arc<DlcStorageProvider<S>>,
     Arc<DlcWallet<D, S, N>>,
-    Arc<P2PDOracleClient>,
+    Arc<CachedOracle<P2PDOracleClient>>,
     Arc<SystemTimeProvider>,
     Arc<FeeRateEstimator>,
     Arc<DlcManager<D, S, N>>,
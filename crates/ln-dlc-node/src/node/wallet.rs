@@ -5,6 +5,7 @@ use crate::on_chain_wallet;
 use crate::on_chain_wallet::BdkStorage;
 use crate::on_chain_wallet::OnChainWallet;
 use crate::on_chain_wallet::TransactionDetails;
+use crate::on_chain_wallet::Utxo;
 use crate::storage::TenTenOneStorage;
 use anyhow::Context;
 use anyhow::Result;
@@ -17,6 +18,7 @@ use bitcoin::ScriptBuf;
 use bitcoin::TxOut;
 use lightning::chain::chaininterface::ConfirmationTarget;
 use std::sync::Arc;
+use time::OffsetDateTime;
 use tokio::task::spawn_blocking;
 
 /// The number of parallel requests to be used during the on-chain sync.
@@ -59,6 +61,11 @@ impl<D: BdkStorage, S: TenTenOneStorage, N: Storage + Send + Sync + 'static> Nod
         self.wallet.get_utxos()
     }
 
+    /// List the unspent outputs of the on-chain wallet, largest first.
+    pub fn list_utxos(&self) -> Vec<Utxo> {
+        self.wallet.list_utxos()
+    }
+
     pub fn is_mine(&self, script_pubkey: &ScriptBuf) -> bool {
         self.wallet.is_mine(script_pubkey)
     }
@@ -120,6 +127,8 @@ impl<D: BdkStorage, S: TenTenOneStorage, N: Storage + Send + Sync + 'static> Nod
         .await
         .expect("task to complete")?;
 
+        self.last_syncs.lock().on_chain = Some(OffsetDateTime::now_utc());
+
         Ok(())
     }
 
@@ -167,6 +176,8 @@ impl<D: BdkStorage, S: TenTenOneStorage, N: Storage + Send + Sync + 'static> Nod
         .await
         .expect("task to complete")?;
 
+        self.last_syncs.lock().on_chain = Some(OffsetDateTime::now_utc());
+
         Ok(())
     }
 }
@@ -4,6 +4,7 @@ use crate::node::Fee;
 use crate::seed::WalletSeed;
 use anyhow::anyhow;
 use anyhow::bail;
+use anyhow::Context;
 use anyhow::Result;
 use bdk::chain::indexed_tx_graph::Indexer;
 use bdk::chain::local_chain::LocalChain;
@@ -11,9 +12,11 @@ use bdk::chain::tx_graph::CalculateFeeError;
 use bdk::chain::tx_graph::CanonicalTx;
 use bdk::chain::Append;
 use bdk::chain::ChainPosition;
+use bdk::chain::ConfirmationTime;
 use bdk::chain::PersistBackend;
 use bdk::psbt::PsbtUtils;
 use bdk::wallet::IsDust;
+use bdk::FeeRate;
 use bdk::KeychainKind;
 use bdk::LocalOutput;
 use bdk::SignOptions;
@@ -91,6 +94,28 @@ impl<D> OnChainWallet<D> {
         self.bdk.read().list_unspent().collect()
     }
 
+    /// List the wallet's unspent outputs, largest first.
+    pub fn list_utxos(&self) -> Vec<Utxo> {
+        let tip = self.get_tip();
+
+        let mut utxos = self
+            .list_unspent()
+            .into_iter()
+            .map(|output| Utxo {
+                outpoint: output.outpoint,
+                amount: Amount::from_sat(output.txout.value),
+                script_pubkey: output.txout.script_pubkey,
+                keychain: output.keychain,
+                derivation_index: output.derivation_index,
+                n_confirmations: n_confirmations(&output.confirmation_time, tip),
+            })
+            .collect::<Vec<_>>();
+
+        utxos.sort_by(|a, b| b.amount.cmp(&a.amount));
+
+        utxos
+    }
+
     pub(crate) fn unreserve_utxos(&self, outpoints: &[bitcoin_old::OutPoint]) {
         self.locked_utxos
             .lock()
@@ -343,6 +368,9 @@ where
             builder.drain_wallet().drain_to(script_pubkey);
         }
 
+        // Signal replaceability, so that the transaction can be fee-bumped if it gets stuck.
+        builder.enable_rbf();
+
         let fee_rate = match fee {
             Fee::Priority(target) => self.fee_rate_estimator.get(target),
             Fee::FeeRate(fee_rate) => fee_rate,
@@ -376,6 +404,64 @@ where
         Ok(psbt)
     }
 
+    /// Build and sign a transaction replacing the unconfirmed wallet transaction `txid`, paying
+    /// the given `fee_rate`.
+    pub(crate) fn build_fee_bump_tx(&self, txid: Txid, fee_rate: FeeRate) -> Result<Transaction> {
+        ensure_fee_bumpable(txid, &self.get_confirmation_status(&txid))?;
+
+        // The replacement spends the inputs of the original transaction, which are locked if we
+        // built it, so only the remaining locked UTXOs have to be excluded.
+        let original_inputs = self
+            .get_transaction(&txid)
+            .context("Unknown transaction")?
+            .input
+            .iter()
+            .map(|input| input.previous_output)
+            .collect::<Vec<_>>();
+
+        let wallet = &mut self.bdk.write();
+
+        let mut builder = wallet.build_fee_bump(txid).map_err(|e| anyhow!("{e:?}"))?;
+        builder.fee_rate(fee_rate);
+
+        let mut locked_utxos = self.locked_utxos.lock();
+        for outpoint in locked_utxos.iter() {
+            if !original_inputs.contains(outpoint) {
+                builder.add_unspendable(*outpoint);
+            }
+        }
+
+        let mut psbt = builder.finish().map_err(|e| anyhow!("{e:?}"))?;
+
+        let finalized = wallet
+            .sign(&mut psbt, SignOptions::default())
+            .map_err(|e| anyhow!("{e:?}"))?;
+
+        if !finalized {
+            bail!("PSBT not finalized");
+        }
+
+        let tx = psbt.extract_tx();
+
+        // Inputs added to pay for the higher fee must not be spent by another transaction either.
+        let added_inputs = tx
+            .input
+            .iter()
+            .map(|input| input.previous_output)
+            .filter(|outpoint| !locked_utxos.contains(outpoint))
+            .collect::<Vec<_>>();
+        locked_utxos.extend(added_inputs);
+
+        tracing::info!(
+            original_txid = %txid,
+            txid = %tx.txid(),
+            ?fee_rate,
+            "Built fee bump transaction"
+        );
+
+        Ok(tx)
+    }
+
     /// Estimate the fee for sending funds to a given [`Address`].
     pub fn estimate_fee(
         &self,
@@ -435,6 +521,26 @@ impl TransactionDetails {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct Utxo {
+    pub outpoint: OutPoint,
+    pub amount: Amount,
+    pub script_pubkey: ScriptBuf,
+    /// Whether the output belongs to the external or the internal (change) keychain.
+    pub keychain: KeychainKind,
+    /// The index of the output's script in its keychain.
+    pub derivation_index: u32,
+    pub n_confirmations: u32,
+}
+
+fn n_confirmations(confirmation_time: &ConfirmationTime, tip: u32) -> u32 {
+    match confirmation_time {
+        // Being included in a block counts as a confirmation!
+        ConfirmationTime::Confirmed { height, .. } => tip.saturating_sub(*height) + 1,
+        ConfirmationTime::Unconfirmed { .. } => 0,
+    }
+}
+
 #[derive(Debug)]
 pub enum ConfirmationStatus {
     Unknown,
@@ -458,6 +564,18 @@ impl ConfirmationStatus {
     }
 }
 
+fn ensure_fee_bumpable(txid: Txid, confirmation_status: &ConfirmationStatus) -> Result<()> {
+    match confirmation_status {
+        ConfirmationStatus::Mempool { .. } => Ok(()),
+        ConfirmationStatus::Confirmed { .. } => {
+            bail!("Cannot bump fee of transaction {txid}: already confirmed")
+        }
+        ConfirmationStatus::Unknown => {
+            bail!("Cannot bump fee of transaction {txid}: not found in wallet")
+        }
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum EstimateFeeError {
     #[error("Cannot estimate fee for output below dust")]
@@ -503,3 +621,55 @@ impl PersistBackend<bdk::wallet::ChangeSet> for InMemoryStorage {
         Ok(self.0.clone())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn fee_bump_rejects_confirmed_transaction() {
+        let confirmation_status = ConfirmationStatus::Confirmed {
+            n_confirmations: NonZeroU32::new(1).unwrap(),
+            timestamp: OffsetDateTime::now_utc(),
+        };
+
+        assert!(ensure_fee_bumpable(dummy_txid(), &confirmation_status).is_err());
+    }
+
+    #[test]
+    fn fee_bump_rejects_unknown_transaction() {
+        assert!(ensure_fee_bumpable(dummy_txid(), &ConfirmationStatus::Unknown).is_err());
+    }
+
+    #[test]
+    fn fee_bump_accepts_unconfirmed_transaction() {
+        let confirmation_status = ConfirmationStatus::Mempool {
+            last_seen: OffsetDateTime::now_utc(),
+        };
+
+        assert!(ensure_fee_bumpable(dummy_txid(), &confirmation_status).is_ok());
+    }
+
+    #[test]
+    fn utxo_confirmations() {
+        let unconfirmed = ConfirmationTime::Unconfirmed { last_seen: 0 };
+        assert_eq!(n_confirmations(&unconfirmed, 100), 0);
+
+        let confirmed_at_tip = ConfirmationTime::Confirmed {
+            height: 100,
+            time: 0,
+        };
+        assert_eq!(n_confirmations(&confirmed_at_tip, 100), 1);
+
+        let confirmed_earlier = ConfirmationTime::Confirmed {
+            height: 90,
+            time: 0,
+        };
+        assert_eq!(n_confirmations(&confirmed_earlier, 100), 11);
+    }
+
+    fn dummy_txid() -> Txid {
+        Txid::from_str("e3a1b5a4d2f7ab1b67b9c4c9a1d2e4f5a6b7c8d9e0f1a2b3c4d5e6f7a8b9c0d1").unwrap()
+    }
+}
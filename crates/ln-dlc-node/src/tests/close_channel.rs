@@ -0,0 +1,21 @@
+use crate::node::Node;
+use crate::tests::init_tracing;
+use lightning::ln::ChannelId;
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore]
+async fn closing_unknown_channel_fails() {
+    init_tracing();
+
+    let (app, _running_app) = Node::start_test_app("app").unwrap();
+    let (coordinator, _running_coordinator) = Node::start_test_coordinator("coordinator").unwrap();
+
+    let unknown_channel_id = ChannelId([1; 32]);
+
+    assert!(app
+        .close_channel(unknown_channel_id, coordinator.info.pubkey)
+        .is_err());
+    assert!(app
+        .force_close_channel(unknown_channel_id, coordinator.info.pubkey)
+        .is_err());
+}
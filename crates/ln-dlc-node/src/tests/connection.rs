@@ -0,0 +1,38 @@
+use crate::node::Node;
+use crate::tests::init_tracing;
+use crate::tests::wait_until;
+use std::time::Duration;
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore]
+async fn connect_peer_is_idempotent_and_disconnect_peer_disconnects() {
+    init_tracing();
+
+    let (app, _running_app) = Node::start_test_app("app").unwrap();
+    let (coordinator, _running_coordinator) = Node::start_test_coordinator("coordinator").unwrap();
+
+    app.connect_peer(coordinator.info).await.unwrap();
+    assert!(app.is_connected(coordinator.info.pubkey));
+
+    // Connecting again returns right away without setting up a second connection.
+    tokio::time::timeout(
+        Duration::from_millis(100),
+        app.connect_peer(coordinator.info),
+    )
+    .await
+    .expect("to return without reconnecting")
+    .unwrap();
+    let peers = app.list_peers();
+    assert_eq!(peers.len(), 1);
+    assert_eq!(peers[0].pubkey, coordinator.info.pubkey);
+    assert!(!peers[0].inbound);
+    assert!(peers[0].supports_dlc);
+
+    app.disconnect_peer(coordinator.info.pubkey);
+
+    wait_until(Duration::from_secs(5), || async {
+        Ok((!app.is_connected(coordinator.info.pubkey)).then_some(()))
+    })
+    .await
+    .unwrap();
+}
@@ -1,5 +1,6 @@
 use crate::bitcoin_conversion::to_secp_pk_29;
 use crate::node::dlc_channel::estimated_dlc_channel_fee_reserve;
+use crate::node::dlc_channel::ForceCloseDlcChannelError;
 use crate::node::InMemoryStore;
 use crate::node::Node;
 use crate::node::RunningNode;
@@ -260,6 +261,16 @@ async fn can_open_and_force_close_settled_channel() {
     })
     .await
     .unwrap();
+
+    // The channel is already closing, so it cannot be force-closed again.
+    assert!(matches!(
+        coordinator.force_close_dlc_channel(coordinator_signed_channel.channel_id),
+        Err(ForceCloseDlcChannelError::WrongState(_))
+    ));
+    assert!(matches!(
+        coordinator.force_close_dlc_channel([0; 32]),
+        Err(ForceCloseDlcChannelError::UnknownChannel)
+    ));
 }
 
 #[tokio::test(flavor = "multi_thread")]
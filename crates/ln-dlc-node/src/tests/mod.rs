@@ -1,4 +1,3 @@
-use crate::bitcoin_conversion::to_secp_pk_29;
 use crate::bitcoin_conversion::to_xonly_pk_29;
 use crate::config::app_config;
 use crate::config::coordinator_config;
@@ -57,7 +56,11 @@ use tokio::task::block_in_place;
 use uuid::Uuid;
 
 mod bitcoind;
+mod close_channel;
+mod connection;
 mod dlc_channel;
+mod shutdown;
+mod sweep;
 
 const ELECTRS_ORIGIN: &str = "http://localhost:3000";
 const FAUCET_ORIGIN: &str = "http://localhost:8080";
@@ -285,8 +288,7 @@ impl Node<on_chain_wallet::InMemoryStorage, TenTenOneInMemoryStorage, InMemorySt
     }
 
     pub fn disconnect(&self, peer: NodeInfo) {
-        self.peer_manager
-            .disconnect_by_node_id(to_secp_pk_29(peer.pubkey))
+        self.disconnect_peer(peer.pubkey)
     }
 
     pub async fn reconnect(&self, peer: NodeInfo) -> Result<()> {
@@ -415,6 +417,8 @@ fn ln_dlc_node_settings_coordinator() -> LnDlcNodeSettings {
         fee_rate_sync_interval: Duration::from_secs(20),
         sub_channel_manager_periodic_check_interval: Duration::from_secs(30),
         shadow_sync_interval: Duration::from_secs(600),
+        oracle_cache_ttl: Duration::from_secs(60),
+        oracle_negative_cache_ttl: Duration::from_secs(5),
     }
 }
 
@@ -425,6 +429,8 @@ fn ln_dlc_node_settings_app() -> LnDlcNodeSettings {
         fee_rate_sync_interval: Duration::from_secs(20),
         sub_channel_manager_periodic_check_interval: Duration::from_secs(30),
         shadow_sync_interval: Duration::from_secs(600),
+        oracle_cache_ttl: Duration::from_secs(60),
+        oracle_negative_cache_ttl: Duration::from_secs(5),
     }
 }
 
@@ -0,0 +1,31 @@
+use crate::node::Node;
+use crate::tests::init_tracing;
+use crate::tests::wait_until;
+use std::net::TcpListener;
+use std::time::Duration;
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore]
+async fn shutdown_releases_listening_socket() {
+    init_tracing();
+
+    let (node, running) = Node::start_test_app("app").unwrap();
+    let address = node.info.address;
+
+    // The node is listening on the address.
+    wait_until(Duration::from_secs(5), || async {
+        Ok(TcpListener::bind(address).is_err().then_some(()))
+    })
+    .await
+    .unwrap();
+
+    running.shutdown().await.unwrap();
+
+    // The listening task is stopped in the background, so the socket may not be released right
+    // away.
+    wait_until(Duration::from_secs(5), || async {
+        Ok(TcpListener::bind(address).ok())
+    })
+    .await
+    .unwrap();
+}
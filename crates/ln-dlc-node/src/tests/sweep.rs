@@ -0,0 +1,54 @@
+use crate::node::Fee;
+use crate::node::Node;
+use crate::tests::bitcoind;
+use crate::tests::init_tracing;
+use crate::tests::wait_until;
+use bdk::FeeRate;
+use bitcoin::Amount;
+use std::time::Duration;
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore]
+async fn sweep_sends_all_on_chain_funds() {
+    init_tracing();
+
+    let (app, _running_app) = Node::start_test_app("app").unwrap();
+    let (coordinator, _running_coordinator) = Node::start_test_coordinator("coordinator").unwrap();
+
+    app.fund(Amount::from_sat(100_000), 2).await.unwrap();
+
+    let address = coordinator.wallet.get_unused_address().unwrap();
+    app.sweep_on_chain(
+        address.as_unchecked().clone(),
+        Fee::FeeRate(FeeRate::from_sat_per_vb(1.0)),
+    )
+    .await
+    .unwrap();
+
+    bitcoind::mine(1).await.unwrap();
+
+    let received = wait_until(Duration::from_secs(30), || async {
+        coordinator.sync_wallets().await?;
+        let confirmed = coordinator.get_on_chain_balance().confirmed;
+
+        Ok((confirmed > 0).then_some(confirmed))
+    })
+    .await
+    .unwrap();
+
+    // Both inputs are spent into a single output, so only the fee is deducted.
+    assert!(received < 100_000);
+    assert!(received > 99_000);
+
+    app.sync_wallets().await.unwrap();
+    assert_eq!(app.get_on_chain_balance().total(), 0);
+
+    // Nothing is left to sweep.
+    assert!(app
+        .sweep_on_chain(
+            address.as_unchecked().clone(),
+            Fee::FeeRate(FeeRate::from_sat_per_vb(1.0)),
+        )
+        .await
+        .is_err());
+}
@@ -71,6 +71,7 @@ async fn subscribe_impl(
                     fcm_token,
                     version,
                     signature,
+                    cancel_on_disconnect: false,
                 },
             )?)
             .await;
@@ -60,6 +60,21 @@ pub fn calculate_short_liquidation_price(leverage: Decimal, price: Decimal) -> D
     price * leverage / (leverage - Decimal::ONE)
 }
 
+/// Calculate the price at which the margin of a position opened at `entry_price` is exhausted.
+///
+/// Like the coordinator, we do not require a maintenance margin: the position is liquidated once
+/// its losses have consumed all of its margin.
+pub fn calculate_liquidation_price(
+    entry_price: Decimal,
+    leverage: Decimal,
+    direction: Direction,
+) -> Decimal {
+    match direction {
+        Direction::Long => calculate_long_liquidation_price(leverage, entry_price),
+        Direction::Short => calculate_short_liquidation_price(leverage, entry_price),
+    }
+}
+
 /// Compute the payout for the given CFD parameters at a particular `closing_price`.
 ///
 /// The `opening_price` of the position is the weighted opening price per quantity.
@@ -116,10 +131,256 @@ pub fn calculate_pnl(
     pnl.to_i64().context("to be able to convert into i64")
 }
 
+/// Compute the PnL of [`calculate_pnl`] net of the fees paid for opening and closing the
+/// position.
+#[allow(clippy::too_many_arguments)]
+pub fn calculate_pnl_with_fees(
+    opening_price: Decimal,
+    closing_price: Decimal,
+    quantity: f32,
+    direction: Direction,
+    initial_margin_long: u64,
+    initial_margin_short: u64,
+    opening_fee_sats: u64,
+    closing_fee_sats: u64,
+) -> Result<i64> {
+    let pnl = calculate_pnl(
+        opening_price,
+        closing_price,
+        quantity,
+        direction,
+        initial_margin_long,
+        initial_margin_short,
+    )?;
+
+    let fees = opening_fee_sats
+        .checked_add(closing_fee_sats)
+        .and_then(|fees| i64::try_from(fees).ok())
+        .context("fees to fit into i64")?;
+
+    pnl.checked_sub(fees).context("net pnl to fit into i64")
+}
+
+/// Compute the PnL of [`calculate_pnl`] as a percentage of the initial margin of the party going
+/// in `direction`.
+///
+/// A fully liquidated position returns -100%. If the initial margin is zero, there is nothing to
+/// win or lose and zero is returned.
+pub fn calculate_pnl_percent(
+    opening_price: Decimal,
+    closing_price: Decimal,
+    quantity: f32,
+    direction: Direction,
+    initial_margin_long: u64,
+    initial_margin_short: u64,
+) -> Result<Decimal> {
+    let pnl = calculate_pnl(
+        opening_price,
+        closing_price,
+        quantity,
+        direction,
+        initial_margin_long,
+        initial_margin_short,
+    )?;
+
+    let initial_margin = match direction {
+        Direction::Long => initial_margin_long,
+        Direction::Short => initial_margin_short,
+    };
+
+    if initial_margin == 0 {
+        return Ok(Decimal::ZERO);
+    }
+
+    let pnl = Decimal::from(pnl);
+    let initial_margin = Decimal::from(initial_margin);
+
+    Ok(pnl / initial_margin * dec!(100))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn pnl_with_fees_is_gross_pnl_minus_fees() {
+        let opening_price = dec!(20_000);
+        let quantity = 20_000.0;
+        let long_margin = calculate_margin(opening_price, quantity, 2.0);
+        let short_margin = calculate_margin(opening_price, quantity, 1.0);
+
+        for (closing_price, direction) in [
+            (dec!(22_000), Direction::Long),
+            (dec!(22_000), Direction::Short),
+            (dec!(18_000), Direction::Long),
+            (dec!(20_000), Direction::Short),
+        ] {
+            let gross_pnl = calculate_pnl(
+                opening_price,
+                closing_price,
+                quantity,
+                direction,
+                long_margin,
+                short_margin,
+            )
+            .unwrap();
+
+            let net_pnl = calculate_pnl_with_fees(
+                opening_price,
+                closing_price,
+                quantity,
+                direction,
+                long_margin,
+                short_margin,
+                30_000,
+                27_273,
+            )
+            .unwrap();
+
+            assert_eq!(net_pnl, gross_pnl - 30_000 - 27_273);
+        }
+    }
+
+    #[test]
+    fn pnl_with_fees_of_liquidated_position_loses_more_than_margin() {
+        let opening_price = dec!(20_000);
+        let quantity = 20_000.0;
+        let long_margin = calculate_margin(opening_price, quantity, 2.0);
+        let short_margin = calculate_margin(opening_price, quantity, 1.0);
+
+        let net_pnl = calculate_pnl_with_fees(
+            opening_price,
+            dec!(10_000),
+            quantity,
+            Direction::Long,
+            long_margin,
+            short_margin,
+            30_000,
+            60_000,
+        )
+        .unwrap();
+
+        assert_eq!(net_pnl, -(long_margin as i64) - 90_000);
+    }
+
+    #[test]
+    fn pnl_percent_long_winning() {
+        let opening_price = dec!(20_000);
+        let closing_price = dec!(22_000);
+        let quantity = 20_000.0;
+        let long_margin = calculate_margin(opening_price, quantity, 2.0);
+        let short_margin = calculate_margin(opening_price, quantity, 1.0);
+
+        let pnl_percent = calculate_pnl_percent(
+            opening_price,
+            closing_price,
+            quantity,
+            Direction::Long,
+            long_margin,
+            short_margin,
+        )
+        .unwrap();
+
+        // The contract is inverse, so the price going up by 10% only gains ~9.09% in BTC, which
+        // is ~18.18% of the margin with leverage 2.
+        assert_eq!(pnl_percent.round_dp(2), dec!(18.18));
+    }
+
+    #[test]
+    fn pnl_percent_short_losing() {
+        let opening_price = dec!(20_000);
+        let closing_price = dec!(22_000);
+        let quantity = 20_000.0;
+        let long_margin = calculate_margin(opening_price, quantity, 1.0);
+        let short_margin = calculate_margin(opening_price, quantity, 5.0);
+
+        let pnl_percent = calculate_pnl_percent(
+            opening_price,
+            closing_price,
+            quantity,
+            Direction::Short,
+            long_margin,
+            short_margin,
+        )
+        .unwrap();
+
+        assert_eq!(pnl_percent.round_dp(2), dec!(-45.45));
+    }
+
+    #[test]
+    fn pnl_percent_of_liquidated_position_is_minus_100() {
+        let opening_price = dec!(20_000);
+        let quantity = 20_000.0;
+        let margin_leverage_1 = calculate_margin(opening_price, quantity, 1.0);
+        let margin_leverage_2 = calculate_margin(opening_price, quantity, 2.0);
+
+        let long_pnl_percent = calculate_pnl_percent(
+            opening_price,
+            dec!(10_000),
+            quantity,
+            Direction::Long,
+            margin_leverage_2,
+            margin_leverage_1,
+        )
+        .unwrap();
+
+        let short_pnl_percent = calculate_pnl_percent(
+            opening_price,
+            dec!(1_000_000),
+            quantity,
+            Direction::Short,
+            margin_leverage_1,
+            margin_leverage_2,
+        )
+        .unwrap();
+
+        assert_eq!(long_pnl_percent, dec!(-100));
+        assert_eq!(short_pnl_percent, dec!(-100));
+    }
+
+    #[test]
+    fn liquidation_price_long() {
+        let liquidation_price =
+            calculate_liquidation_price(dec!(30_000), dec!(2.0), Direction::Long);
+
+        assert_eq!(liquidation_price, dec!(20_000));
+    }
+
+    #[test]
+    fn liquidation_price_short() {
+        let liquidation_price =
+            calculate_liquidation_price(dec!(30_000), dec!(2.0), Direction::Short);
+
+        assert_eq!(liquidation_price, dec!(60_000));
+    }
+
+    #[test]
+    fn liquidation_price_short_without_leverage_is_max_price() {
+        let liquidation_price =
+            calculate_liquidation_price(dec!(30_000), dec!(1.0), Direction::Short);
+
+        assert_eq!(liquidation_price, Decimal::from(BTCUSD_MAX_PRICE));
+    }
+
+    #[test]
+    fn liquidation_price_with_fractional_f32_leverage() {
+        let leverage = Decimal::try_from(2.5_f32).unwrap();
+
+        let liquidation_price =
+            calculate_liquidation_price(dec!(35_000), leverage, Direction::Long);
+
+        assert_eq!(liquidation_price, dec!(25_000));
+    }
+
+    #[test]
+    fn liquidation_price_high_leverage_is_close_to_entry_price() {
+        let long = calculate_liquidation_price(dec!(50_500), dec!(100.0), Direction::Long);
+        let short = calculate_liquidation_price(dec!(49_500), dec!(100.0), Direction::Short);
+
+        assert_eq!(long, dec!(50_000));
+        assert_eq!(short, dec!(50_000));
+    }
+
     #[test]
     fn given_position_when_price_same_then_zero_pnl() {
         let opening_price = Decimal::from(20000);
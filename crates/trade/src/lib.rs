@@ -11,29 +11,84 @@ pub mod cfd;
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum ContractSymbol {
     BtcUsd,
+    EthUsd,
 }
 
 impl ContractSymbol {
+    /// All the contract symbols which can be traded.
+    pub const ALL: [ContractSymbol; 2] = [ContractSymbol::BtcUsd, ContractSymbol::EthUsd];
+
     pub fn label(self) -> String {
         match self {
             ContractSymbol::BtcUsd => "btcusd".to_string(),
+            ContractSymbol::EthUsd => "ethusd".to_string(),
+        }
+    }
+
+    /// The inverse of [`ContractSymbol::label`].
+    ///
+    /// Contrary to the [`FromStr`] implementation, only the exact label is accepted.
+    pub fn from_label(label: &str) -> Option<ContractSymbol> {
+        ContractSymbol::ALL
+            .into_iter()
+            .find(|symbol| symbol.label() == label)
+    }
+
+    /// The asset the contract is denominated in, e.g. `BTC` for `BTC/USD`.
+    pub fn base_currency(self) -> &'static str {
+        match self {
+            ContractSymbol::BtcUsd => "BTC",
+            ContractSymbol::EthUsd => "ETH",
+        }
+    }
+
+    /// The currency the contract is quoted in, e.g. `USD` for `BTC/USD`.
+    pub fn quote_currency(self) -> &'static str {
+        match self {
+            ContractSymbol::BtcUsd | ContractSymbol::EthUsd => "USD",
         }
     }
 }
 
+/// The direction of a position or an order.
+///
+/// The direction is serialized as `"Long"` or `"Short"`, which is what released apps expect, but
+/// the lowercase form returned by [`Direction::as_str`] is accepted as well.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum Direction {
+    #[serde(alias = "long")]
     Long,
+    #[serde(alias = "short")]
     Short,
 }
 
 impl Direction {
-    pub fn opposite(&self) -> Direction {
+    pub fn opposite(self) -> Direction {
         match self {
             Direction::Long => Direction::Short,
             Direction::Short => Direction::Long,
         }
     }
+
+    /// The lowercase form of the direction, e.g. `long`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Direction::Long => "long",
+            Direction::Short => "short",
+        }
+    }
+}
+
+impl FromStr for Direction {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "long" => Ok(Direction::Long),
+            "short" => Ok(Direction::Short),
+            unknown => bail!("Unknown direction {unknown}"),
+        }
+    }
 }
 
 impl fmt::Display for Direction {
@@ -73,6 +128,7 @@ impl FromStr for ContractSymbol {
             "btcusd" => Ok(ContractSymbol::BtcUsd),
             // BitMEX representation
             "xbtusd" => Ok(ContractSymbol::BtcUsd),
+            "ethusd" => Ok(ContractSymbol::EthUsd),
             unknown => bail!("Unknown contract symbol {unknown}"),
         }
     }
@@ -82,6 +138,7 @@ impl fmt::Display for ContractSymbol {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let symbol = match self {
             ContractSymbol::BtcUsd => "btcusd",
+            ContractSymbol::EthUsd => "ethusd",
         };
         symbol.to_string().fmt(f)
     }
@@ -90,6 +147,7 @@ impl fmt::Display for ContractSymbol {
 #[cfg(test)]
 mod tests {
     use crate::ContractSymbol;
+    use crate::Direction;
     use std::str::FromStr;
 
     #[test]
@@ -106,6 +164,69 @@ mod tests {
             ContractSymbol::from_str("xbtusd").unwrap(),
             ContractSymbol::BtcUsd
         );
+        assert_eq!(
+            ContractSymbol::from_str("ETHUSD").unwrap(),
+            ContractSymbol::EthUsd
+        );
         assert!(ContractSymbol::from_str("dogeusd").is_err());
     }
+
+    #[test]
+    fn contract_symbol_label_roundtrip() {
+        for symbol in ContractSymbol::ALL {
+            assert_eq!(ContractSymbol::from_label(&symbol.label()), Some(symbol));
+            assert_eq!(symbol.label(), symbol.to_string());
+        }
+
+        assert_eq!(ContractSymbol::from_label("xbtusd"), None);
+        assert_eq!(ContractSymbol::from_label("dogeusd"), None);
+    }
+
+    #[test]
+    fn contract_symbol_currencies() {
+        assert_eq!(ContractSymbol::BtcUsd.base_currency(), "BTC");
+        assert_eq!(ContractSymbol::EthUsd.base_currency(), "ETH");
+
+        for symbol in ContractSymbol::ALL {
+            assert_eq!(symbol.quote_currency(), "USD");
+        }
+    }
+
+    #[test]
+    fn direction_opposite() {
+        assert_eq!(Direction::Long.opposite(), Direction::Short);
+        assert_eq!(Direction::Short.opposite(), Direction::Long);
+    }
+
+    #[test]
+    fn direction_str_roundtrip() {
+        for direction in [Direction::Long, Direction::Short] {
+            assert_eq!(Direction::from_str(direction.as_str()).unwrap(), direction);
+            assert_eq!(
+                Direction::from_str(&direction.to_string()).unwrap(),
+                direction
+            );
+        }
+
+        assert!(Direction::from_str("sideways").is_err());
+    }
+
+    #[test]
+    fn direction_serde() {
+        assert_eq!(serde_json::to_string(&Direction::Long).unwrap(), "\"Long\"");
+
+        for direction in [Direction::Long, Direction::Short] {
+            let serialized = serde_json::to_string(&direction).unwrap();
+            assert_eq!(
+                serde_json::from_str::<Direction>(&serialized).unwrap(),
+                direction
+            );
+
+            let lowercase = format!("\"{}\"", direction.as_str());
+            assert_eq!(
+                serde_json::from_str::<Direction>(&lowercase).unwrap(),
+                direction
+            );
+        }
+    }
 }
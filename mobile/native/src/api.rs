@@ -268,6 +268,7 @@ pub fn calculate_quantity(price: f32, margin: u64, leverage: f32) -> SyncReturn<
 #[derive(Debug, Clone, Copy)]
 pub enum _ContractSymbol {
     BtcUsd,
+    EthUsd,
 }
 
 #[allow(dead_code)]
@@ -427,6 +428,7 @@ pub fn run_in_flutter(seed_dir: String, fcm_token: String) -> Result<()> {
                     fcm_token: Some(fcm_token),
                     version: Some(version),
                     signature,
+                    cancel_on_disconnect: false,
                 })
             })?;
         }
@@ -54,12 +54,9 @@ pub fn calculate_liquidation_price(price: f32, leverage: f32, direction: Directi
 
     tracing::trace!("Initial price: {}", price);
 
-    let leverage = Decimal::try_from(leverage).expect("leverage to fix into decimal");
+    let leverage = Decimal::try_from(leverage).expect("leverage to fit into decimal");
 
-    let liquidation_price = match direction {
-        Direction::Long => cfd::calculate_long_liquidation_price(leverage, initial_price),
-        Direction::Short => cfd::calculate_short_liquidation_price(leverage, initial_price),
-    };
+    let liquidation_price = cfd::calculate_liquidation_price(initial_price, leverage, direction);
 
     let liquidation_price = liquidation_price.to_f32().expect("price to fit into f32");
     tracing::trace!("Liquidation_price: {liquidation_price}");
@@ -99,6 +99,7 @@ impl ToSql<Text, Sqlite> for ContractSymbol {
     fn to_sql(&self, out: &mut Output<Sqlite>) -> serialize::Result {
         let text = match *self {
             ContractSymbol::BtcUsd => "BtcUsd",
+            ContractSymbol::EthUsd => "EthUsd",
         };
         out.set_value(text);
         Ok(IsNull::No)
@@ -111,6 +112,7 @@ impl FromSql<Text, Sqlite> for ContractSymbol {
 
         return match string.as_str() {
             "BtcUsd" => Ok(ContractSymbol::BtcUsd),
+            "EthUsd" => Ok(ContractSymbol::EthUsd),
             _ => Err("Unrecognized enum variant".into()),
         };
     }
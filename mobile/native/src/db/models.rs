@@ -472,12 +472,14 @@ impl From<PositionState> for crate::trade::position::PositionState {
 #[diesel(sql_type = Text)]
 pub enum ContractSymbol {
     BtcUsd,
+    EthUsd,
 }
 
 impl From<trade::ContractSymbol> for ContractSymbol {
     fn from(value: trade::ContractSymbol) -> Self {
         match value {
             trade::ContractSymbol::BtcUsd => ContractSymbol::BtcUsd,
+            trade::ContractSymbol::EthUsd => ContractSymbol::EthUsd,
         }
     }
 }
@@ -486,6 +488,7 @@ impl From<ContractSymbol> for trade::ContractSymbol {
     fn from(value: ContractSymbol) -> Self {
         match value {
             ContractSymbol::BtcUsd => trade::ContractSymbol::BtcUsd,
+            ContractSymbol::EthUsd => trade::ContractSymbol::EthUsd,
         }
     }
 }
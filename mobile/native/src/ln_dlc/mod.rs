@@ -1033,6 +1033,8 @@ fn ln_dlc_node_settings() -> LnDlcNodeSettings {
         fee_rate_sync_interval: Duration::from_secs(20),
         sub_channel_manager_periodic_check_interval: Duration::from_secs(30),
         shadow_sync_interval: Duration::from_secs(600),
+        oracle_cache_ttl: Duration::from_secs(60),
+        oracle_negative_cache_ttl: Duration::from_secs(5),
     }
 }
 
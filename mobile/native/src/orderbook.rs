@@ -22,6 +22,7 @@ use commons::OrderState;
 use commons::OrderbookRequest;
 use commons::Prices;
 use commons::Signature;
+use commons::HEARTBEAT_INTERVAL;
 use futures::SinkExt;
 use futures::TryStreamExt;
 use parking_lot::Mutex;
@@ -151,6 +152,20 @@ pub fn subscribe(
                         }
                     });
 
+                    // Lets the coordinator know that we are still around, e.g. to keep our dead
+                    // man's switch from firing.
+                    let heartbeat = tokio::spawn({
+                        let tx_websocket = tx_websocket.clone();
+                        async move {
+                            loop {
+                                tokio::time::sleep(HEARTBEAT_INTERVAL).await;
+                                if let Err(e) = tx_websocket.send(OrderbookRequest::Heartbeat) {
+                                    tracing::warn!("Failed to send heartbeat: {e:#}");
+                                }
+                            }
+                        }
+                    });
+
                     let mut cached_best_price: Prices = HashMap::new();
                     loop {
                         let msg = match stream.try_next().await {
@@ -177,6 +192,7 @@ pub fn subscribe(
 
                     // abort handler on sending messages over a lost websocket connection.
                     handle.abort();
+                    heartbeat.abort();
                 }
                 Err(e) => {
                     tracing::error!("Could not start up orderbook client: {e:#}");
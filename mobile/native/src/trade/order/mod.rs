@@ -204,6 +204,7 @@ impl From<Order> for commons::NewOrder {
             order_type: order.order_type.into(),
             expiry: order.order_expiry_timestamp,
             stable: order.stable,
+            auto_rollover: true,
         }
     }
 }
@@ -67,8 +67,19 @@ pub fn set_position_state(state: PositionState) -> Result<()> {
 /// - Opening a new position.
 /// - Resizing a new position.
 pub fn handle_channel_renewal_offer(expiry_timestamp: OffsetDateTime) -> Result<()> {
-    // FIXME: This won't always be true once we reintroduce position resizing.
     if let Some(position) = db::get_positions()?.first() {
+        // An order in `Filling` means that the coordinator is resizing our position, e.g. to close
+        // part of it.
+        if db::get_order_in_filling()?.is_some() {
+            tracing::debug!("Setting position to resizing");
+            db::update_position_state(position.contract_symbol, PositionState::Resizing)?;
+            let mut position = position.clone();
+            position.position_state = PositionState::Resizing;
+            event::publish(&EventInternal::PositionUpdateNotification(position));
+
+            return Ok(());
+        }
+
         tracing::debug!("Setting position to rollover");
         db::rollover_position(position.contract_symbol, expiry_timestamp)?;
         let mut position = position.clone();